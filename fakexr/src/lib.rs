@@ -118,6 +118,22 @@ pub fn set_grip(session: xr::Session, path: UserPath, pose: xr::Posef) {
     get_hand_data(path, &session).grip_pose.store(pose);
 }
 
+/// Poses bound to this hand will be extrapolated with this velocity, based on the requested time.
+pub fn set_linear_velocity(session: xr::Session, path: UserPath, velocity: xr::Vector3f) {
+    let session = session.to_handle().unwrap();
+    get_hand_data(path, &session)
+        .linear_velocity
+        .store(velocity);
+}
+
+/// Reported as the angular velocity of poses bound to this hand, in radians per second.
+pub fn set_angular_velocity(session: xr::Session, path: UserPath, velocity: xr::Vector3f) {
+    let session = session.to_handle().unwrap();
+    get_hand_data(path, &session)
+        .angular_velocity
+        .store(velocity);
+}
+
 pub fn set_aim(session: xr::Session, path: UserPath, pose: xr::Posef) {
     let session = session.to_handle().unwrap();
     get_hand_data(path, &session).aim_pose.store(pose);
@@ -148,6 +164,123 @@ pub fn session_frame_state(session: xr::Session) -> FrameState {
     session.frame_state.load()
 }
 
+/// Simulates the runtime ending the session on its own, e.g. the user quitting from the
+/// runtime's UI.
+pub fn stop_session(session: xr::Session) {
+    let sess = session.to_handle().unwrap();
+    send_event(
+        &sess.event_sender,
+        xr::EventDataSessionStateChanged {
+            ty: xr::EventDataSessionStateChanged::TYPE,
+            next: std::ptr::null(),
+            session,
+            state: xr::SessionState::STOPPING,
+            time: xr::Time::from_nanos(0),
+        },
+        None,
+    );
+}
+
+/// Simulates the runtime moving the session to a different state, e.g. VISIBLE when its
+/// dashboard is opened.
+pub fn set_session_state(session: xr::Session, state: xr::SessionState) {
+    let sess = session.to_handle().unwrap();
+    send_event(
+        &sess.event_sender,
+        xr::EventDataSessionStateChanged {
+            ty: xr::EventDataSessionStateChanged::TYPE,
+            next: std::ptr::null(),
+            session,
+            state,
+            time: xr::Time::from_nanos(0),
+        },
+        None,
+    );
+}
+
+/// Simulates the runtime moving a reference space, e.g. because the user recentered.
+pub fn change_reference_space(session: xr::Session, ty: xr::ReferenceSpaceType) {
+    let sess = session.to_handle().unwrap();
+    send_event(
+        &sess.event_sender,
+        xr::EventDataReferenceSpaceChangePending {
+            ty: xr::EventDataReferenceSpaceChangePending::TYPE,
+            next: std::ptr::null(),
+            session,
+            reference_space_type: ty,
+            change_time: xr::Time::from_nanos(0),
+            pose_valid: false.into(),
+            pose_in_previous_space: xr::Posef::IDENTITY,
+        },
+        None,
+    );
+}
+
+/// Simulates the HMD's proximity sensor changing state (XR_EXT_user_presence).
+pub fn set_user_presence(session: xr::Session, present: bool) {
+    let sess = session.to_handle().unwrap();
+    send_event(
+        &sess.event_sender,
+        xr::EventDataUserPresenceChangedEXT {
+            ty: xr::EventDataUserPresenceChangedEXT::TYPE,
+            next: std::ptr::null(),
+            session,
+            is_user_present: present.into(),
+        },
+        None,
+    );
+}
+
+/// The types of the layers passed to the last xrEndFrame call.
+pub fn submitted_layer_types(session: xr::Session) -> Vec<xr::StructureType> {
+    let session = session.to_handle().unwrap();
+    session.submitted_layers.lock().unwrap().clone()
+}
+
+/// The (near_z, far_z) of every depth info chained onto a projection view in the last
+/// xrEndFrame call.
+pub fn submitted_depth_ranges(session: xr::Session) -> Vec<(f32, f32)> {
+    let session = session.to_handle().unwrap();
+    session.submitted_depth_ranges.lock().unwrap().clone()
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct HapticVibration {
+    pub duration: xr::Duration,
+    pub frequency: f32,
+    pub amplitude: f32,
+}
+
+/// The parameters of the last haptic vibration applied in this session.
+pub fn last_haptic_vibration(session: xr::Session) -> Option<HapticVibration> {
+    let session = session.to_handle().unwrap();
+    *session.last_haptic.lock().unwrap()
+}
+
+/// Sets the FOVs reported by xrLocateViews for the left and right eye.
+pub fn set_view_fovs(session: xr::Session, fovs: [xr::Fovf; 2]) {
+    let session = session.to_handle().unwrap();
+    *session.view_fovs.lock().unwrap() = fovs;
+}
+
+/// Sets the STAGE bounds reported by xrGetReferenceSpaceBoundsRect - None if they're unavailable.
+pub fn set_stage_bounds(session: xr::Session, bounds: Option<xr::Extent2Df>) {
+    let session = session.to_handle().unwrap();
+    *session.stage_bounds.lock().unwrap() = bounds;
+}
+
+/// Sets the formats reported by xrEnumerateSwapchainFormats, in order of preference.
+pub fn set_swapchain_formats(session: xr::Session, formats: &[i64]) {
+    let session = session.to_handle().unwrap();
+    *session.swapchain_formats.lock().unwrap() = formats.to_vec();
+}
+
+/// The environment blend mode passed to the last xrEndFrame call.
+pub fn submitted_blend_mode(session: xr::Session) -> xr::EnvironmentBlendMode {
+    let session = session.to_handle().unwrap();
+    session.submitted_blend_mode.load()
+}
+
 macro_rules! fn_unimplemented_impl {
     ($($param:ident),+) => {
         fn_unimplemented_impl!($($param),+  -> []);
@@ -272,9 +405,9 @@ pub unsafe extern "system" fn get_instance_proc_addr(
                 CreateActionSpace,
                 LocateSpace,
                 (EnumerateViewConfigurations),
-                (EnumerateEnvironmentBlendModes),
+                EnumerateEnvironmentBlendModes,
                 (GetViewConfigurationProperties),
-                (EnumerateViewConfigurationViews),
+                EnumerateViewConfigurationViews,
                 BeginFrame,
                 EndFrame,
                 WaitFrame,
@@ -283,7 +416,7 @@ pub unsafe extern "system" fn get_instance_proc_addr(
                 (PollEvent),
                 StringToPath,
                 PathToString,
-                (GetReferenceSpaceBoundsRect),
+                GetReferenceSpaceBoundsRect,
                 GetActionStateBoolean,
                 GetActionStateFloat,
                 GetActionStateVector2f,
@@ -318,20 +451,28 @@ extern "system" fn enumerate_instance_extension_properties(
     properties: *mut xr::ExtensionProperties,
 ) -> xr::Result {
     assert!(layer_name.is_null());
-    unsafe { *property_count_output = 1 };
+    const EXTENSIONS: [&[u8]; 5] = [
+        xr::KHR_VULKAN_ENABLE_EXTENSION_NAME,
+        xr::EXT_USER_PRESENCE_EXTENSION_NAME,
+        xr::META_TOUCH_CONTROLLER_PLUS_EXTENSION_NAME,
+        xr::EXT_EYE_GAZE_INTERACTION_EXTENSION_NAME,
+        xr::KHR_COMPOSITION_LAYER_DEPTH_EXTENSION_NAME,
+    ];
+    unsafe { *property_count_output = EXTENSIONS.len() as u32 };
     if property_capacity_input > 0 {
         let props =
             unsafe { std::slice::from_raw_parts_mut(properties, property_capacity_input as usize) };
-        props[0] = xr::ExtensionProperties {
-            ty: xr::ExtensionProperties::TYPE,
-            next: std::ptr::null_mut(),
-            extension_name: [0 as c_char; xr::MAX_EXTENSION_NAME_SIZE],
-            extension_version: 1,
-        };
-        let name = xr::KHR_VULKAN_ENABLE_EXTENSION_NAME;
-        let name =
-            unsafe { std::slice::from_raw_parts(name.as_ptr() as *const c_char, name.len()) };
-        props[0].extension_name[..name.len()].copy_from_slice(name);
+        for (prop, name) in props.iter_mut().zip(EXTENSIONS) {
+            *prop = xr::ExtensionProperties {
+                ty: xr::ExtensionProperties::TYPE,
+                next: std::ptr::null_mut(),
+                extension_name: [0 as c_char; xr::MAX_EXTENSION_NAME_SIZE],
+                extension_version: 1,
+            };
+            let name =
+                unsafe { std::slice::from_raw_parts(name.as_ptr() as *const c_char, name.len()) };
+            prop.extension_name[..name.len()].copy_from_slice(name);
+        }
     }
     xr::Result::SUCCESS
 }
@@ -429,6 +570,8 @@ struct HandData {
     profile: AtomicCell<xr::Path>,
     grip_pose: AtomicCell<xr::Posef>,
     aim_pose: AtomicCell<xr::Posef>,
+    linear_velocity: AtomicCell<xr::Vector3f>,
+    angular_velocity: AtomicCell<xr::Vector3f>,
 }
 
 impl Default for HandData {
@@ -438,6 +581,8 @@ impl Default for HandData {
             profile: Default::default(),
             grip_pose: xr::Posef::IDENTITY.into(),
             aim_pose: xr::Posef::IDENTITY.into(),
+            linear_velocity: Default::default(),
+            angular_velocity: Default::default(),
         }
     }
 }
@@ -461,6 +606,13 @@ struct Session {
     state_synced: AtomicBool,
     should_render: AtomicBool,
     frame_state: AtomicCell<FrameState>,
+    submitted_layers: Mutex<Vec<xr::StructureType>>,
+    submitted_depth_ranges: Mutex<Vec<(f32, f32)>>,
+    submitted_blend_mode: AtomicCell<xr::EnvironmentBlendMode>,
+    view_fovs: Mutex<[xr::Fovf; 2]>,
+    last_haptic: Mutex<Option<HapticVibration>>,
+    swapchain_formats: Mutex<Vec<i64>>,
+    stage_bounds: Mutex<Option<xr::Extent2Df>>,
 }
 
 impl Session {
@@ -574,12 +726,20 @@ struct Space {
 }
 
 impl Space {
-    fn get_pose_relative_to_local(&self) -> Result<xr::SpaceLocation, xr::Result> {
-        let default = || xr::SpaceLocation {
-            ty: xr::SpaceLocation::TYPE,
-            next: std::ptr::null_mut(),
-            location_flags: xr::SpaceLocationFlags::default(),
-            pose: xr::Posef::default(),
+    fn get_pose_relative_to_local(
+        &self,
+        time: xr::Time,
+    ) -> Result<(xr::SpaceLocation, xr::SpaceVelocity), xr::Result> {
+        let default = || {
+            (
+                xr::SpaceLocation {
+                    ty: xr::SpaceLocation::TYPE,
+                    next: std::ptr::null_mut(),
+                    location_flags: xr::SpaceLocationFlags::default(),
+                    pose: xr::Posef::default(),
+                },
+                default_velocity(),
+            )
         };
         let session = self
             .session
@@ -640,7 +800,7 @@ impl Space {
             })
             .unwrap_or_else(|| panic!("expected binding for space for action {:?}", action.name));
 
-        let pose = match binding.strip_prefix(hand.as_path()).unwrap() {
+        let mut pose = match binding.strip_prefix(hand.as_path()).unwrap() {
             "/input/grip/pose" => hand_data.grip_pose.load(),
             "/input/aim/pose" => hand_data.aim_pose.load(),
             other => panic!(
@@ -649,17 +809,38 @@ impl Space {
             ),
         };
 
+        let velocity = hand_data.linear_velocity.load();
+        let secs = time.as_nanos() as f32 / 1_000_000_000.0;
+        pose.position.x += velocity.x * secs;
+        pose.position.y += velocity.y * secs;
+        pose.position.z += velocity.z * secs;
+
         let mat = pose_to_mat(pose);
         let offset = pose_to_mat(self.offset);
 
         let ret = mat_to_pose(mat * offset);
 
-        Ok(xr::SpaceLocation {
-            ty: xr::SpaceLocation::TYPE,
-            next: std::ptr::null_mut(),
-            location_flags: *LOCATION_FLAGS_TRACKED,
-            pose: ret,
-        })
+        // The offset point is swept around by the angular velocity as well.
+        let angular = vec3(hand_data.angular_velocity.load());
+        let lever = mat.transform_vector3(vec3(self.offset.position));
+        let linear = vec3(velocity) + angular.cross(lever);
+
+        Ok((
+            xr::SpaceLocation {
+                ty: xr::SpaceLocation::TYPE,
+                next: std::ptr::null_mut(),
+                location_flags: *LOCATION_FLAGS_TRACKED,
+                pose: ret,
+            },
+            xr::SpaceVelocity {
+                ty: xr::SpaceVelocity::TYPE,
+                next: std::ptr::null_mut(),
+                velocity_flags: xr::SpaceVelocityFlags::LINEAR_VALID
+                    | xr::SpaceVelocityFlags::ANGULAR_VALID,
+                linear_velocity: xr_vec3(linear),
+                angular_velocity: xr_vec3(angular),
+            },
+        ))
     }
 }
 
@@ -793,6 +974,13 @@ extern "system" fn create_session(
         state_synced: true.into(),
         should_render: false.into(),
         frame_state: FrameState::Ended.into(),
+        submitted_layers: Default::default(),
+        submitted_depth_ranges: Default::default(),
+        submitted_blend_mode: xr::EnvironmentBlendMode::OPAQUE.into(),
+        view_fovs: Default::default(),
+        last_haptic: Default::default(),
+        swapchain_formats: vec![0].into(),
+        stage_bounds: Default::default(),
     });
 
     let tx = sess.event_sender.clone();
@@ -1436,7 +1624,7 @@ extern "system" fn get_current_interaction_profile(
 extern "system" fn locate_space(
     space: xr::Space,
     base_space: xr::Space,
-    _time: xr::Time,
+    time: xr::Time,
     location: *mut xr::SpaceLocation,
 ) -> xr::Result {
     let base_space = get_handle!(base_space);
@@ -1460,41 +1648,31 @@ extern "system" fn locate_space(
         location_flags: xr::SpaceLocationFlags::EMPTY,
         pose: xr::Posef::IDENTITY,
     };
+    let mut out_velo = default_velocity();
 
-    if !next.is_null() {
-        let header = next as *mut xr::BaseOutStructure;
-        unsafe {
-            if (*header).ty == xr::SpaceVelocity::TYPE {
-                let velo = next as *mut xr::SpaceVelocity;
-                velo.write(xr::SpaceVelocity {
-                    ty: xr::SpaceVelocity::TYPE,
-                    next: (*velo).next,
-                    velocity_flags: xr::SpaceVelocityFlags::EMPTY,
-                    linear_velocity: Default::default(),
-                    angular_velocity: Default::default(),
-                });
-                out_loc.next = velo as _;
-            }
-        }
-    }
     if matches!(
         base_space.ty,
         SpaceType::Reference(xr::ReferenceSpaceType::LOCAL)
     ) {
-        match space.get_pose_relative_to_local() {
-            Ok(loc) => {
-                out_loc = loc;
-            }
+        let (loc, velo) = match space.get_pose_relative_to_local(time) {
+            Ok(ret) => ret,
             Err(e) => return e,
         };
+
+        if loc.location_flags.contains(*LOCATION_FLAGS_TRACKED) {
+            out_loc.location_flags = loc.location_flags;
+            let base_mat = pose_to_mat(base_space.offset);
+            out_loc.pose = mat_to_pose(base_mat.inverse() * pose_to_mat(loc.pose));
+        }
+        out_velo = relative_velocity(pose_rotation(base_space.offset), velo, None);
     } else {
-        let base_loc = match base_space.get_pose_relative_to_local() {
-            Ok(loc) => loc,
+        let (base_loc, base_velo) = match base_space.get_pose_relative_to_local(time) {
+            Ok(ret) => ret,
             Err(e) => return e,
         };
 
-        let target_loc = match space.get_pose_relative_to_local() {
-            Ok(loc) => loc,
+        let (target_loc, target_velo) = match space.get_pose_relative_to_local(time) {
+            Ok(ret) => ret,
             Err(e) => return e,
         };
 
@@ -1507,6 +1685,19 @@ extern "system" fn locate_space(
 
             let out_mat = base_mat.inverse() * target_mat;
             out_loc.pose = mat_to_pose(out_mat);
+            out_velo =
+                relative_velocity(pose_rotation(base_loc.pose), target_velo, Some(base_velo));
+        }
+    }
+
+    if !next.is_null() {
+        let header = next as *mut xr::BaseOutStructure;
+        unsafe {
+            if (*header).ty == xr::SpaceVelocity::TYPE {
+                let velo = next as *mut xr::SpaceVelocity;
+                out_velo.next = (*velo).next;
+                velo.write(out_velo);
+            }
         }
     }
 
@@ -1540,17 +1731,97 @@ extern "system" fn destroy_swapchain(swapchain: xr::Swapchain) -> xr::Result {
 }
 
 extern "system" fn enumerate_swapchain_formats(
-    _session: xr::Session,
+    session: xr::Session,
     capacity: u32,
     output: *mut u32,
     formats: *mut i64,
 ) -> xr::Result {
+    let session = get_handle!(session);
+    let supported = session.swapchain_formats.lock().unwrap();
     unsafe {
-        output.write(1);
+        output.write(supported.len() as u32);
     }
-    if capacity >= 1 {
+    if capacity > 0 && capacity as usize >= supported.len() {
         let formats = unsafe { std::slice::from_raw_parts_mut(formats, capacity as usize) };
-        formats[0] = 0;
+        formats[..supported.len()].copy_from_slice(&supported);
+    }
+
+    xr::Result::SUCCESS
+}
+
+extern "system" fn get_reference_space_bounds_rect(
+    session: xr::Session,
+    ty: xr::ReferenceSpaceType,
+    bounds: *mut xr::Extent2Df,
+) -> xr::Result {
+    let session = get_handle!(session);
+    let stage_bounds = *session.stage_bounds.lock().unwrap();
+    let (extent, result) = match stage_bounds.filter(|_| ty == xr::ReferenceSpaceType::STAGE) {
+        Some(extent) => (extent, xr::Result::SUCCESS),
+        None => (
+            xr::Extent2Df {
+                width: 0.0,
+                height: 0.0,
+            },
+            xr::Result::SPACE_BOUNDS_UNAVAILABLE,
+        ),
+    };
+    unsafe {
+        bounds.write(extent);
+    }
+    result
+}
+
+extern "system" fn enumerate_environment_blend_modes(
+    _instance: xr::Instance,
+    _system_id: xr::SystemId,
+    _view_configuration_type: xr::ViewConfigurationType,
+    capacity: u32,
+    output: *mut u32,
+    modes: *mut xr::EnvironmentBlendMode,
+) -> xr::Result {
+    const MODES: [xr::EnvironmentBlendMode; 2] = [
+        xr::EnvironmentBlendMode::OPAQUE,
+        xr::EnvironmentBlendMode::ALPHA_BLEND,
+    ];
+    unsafe {
+        output.write(MODES.len() as u32);
+    }
+    if capacity > 0 {
+        let modes = unsafe { std::slice::from_raw_parts_mut(modes, capacity as usize) };
+        for (out, mode) in modes.iter_mut().zip(MODES) {
+            *out = mode;
+        }
+    }
+
+    xr::Result::SUCCESS
+}
+
+/// The recommended and max image rect sizes reported for each view.
+pub const RECOMMENDED_VIEW_SIZE: (u32, u32) = (1000, 1100);
+pub const MAX_VIEW_SIZE: (u32, u32) = (2000, 2200);
+
+extern "system" fn enumerate_view_configuration_views(
+    _instance: xr::Instance,
+    _system_id: xr::SystemId,
+    _view_configuration_type: xr::ViewConfigurationType,
+    capacity: u32,
+    output: *mut u32,
+    views: *mut xr::ViewConfigurationView,
+) -> xr::Result {
+    unsafe {
+        output.write(2);
+    }
+    if capacity > 0 {
+        let views = unsafe { std::slice::from_raw_parts_mut(views, capacity as usize) };
+        for view in views.iter_mut().take(2) {
+            view.recommended_image_rect_width = RECOMMENDED_VIEW_SIZE.0;
+            view.recommended_image_rect_height = RECOMMENDED_VIEW_SIZE.1;
+            view.max_image_rect_width = MAX_VIEW_SIZE.0;
+            view.max_image_rect_height = MAX_VIEW_SIZE.1;
+            view.recommended_swapchain_sample_count = 1;
+            view.max_swapchain_sample_count = 1;
+        }
     }
 
     xr::Result::SUCCESS
@@ -1633,11 +1904,39 @@ extern "system" fn begin_frame(
     xr::Result::SUCCESS
 }
 
-extern "system" fn end_frame(session: xr::Session, _info: *const xr::FrameEndInfo) -> xr::Result {
+extern "system" fn end_frame(session: xr::Session, info: *const xr::FrameEndInfo) -> xr::Result {
     let session = get_handle!(session);
     if let Err(e) = transition_frame_state(&session.frame_state, FrameState::Ended) {
         return e;
     }
+    let info = unsafe { info.as_ref() }.unwrap();
+    let layers: &[*const xr::CompositionLayerBaseHeader] = if info.layers.is_null() {
+        &[]
+    } else {
+        unsafe { std::slice::from_raw_parts(info.layers, info.layer_count as usize) }
+    };
+    *session.submitted_layers.lock().unwrap() =
+        layers.iter().map(|layer| unsafe { (**layer).ty }).collect();
+    *session.submitted_depth_ranges.lock().unwrap() = layers
+        .iter()
+        .filter(|layer| unsafe { (***layer).ty } == xr::StructureType::COMPOSITION_LAYER_PROJECTION)
+        .flat_map(|layer| {
+            let proj = unsafe { &*layer.cast::<xr::CompositionLayerProjection>() };
+            unsafe { std::slice::from_raw_parts(proj.views, proj.view_count as usize) }
+        })
+        .filter_map(|view| {
+            let depth = unsafe {
+                view.next
+                    .cast::<xr::CompositionLayerDepthInfoKHR>()
+                    .as_ref()
+            }?;
+            (depth.ty == xr::CompositionLayerDepthInfoKHR::TYPE)
+                .then_some((depth.near_z, depth.far_z))
+        })
+        .collect();
+    session
+        .submitted_blend_mode
+        .store(info.environment_blend_mode);
     if session.state.load() == xr::SessionState::READY {
         session.synchronized();
     }
@@ -1652,7 +1951,7 @@ extern "system" fn locate_views(
     output: *mut u32,
     views: *mut xr::View,
 ) -> xr::Result {
-    let _session = get_handle!(session);
+    let session = get_handle!(session);
     if !state.is_null() {
         unsafe {
             state.write(xr::ViewState {
@@ -1673,14 +1972,15 @@ extern "system" fn locate_views(
             return xr::Result::ERROR_SIZE_INSUFFICIENT;
         }
         let views = unsafe { std::slice::from_raw_parts_mut(views, capacity as usize) };
-        let view = xr::View {
-            ty: xr::View::TYPE,
-            next: std::ptr::null_mut(),
-            pose: xr::Posef::default(),
-            fov: xr::Fovf::default(),
-        };
-        views[0] = view;
-        views[1] = view;
+        let fovs = *session.view_fovs.lock().unwrap();
+        for (view, fov) in views.iter_mut().zip(fovs) {
+            *view = xr::View {
+                ty: xr::View::TYPE,
+                next: std::ptr::null_mut(),
+                pose: xr::Posef::default(),
+                fov,
+            };
+        }
     }
 
     xr::Result::SUCCESS
@@ -1698,6 +1998,60 @@ fn pose_to_mat(
     )
 }
 
+fn pose_rotation(pose: xr::Posef) -> Quat {
+    let r = pose.orientation;
+    Quat::from_xyzw(r.x, r.y, r.z, r.w)
+}
+
+fn default_velocity() -> xr::SpaceVelocity {
+    xr::SpaceVelocity {
+        ty: xr::SpaceVelocity::TYPE,
+        next: std::ptr::null_mut(),
+        velocity_flags: xr::SpaceVelocityFlags::EMPTY,
+        linear_velocity: Default::default(),
+        angular_velocity: Default::default(),
+    }
+}
+
+/// Expresses a velocity relative to local in the frame of a base space with the given orientation.
+/// The motion of the base space itself is only accounted for linearly.
+fn relative_velocity(
+    base_rotation: Quat,
+    target: xr::SpaceVelocity,
+    base: Option<xr::SpaceVelocity>,
+) -> xr::SpaceVelocity {
+    let (base_linear, base_angular, base_flags) = base
+        .map(|b| {
+            (
+                vec3(b.linear_velocity),
+                vec3(b.angular_velocity),
+                b.velocity_flags,
+            )
+        })
+        .unwrap_or((Vec3::ZERO, Vec3::ZERO, target.velocity_flags));
+
+    let inverse = base_rotation.inverse();
+    xr::SpaceVelocity {
+        ty: xr::SpaceVelocity::TYPE,
+        next: std::ptr::null_mut(),
+        velocity_flags: target.velocity_flags & base_flags,
+        linear_velocity: xr_vec3(inverse * (vec3(target.linear_velocity) - base_linear)),
+        angular_velocity: xr_vec3(inverse * (vec3(target.angular_velocity) - base_angular)),
+    }
+}
+
+fn vec3(v: xr::Vector3f) -> Vec3 {
+    Vec3::new(v.x, v.y, v.z)
+}
+
+fn xr_vec3(v: Vec3) -> xr::Vector3f {
+    xr::Vector3f {
+        x: v.x,
+        y: v.y,
+        z: v.z,
+    }
+}
+
 fn mat_to_pose(mat: Affine3A) -> xr::Posef {
     let (_, rot, pos) = mat.to_scale_rotation_translation();
     xr::Posef {
@@ -1755,6 +2109,12 @@ extern "system" fn apply_haptic_feedback(
     }
 
     hand_state.state = ActionState::Haptic(true);
+    let vibration = unsafe { (haptic_feedback as *const xr::HapticVibration).read() };
+    *session.last_haptic.lock().unwrap() = Some(HapticVibration {
+        duration: vibration.duration,
+        frequency: vibration.frequency,
+        amplitude: vibration.amplitude,
+    });
 
     let instance = session.instance.upgrade().unwrap();
 
@@ -6,9 +6,10 @@ pub fn space_relation_to_openvr_pose(
     location: xr::SpaceLocation,
     velocity: xr::SpaceVelocity,
 ) -> TrackedDevicePose_t {
-    if !location.location_flags.contains(
-        xr::SpaceLocationFlags::POSITION_VALID | xr::SpaceLocationFlags::ORIENTATION_VALID,
-    ) {
+    if !location
+        .location_flags
+        .contains(xr::SpaceLocationFlags::ORIENTATION_VALID)
+    {
         return TrackedDevicePose_t {
             bPoseIsValid: false,
             bDeviceIsConnected: false,
@@ -19,6 +20,11 @@ pub fn space_relation_to_openvr_pose(
         };
     }
 
+    // With only orientation (i.e. positional tracking lost), the device is still there, but its
+    // pose can't be trusted.
+    let tracked = location
+        .location_flags
+        .contains(xr::SpaceLocationFlags::POSITION_VALID);
     let location = HmdMatrix34_t::from(location.pose);
     let linear_velo = velocity
         .velocity_flags
@@ -33,8 +39,12 @@ pub fn space_relation_to_openvr_pose(
         mDeviceToAbsoluteTracking: location,
         vVelocity: linear_velo.unwrap_or_default(),
         vAngularVelocity: angular_velo.unwrap_or_default(),
-        eTrackingResult: ETrackingResult::Running_OK,
-        bPoseIsValid: true,
+        eTrackingResult: if tracked {
+            ETrackingResult::Running_OK
+        } else {
+            ETrackingResult::Running_OutOfRange
+        },
+        bPoseIsValid: tracked,
         bDeviceIsConnected: true,
     }
 }
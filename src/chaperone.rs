@@ -55,17 +55,24 @@ impl vr::IVRChaperone004_Interface for Chaperone {
         crate::warn_unimplemented!("ReloadInfo");
     }
     fn GetPlayAreaRect(&self, rect: *mut vr::HmdQuad_t) -> bool {
-        crate::warn_unimplemented!("GetPlayAreaRect");
-        unsafe {
-            *rect = Default::default();
-        }
-        false
+        let Some(rect) = (unsafe { rect.as_mut() }) else {
+            return false;
+        };
+        let (width, depth) = self.openxr.session_data.get().play_area_size();
+        let (x, z) = (width / 2.0, depth / 2.0);
+        // Centered on the standing origin, on the floor.
+        rect.vCorners =
+            [(-x, -z), (x, -z), (x, z), (-x, z)].map(|(x, z)| vr::HmdVector3_t { v: [x, 0.0, z] });
+        true
     }
     fn GetPlayAreaSize(&self, size_x: *mut f32, size_z: *mut f32) -> bool {
-        crate::warn_unimplemented!("GetPlayAreaSize");
+        if size_x.is_null() || size_z.is_null() {
+            return false;
+        }
+        let (width, depth) = self.openxr.session_data.get().play_area_size();
         unsafe {
-            *size_x = 1.0;
-            *size_z = 1.0;
+            *size_x = width;
+            *size_z = depth;
         };
         true
     }
@@ -73,3 +80,41 @@ impl vr::IVRChaperone004_Interface for Chaperone {
         vr::ChaperoneCalibrationState::OK
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clientcore::Injector;
+    use crate::openxr_data::OpenXrData;
+    use openxr as xr;
+    use vr::IVRChaperone004_Interface;
+
+    #[test]
+    fn play_area_size_from_stage_bounds() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let chaperone = Chaperone::new(xr.clone());
+        let size = || {
+            let (mut x, mut z) = (0.0, 0.0);
+            assert!(chaperone.GetPlayAreaSize(&mut x, &mut z));
+            (x, z)
+        };
+
+        // No bounds from the runtime
+        assert_eq!(size(), (2.0, 2.0));
+
+        let session = xr.session_data.get().session.as_raw();
+        fakexr::set_stage_bounds(
+            session,
+            Some(xr::Extent2Df {
+                width: 3.0,
+                height: 2.5,
+            }),
+        );
+        assert_eq!(size(), (3.0, 2.5));
+
+        let mut rect = vr::HmdQuad_t::default();
+        assert!(chaperone.GetPlayAreaRect(&mut rect));
+        assert_eq!(rect.vCorners[0].v, [-1.5, 0.0, -1.25]);
+        assert_eq!(rect.vCorners[2].v, [1.5, 0.0, 1.25]);
+    }
+}
@@ -4,21 +4,26 @@ use crate::{
     input::Input,
     openxr_data::{self, FrameStream, OpenXrData, SessionCreateInfo, SessionData},
     overlay::OverlayMan,
+    perf_hud::{PerfHud, HUD_HEIGHT, HUD_WIDTH},
     system::System,
-    tracy_span, AtomicF64,
+    tracy_span,
 };
 
+#[cfg(test)]
+use crate::graphics_backends::TextureFormat;
+use glam::Quat;
 use log::{debug, info, trace, warn};
 use openvr as vr;
 use openxr as xr;
 use std::mem::offset_of;
-use std::sync::{
-    atomic::{AtomicU32, Ordering},
-    Arc, Mutex, Once,
-};
-use std::time::Instant;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, Once};
 use std::{ffi::c_char, ops::Deref};
 
+/// Size of the loading indicator texture, in pixels
+const LOADING_SIZE: u32 = 16;
+const LOADING_COLOR: [u8; 4] = [220, 220, 220, 255];
+
 #[derive(Default)]
 pub struct CompositorSessionData(Mutex<Option<DynFrameController>>);
 
@@ -33,10 +38,19 @@ pub struct Compositor {
     /// Stores the backend data in between session restarts.
     tmp_backend: Mutex<Option<AnyTempBackendData>>,
     overlays: Injected<OverlayMan>,
-    metrics: FrameMetrics,
     timing_mode: Mutex<vr::EVRCompositorTimingMode>,
     frame_state: Mutex<FrameState>,
     focused: Once,
+    /// Only set if the performance HUD is enabled (XRIZER_PERF_HUD)
+    perf_hud: Mutex<Option<PerfHud>>,
+    /// Passed to xrEndFrame, can be chosen with XRIZER_BLEND_MODE
+    blend_mode: Mutex<xr::EnvironmentBlendMode>,
+    /// Show a loading indicator until the app presents its first frame (XRIZER_LOADING_LAYER)
+    loading_layer: bool,
+    /// Set once a frame with the app's projection layer has been presented
+    app_frame_presented: AtomicBool,
+    /// How much further than the display time render poses are predicted (XRIZER_EXTRA_PREDICTION_MS)
+    extra_prediction: Mutex<xr::Duration>,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -64,12 +78,6 @@ impl FrameState {
     }
 }
 
-struct FrameMetrics {
-    system_start: Instant,
-    index: AtomicU32,
-    time: AtomicF64,
-}
-
 struct TempBackendData<G: GraphicsBackend> {
     backend: G,
     swapchain_create_info: Option<xr::SwapchainCreateInfo<G::Api>>,
@@ -78,21 +86,91 @@ supported_backends_enum!(enum AnyTempBackendData: TempBackendData);
 
 impl Compositor {
     pub fn new(openxr: Arc<OpenXrData<Self>>, injector: &Injector) -> Self {
-        Self {
+        let supported_blend_modes = supported_blend_modes(&openxr);
+        let requested_blend_mode =
+            std::env::var("XRIZER_BLEND_MODE")
+                .ok()
+                .and_then(|mode| match mode.as_str() {
+                    "opaque" => Some(xr::EnvironmentBlendMode::OPAQUE),
+                    "additive" => Some(xr::EnvironmentBlendMode::ADDITIVE),
+                    "alpha-blend" => Some(xr::EnvironmentBlendMode::ALPHA_BLEND),
+                    other => {
+                        warn!("Unknown XRIZER_BLEND_MODE value: {other}");
+                        None
+                    }
+                });
+        let blend_mode = select_blend_mode(&supported_blend_modes, requested_blend_mode);
+        info!("Using environment blend mode {blend_mode:?} (supported: {supported_blend_modes:?})");
+
+        let comp = Self {
             vtables: Default::default(),
             openxr,
             input: injector.inject(),
             system: injector.inject(),
             tmp_backend: Mutex::default(),
             overlays: injector.inject(),
-            metrics: FrameMetrics {
-                system_start: Instant::now(),
-                index: 0.into(),
-                time: 0.0.into(),
-            },
             timing_mode: vr::EVRCompositorTimingMode::Implicit.into(),
             frame_state: FrameState::Submitted.into(),
             focused: Once::new(),
+            perf_hud: std::env::var("XRIZER_PERF_HUD")
+                .is_ok_and(|enabled| enabled == "1")
+                .then(PerfHud::new)
+                .into(),
+            blend_mode: blend_mode.into(),
+            loading_layer: !std::env::var("XRIZER_LOADING_LAYER").is_ok_and(|s| s == "0"),
+            app_frame_presented: false.into(),
+            extra_prediction: xr::Duration::from_nanos(0).into(),
+        };
+
+        if let Ok(ms) = std::env::var("XRIZER_EXTRA_PREDICTION_MS") {
+            match ms.parse::<f32>() {
+                Ok(ms) => {
+                    comp.set_extra_prediction_ms(ms);
+                }
+                Err(e) => warn!("Invalid XRIZER_EXTRA_PREDICTION_MS ({e})"),
+            }
+        }
+
+        comp
+    }
+
+    /// Predicts render poses this many milliseconds past the display time. Negative values are
+    /// rejected, since poses can't be predicted into the past of the frame being rendered.
+    pub fn set_extra_prediction_ms(&self, ms: f32) -> bool {
+        if !ms.is_finite() || ms < 0.0 {
+            warn!("Rejecting invalid extra pose prediction ({ms}ms)");
+            return false;
+        }
+        *self.extra_prediction.lock().unwrap() =
+            xr::Duration::from_nanos((ms * 1_000_000.0) as i64);
+        true
+    }
+
+    /// The time render poses are predicted for, if it differs from the display time.
+    fn pose_prediction_time(&self) -> Option<xr::Time> {
+        let extra = *self.extra_prediction.lock().unwrap();
+        (extra.as_nanos() > 0).then(|| {
+            xr::Time::from_nanos(self.openxr.display_time.get().as_nanos() + extra.as_nanos())
+        })
+    }
+
+    #[inline]
+    pub fn blend_mode(&self) -> xr::EnvironmentBlendMode {
+        *self.blend_mode.lock().unwrap()
+    }
+
+    /// Falls back to the default blend mode if the requested one isn't supported.
+    #[cfg(test)]
+    pub fn set_blend_mode(&self, mode: xr::EnvironmentBlendMode) {
+        *self.blend_mode.lock().unwrap() =
+            select_blend_mode(&supported_blend_modes(&self.openxr), Some(mode));
+    }
+
+    #[cfg(test)]
+    pub fn set_perf_hud_enabled(&self, enabled: bool) {
+        let mut hud = self.perf_hud.lock().unwrap();
+        if enabled != hud.is_some() {
+            *hud = enabled.then(PerfHud::new);
         }
     }
 
@@ -109,13 +187,19 @@ impl Compositor {
         };
 
         #[macros::any_graphics(DynFrameController)]
-        fn wait_frame<G: GraphicsBackend + 'static>(ctrl: &mut FrameController<G>) -> xr::Time {
+        fn wait_frame<G: GraphicsBackend + 'static>(
+            ctrl: &mut FrameController<G>,
+        ) -> xr::FrameState {
             ctrl.wait_frame()
         }
 
+        let frame_state = ctrl.with_any_graphics_mut::<wait_frame>(());
         self.openxr
             .display_time
-            .set(ctrl.with_any_graphics_mut::<wait_frame>(()));
+            .set(frame_state.predicted_display_time);
+        self.openxr
+            .frame_metrics
+            .set_display_period(frame_state.predicted_display_period);
     }
 
     fn maybe_begin_frame(&self, session_data: &SessionData) {
@@ -179,6 +263,14 @@ impl Compositor {
         self.openxr.restart_session();
         Ok(())
     }
+
+    /// The format engines should submit textures in to match the runtime's preferred swapchain
+    /// format, so they avoid formats the runtime would reject. IVRCompositor has no method to
+    /// expose this through, so it's test only until one exists.
+    #[cfg(test)]
+    pub fn recommended_swapchain_format(&self) -> Option<TextureFormat> {
+        self.openxr.session_data.get().recommended_texture_format()
+    }
 }
 
 fn fill_vk_extensions_buffer(extensions: String, buffer: *mut c_char, buffer_size: u32) -> u32 {
@@ -606,11 +698,17 @@ impl vr::IVRCompositor029_Interface for Compositor {
         unsafe {
             // TODO: These values are copy/pasted from OpenComposite, determine if real values are
             // necessary/better
-            set!(m_nFrameIndex, self.metrics.index.load(Ordering::Relaxed));
+            set!(
+                m_nFrameIndex,
+                self.openxr.frame_metrics.frame_index() as u32
+            );
             set!(m_nNumFramePresents, 1);
             set!(m_nNumMisPresented, 0);
             set!(m_nReprojectionFlags, 0);
-            set!(m_flSystemTimeInSeconds, self.metrics.time.load());
+            set!(
+                m_flSystemTimeInSeconds,
+                self.openxr.frame_metrics.last_frame_time()
+            );
             set!(m_flPreSubmitGpuMs, 8.0);
             set!(m_flPostSubmitGpuMs, 1.0);
             set!(m_flTotalRenderGpuMs, 9.0);
@@ -642,11 +740,26 @@ impl vr::IVRCompositor029_Interface for Compositor {
             system: &System,
             display_time: xr::Time,
             overlays: Option<&OverlayMan>,
-        ) where
+            perf_hud: Option<&PerfHud>,
+            blend_mode: xr::EnvironmentBlendMode,
+            show_loading: bool,
+        ) -> bool
+        where
             for<'b> &'b crate::overlay::AnySwapchainMap:
                 TryInto<&'b crate::overlay::SwapchainMap<G::Api>, Error: std::fmt::Display>,
+            for<'b> &'b openxr_data::GraphicalSession:
+                TryInto<&'b openxr_data::Session<G::Api>, Error: std::fmt::Display>,
+            <G::Api as xr::Graphics>::Format: PartialEq + std::fmt::Debug,
         {
-            ctrl.end_frame(session_data, system, display_time, overlays)
+            ctrl.end_frame(
+                session_data,
+                system,
+                display_time,
+                overlays,
+                perf_hud,
+                blend_mode,
+                show_loading,
+            )
         }
 
         let session_data = self.openxr.session_data.get();
@@ -664,23 +777,33 @@ impl vr::IVRCompositor029_Interface for Compositor {
         let system = self.system.force(|i| System::new(self.openxr.clone(), i));
         let display_time = self.openxr.display_time.get();
         let overlays = self.overlays.get();
+        let mut perf_hud = self.perf_hud.lock().unwrap();
+        if let Some(hud) = perf_hud.as_mut() {
+            hud.record_frame(&self.openxr.frame_metrics);
+        }
 
-        ctrl.with_any_graphics_mut::<end_frame>((
+        let show_loading = self.loading_layer && !self.app_frame_presented.load(Ordering::Relaxed);
+        let presented = ctrl.with_any_graphics_mut::<end_frame>((
             &session_data,
             &system,
             display_time,
             overlays.as_deref(),
+            perf_hud.as_ref(),
+            self.blend_mode(),
+            show_loading,
         ));
+        drop(perf_hud);
+        if presented && show_loading {
+            debug!("app presented its first frame, hiding loading indicator");
+            self.app_frame_presented.store(true, Ordering::Relaxed);
+        }
 
         self.frame_state
             .lock()
             .unwrap()
             .advance_to(FrameState::Submitted);
 
-        self.metrics.index.fetch_add(1, Ordering::Relaxed);
-        self.metrics
-            .time
-            .store(self.metrics.system_start.elapsed().as_secs_f64());
+        self.openxr.frame_metrics.advance_frame();
         #[cfg(feature = "tracing")]
         {
             tracy_client::frame_mark();
@@ -742,6 +865,17 @@ impl vr::IVRCompositor029_Interface for Compositor {
             return vr::EVRCompositorError::DoNotHaveFocus;
         }
 
+        let depth = ((submit_flags & vr::EVRSubmitFlags::TextureWithDepth).0 > 0)
+            .then(|| {
+                let texture = std::ptr::from_ref(texture);
+                if (submit_flags & vr::EVRSubmitFlags::TextureWithPose).0 > 0 {
+                    unsafe { &(*texture.cast::<vr::VRTextureWithPoseAndDepth_t>()).depth }
+                } else {
+                    unsafe { &(*texture.cast::<vr::VRTextureWithDepth_t>()).depth }
+                }
+            })
+            .filter(|_| self.openxr.enabled_extensions.khr_composition_layer_depth);
+
         let mut session_lock = self.openxr.session_data.get();
         let mut frame_lock = session_lock.comp_data.0.lock().unwrap();
 
@@ -768,6 +902,7 @@ impl vr::IVRCompositor029_Interface for Compositor {
             session_data: &SessionData,
             eye: vr::EVREye,
             texture: &vr::Texture_t,
+            depth: Option<&vr::VRTextureDepthInfo_t>,
             bounds: vr::VRTextureBounds_t,
             flags: vr::EVRSubmitFlags,
         ) -> xr::Result<(), vr::EVRCompositorError>
@@ -778,10 +913,18 @@ impl vr::IVRCompositor029_Interface for Compositor {
         {
             let real_texture =
                 G::get_texture(texture).ok_or(vr::EVRCompositorError::InvalidTexture)?;
+            let depth = depth.and_then(|depth| {
+                let depth_texture = vr::Texture_t {
+                    handle: depth.handle,
+                    ..*texture
+                };
+                G::get_texture(&depth_texture).map(|t| (t, depth))
+            });
             ctrl.submit_impl(
                 session_data,
                 eye,
                 real_texture,
+                depth,
                 texture.eColorSpace,
                 bounds,
                 flags,
@@ -792,6 +935,7 @@ impl vr::IVRCompositor029_Interface for Compositor {
             &session_lock,
             eye,
             texture,
+            depth,
             bounds,
             submit_flags,
         )) {
@@ -853,9 +997,16 @@ impl vr::IVRCompositor029_Interface for Compositor {
         let render_poses = unsafe {
             std::slice::from_raw_parts_mut(render_pose_array, render_pose_count as usize)
         };
-        self.input
-            .force(|_| Input::new(self.openxr.clone()))
-            .get_poses(render_poses, None);
+        let input = self.input.force(|_| Input::new(self.openxr.clone()));
+        let prediction_time = self.pose_prediction_time();
+        input.get_poses(render_poses, None, prediction_time);
+        if prediction_time.is_some() {
+            // Frames are submitted with the views located at the display time, so the headset
+            // has to stay there or the image won't line up with the views.
+            if let Some(hmd) = input.get_device_pose(vr::k_unTrackedDeviceIndex_Hmd, None) {
+                render_poses[0] = hmd;
+            }
+        }
 
         // Not entirely sure how the game poses are supposed to differ from the render poses,
         // but a lot of games use the game pose array for controller positions.
@@ -976,6 +1127,44 @@ impl vr::IVRCompositor009On014 for Compositor {
 struct SubmittedEye {
     extent: xr::Extent2Di,
     flip_vertically: bool,
+    depth: Option<SubmittedDepth>,
+}
+
+#[derive(Copy, Clone)]
+struct SubmittedDepth {
+    extent: xr::Extent2Di,
+    min_depth: f32,
+    max_depth: f32,
+    near_z: f32,
+    far_z: f32,
+}
+
+impl SubmittedDepth {
+    fn new(extent: xr::Extent2Di, info: &vr::VRTextureDepthInfo_t) -> Self {
+        let [min_depth, max_depth] = match info.vRange.v {
+            [min, max] if 0.0 <= min && min < max && max <= 1.0 => [min, max],
+            _ => [0.0, 1.0],
+        };
+        // OpenVR projection matrices map the near plane to 0 and the far plane to 1, so the
+        // distances can be recovered from the depth terms. Reversed and infinite projections
+        // fall out of this naturally (as near > far and an infinite plane, respectively),
+        // both of which OpenXR accepts.
+        let m = info.mProjection.m;
+        Self {
+            extent,
+            min_depth,
+            max_depth,
+            near_z: m[2][3] / m[2][2],
+            far_z: m[2][3] / (m[2][2] + 1.0),
+        }
+    }
+}
+
+struct DepthSwapchain<G: xr::Graphics> {
+    swapchain: xr::Swapchain<G>,
+    info: xr::SwapchainCreateInfo<G>,
+    image_index: usize,
+    image_acquired: bool,
 }
 
 struct SwapchainData<G: xr::Graphics> {
@@ -984,6 +1173,13 @@ struct SwapchainData<G: xr::Graphics> {
     initial_format: G::Format,
 }
 
+struct HudSwapchain<G: xr::Graphics> {
+    swapchain: xr::Swapchain<G>,
+    images: Vec<G::SwapchainImage>,
+    /// The PerfHud revision currently in the swapchain
+    drawn_revision: Option<u64>,
+}
+
 struct FrameController<G: GraphicsBackend> {
     stream: xr::FrameStream<G::Api>,
     waiter: xr::FrameWaiter,
@@ -995,6 +1191,12 @@ struct FrameController<G: GraphicsBackend> {
     app_fade_grid: bool,
     eyes_submitted: [Option<SubmittedEye>; 2],
     submitting_null: bool,
+    /// Created on the first frame the perf HUD is enabled - None if it couldn't be created
+    hud: Option<Option<HudSwapchain<G::Api>>>,
+    /// Created on the first frame the loading indicator is shown - None if it couldn't be created
+    loading: Option<Option<xr::Swapchain<G::Api>>>,
+    /// Created on the first frame the app submits depth - None if it couldn't be created
+    depth: Option<Option<DepthSwapchain<G::Api>>>,
     backend: G,
 }
 supported_backends_enum!(enum DynFrameController: FrameController);
@@ -1084,6 +1286,9 @@ impl<G: GraphicsBackend> FrameController<G> {
             app_fade_grid: false,
             eyes_submitted: Default::default(),
             submitting_null: false,
+            hud: None,
+            loading: None,
+            depth: None,
             backend,
         }
     }
@@ -1131,13 +1336,13 @@ impl<G: GraphicsBackend> FrameController<G> {
         self.image_acquired = true;
     }
 
-    fn wait_frame(&mut self) -> xr::Time {
+    fn wait_frame(&mut self) -> xr::FrameState {
         let frame_state = {
             tracy_span!("wait frame");
             self.waiter.wait().unwrap()
         };
         self.should_render = frame_state.should_render && !self.app_suspend_render;
-        frame_state.predicted_display_time
+        frame_state
     }
 
     fn begin_frame(&mut self) {
@@ -1169,6 +1374,7 @@ impl<G: GraphicsBackend> FrameController<G> {
         session_data: &SessionData,
         eye: vr::EVREye,
         texture: G::OpenVrTexture,
+        depth: Option<(G::OpenVrTexture, &vr::VRTextureDepthInfo_t)>,
         color_space: vr::EColorSpace,
         bounds: vr::VRTextureBounds_t,
         submit_flags: vr::EVRSubmitFlags,
@@ -1213,7 +1419,13 @@ impl<G: GraphicsBackend> FrameController<G> {
                             self.image_index,
                             submit_flags,
                         ),
+                        // Textures are always copied into a swapchain of the same graphics API,
+                        // so the origin conventions (top-left for Vulkan, bottom-left for GL)
+                        // always match - only the submitted bounds can flip the image.
                         flip_vertically: bounds.vertically_flipped(),
+                        depth: depth.and_then(|(depth_texture, info)| {
+                            self.submit_depth(session_data, eye, depth_texture, info, bounds)
+                        }),
                     }
                 })
                 .or_else(|| {
@@ -1233,34 +1445,305 @@ impl<G: GraphicsBackend> FrameController<G> {
                 data.swapchain.release_image().unwrap();
             }
             self.image_acquired = false;
+
+            if let Some(Some(depth)) = &mut self.depth {
+                if depth.image_acquired {
+                    trace!("releasing depth image");
+                    depth.swapchain.release_image().unwrap();
+                    depth.image_acquired = false;
+                }
+            }
         }
 
         Ok(())
     }
 
+    /// Copies the app's depth texture for this eye into the depth swapchain, acquiring an image
+    /// if this is the first eye to submit depth this frame.
+    fn submit_depth(
+        &mut self,
+        session_data: &SessionData,
+        eye: vr::EVREye,
+        texture: G::OpenVrTexture,
+        info: &vr::VRTextureDepthInfo_t,
+        bounds: vr::VRTextureBounds_t,
+    ) -> Option<SubmittedDepth>
+    where
+        <G::Api as xr::Graphics>::Format: Eq,
+        for<'b> &'b openxr_data::GraphicalSession:
+            TryInto<&'b openxr_data::Session<G::Api>, Error: std::fmt::Display>,
+        <G::Api as xr::Graphics>::Format: PartialEq + std::fmt::Debug,
+    {
+        let create_info = self
+            .backend
+            .depth_swapchain_info_for_texture(texture, bounds)
+            .filter(is_valid_swapchain_info)?;
+
+        let stale = matches!(
+            &self.depth,
+            Some(Some(depth)) if !is_usable_swapchain(&depth.info, depth.info.format, &create_info)
+        );
+        if self.depth.is_none() || stale {
+            info!("creating depth swapchain (for {eye:?})");
+            self.depth = Some(Self::create_depth_swapchain(
+                session_data,
+                create_info,
+                &mut self.backend,
+            ));
+        }
+
+        let depth = self.depth.as_mut().unwrap().as_mut()?;
+        if !depth.image_acquired {
+            depth.image_index = depth
+                .swapchain
+                .acquire_image()
+                .expect("Failed to acquire depth swapchain image")
+                as usize;
+            depth
+                .swapchain
+                .wait_image(xr::Duration::INFINITE)
+                .expect("Failed to wait for depth swapchain image");
+            depth.image_acquired = true;
+        }
+
+        let extent = self
+            .backend
+            .copy_depth_to_swapchain(eye, texture, bounds, depth.image_index);
+        Some(SubmittedDepth::new(extent, info))
+    }
+
+    fn create_depth_swapchain(
+        session_data: &SessionData,
+        mut info: xr::SwapchainCreateInfo<G::Api>,
+        backend: &mut G,
+    ) -> Option<DepthSwapchain<G::Api>>
+    where
+        for<'b> &'b openxr_data::GraphicalSession:
+            TryInto<&'b openxr_data::Session<G::Api>, Error: std::fmt::Display>,
+        <G::Api as xr::Graphics>::Format: PartialEq + std::fmt::Debug,
+    {
+        let requested_format = info.format;
+        session_data.check_format::<G>(&mut info);
+        // Depth is copied as is, so we can't convert it to another format.
+        if info.format != requested_format {
+            warn!("Runtime doesn't support the app's depth format, not submitting depth");
+            return None;
+        }
+
+        let swapchain = session_data
+            .create_swapchain(&info)
+            .inspect_err(|e| warn!("Failed to create depth swapchain: {e}"))
+            .ok()?;
+        let images = swapchain
+            .enumerate_images()
+            .expect("Failed to enumerate depth swapchain images");
+        backend.store_depth_swapchain_images(images);
+        debug!(
+            "Created new depth swapchain: {}x{}, format = {:?}",
+            info.width,
+            info.height,
+            G::to_nice_format(info.format)
+        );
+
+        Some(DepthSwapchain {
+            swapchain,
+            info,
+            image_index: 0,
+            image_acquired: false,
+        })
+    }
+
+    fn create_rgba_swapchain(
+        session_data: &SessionData,
+        width: u32,
+        height: u32,
+        name: &str,
+    ) -> Option<(
+        xr::Swapchain<G::Api>,
+        Vec<<G::Api as xr::Graphics>::SwapchainImage>,
+    )>
+    where
+        for<'b> &'b openxr_data::GraphicalSession:
+            TryInto<&'b openxr_data::Session<G::Api>, Error: std::fmt::Display>,
+        <G::Api as xr::Graphics>::Format: PartialEq + std::fmt::Debug,
+    {
+        let mut info = G::rgba_swapchain_info(width, height);
+        let requested_format = info.format;
+        session_data.check_format::<G>(&mut info);
+        // These are uploaded as raw RGBA pixels, so we can't use another format.
+        if info.format != requested_format {
+            warn!("Runtime doesn't support RGBA swapchains, not showing {name}");
+            return None;
+        }
+
+        let swapchain = session_data
+            .create_swapchain(&info)
+            .inspect_err(|e| warn!("Failed to create {name} swapchain: {e}"))
+            .ok()?;
+        let images = swapchain
+            .enumerate_images()
+            .unwrap_or_else(|e| panic!("Failed to enumerate {name} swapchain images: {e}"));
+
+        Some((swapchain, images))
+    }
+
+    fn create_hud_swapchain(session_data: &SessionData) -> Option<HudSwapchain<G::Api>>
+    where
+        for<'b> &'b openxr_data::GraphicalSession:
+            TryInto<&'b openxr_data::Session<G::Api>, Error: std::fmt::Display>,
+        <G::Api as xr::Graphics>::Format: PartialEq + std::fmt::Debug,
+    {
+        let (swapchain, images) =
+            Self::create_rgba_swapchain(session_data, HUD_WIDTH, HUD_HEIGHT, "performance HUD")?;
+
+        Some(HudSwapchain {
+            swapchain,
+            images,
+            drawn_revision: None,
+        })
+    }
+
+    /// Uploads the HUD to its swapchain if it has changed since it was last drawn.
+    fn update_hud(&mut self, session_data: &SessionData, hud: &PerfHud)
+    where
+        for<'b> &'b openxr_data::GraphicalSession:
+            TryInto<&'b openxr_data::Session<G::Api>, Error: std::fmt::Display>,
+        <G::Api as xr::Graphics>::Format: PartialEq + std::fmt::Debug,
+    {
+        let Some(hud_swapchain) = self
+            .hud
+            .get_or_insert_with(|| Self::create_hud_swapchain(session_data))
+            .as_mut()
+        else {
+            return;
+        };
+
+        if hud_swapchain.drawn_revision != Some(hud.revision()) {
+            tracy_span!("update perf HUD");
+            let image_index = hud_swapchain
+                .swapchain
+                .acquire_image()
+                .expect("Failed to acquire performance HUD image")
+                as usize;
+            hud_swapchain
+                .swapchain
+                .wait_image(xr::Duration::INFINITE)
+                .expect("Failed to wait for performance HUD image");
+
+            if let Some(image) = hud_swapchain.images.get(image_index) {
+                self.backend.upload_rgba_to_swapchain_image(
+                    image,
+                    HUD_WIDTH,
+                    HUD_HEIGHT,
+                    hud.pixels(),
+                );
+            }
+
+            hud_swapchain.swapchain.release_image().unwrap();
+            hud_swapchain.drawn_revision = Some(hud.revision());
+        }
+    }
+
+    /// The loading indicator is a solid square, so it only needs to be uploaded once.
+    fn create_loading_swapchain(
+        session_data: &SessionData,
+        backend: &G,
+    ) -> Option<xr::Swapchain<G::Api>>
+    where
+        for<'b> &'b openxr_data::GraphicalSession:
+            TryInto<&'b openxr_data::Session<G::Api>, Error: std::fmt::Display>,
+        <G::Api as xr::Graphics>::Format: PartialEq + std::fmt::Debug,
+    {
+        let (mut swapchain, images) = Self::create_rgba_swapchain(
+            session_data,
+            LOADING_SIZE,
+            LOADING_SIZE,
+            "loading indicator",
+        )?;
+
+        let image_index = swapchain
+            .acquire_image()
+            .expect("Failed to acquire loading indicator image") as usize;
+        swapchain
+            .wait_image(xr::Duration::INFINITE)
+            .expect("Failed to wait for loading indicator image");
+        if let Some(image) = images.get(image_index) {
+            let pixels = [LOADING_COLOR; (LOADING_SIZE * LOADING_SIZE) as usize].concat();
+            backend.upload_rgba_to_swapchain_image(image, LOADING_SIZE, LOADING_SIZE, &pixels);
+        }
+        swapchain.release_image().unwrap();
+
+        Some(swapchain)
+    }
+
     fn end_frame(
         &mut self,
         session_data: &SessionData,
         system: &System,
         display_time: xr::Time,
         overlays: Option<&OverlayMan>,
-    ) where
+        perf_hud: Option<&PerfHud>,
+        blend_mode: xr::EnvironmentBlendMode,
+        show_loading: bool,
+    ) -> bool
+    where
         for<'b> &'b crate::overlay::AnySwapchainMap:
             TryInto<&'b crate::overlay::SwapchainMap<G::Api>, Error: std::fmt::Display>,
+        for<'b> &'b openxr_data::GraphicalSession:
+            TryInto<&'b openxr_data::Session<G::Api>, Error: std::fmt::Display>,
+        <G::Api as xr::Graphics>::Format: PartialEq + std::fmt::Debug,
     {
-        let mut proj_layer_views = Vec::new();
+        if let Some(hud) = perf_hud {
+            self.update_hud(session_data, hud);
+        }
 
-        if self.should_render
+        let has_projection = self.should_render
             && !self.submitting_null
-            && self.eyes_submitted.iter().all(|eye| eye.is_some())
-        {
+            && self.eyes_submitted.iter().all(|eye| eye.is_some());
+        let show_loading = show_loading && !has_projection;
+        if show_loading {
+            let backend = &self.backend;
+            self.loading
+                .get_or_insert_with(|| Self::create_loading_swapchain(session_data, backend));
+        }
+
+        // Chained onto the projection views, so these must outlive them.
+        let mut depth_infos = Vec::new();
+        let mut proj_layer_views = Vec::new();
+
+        if has_projection {
             let swapchain_data = self
                 .swapchain_data
                 .as_ref()
                 .expect("Swapchain data unexpectedly invalid on submit");
 
+            let depth_swapchain = self.depth.as_ref().and_then(Option::as_ref);
+            depth_infos = (0..2)
+                .map(|eye_index| {
+                    let depth = self.eyes_submitted[eye_index]?.depth?;
+                    Some(xr::sys::CompositionLayerDepthInfoKHR {
+                        ty: xr::sys::CompositionLayerDepthInfoKHR::TYPE,
+                        next: std::ptr::null(),
+                        sub_image: xr::sys::SwapchainSubImage {
+                            swapchain: depth_swapchain?.swapchain.as_raw(),
+                            image_rect: xr::Rect2Di {
+                                extent: depth.extent,
+                                offset: xr::Offset2Di::default(),
+                            },
+                            image_array_index: eye_index as u32,
+                        },
+                        min_depth: depth.min_depth,
+                        max_depth: depth.max_depth,
+                        near_z: depth.near_z,
+                        far_z: depth.far_z,
+                    })
+                })
+                .collect();
+
             let crate::system::ViewData { flags, views } =
                 system.get_views(session_data.current_origin_as_reference_space());
+            // Must match the FOV the game rendered with
+            let fovs = [vr::EVREye::Left, vr::EVREye::Right].map(|eye| system.projection_fov(eye));
             proj_layer_views = views
                 .into_iter()
                 .enumerate()
@@ -1281,9 +1764,10 @@ impl<G: GraphicsBackend> FrameController<G> {
                     let SubmittedEye {
                         extent,
                         flip_vertically,
+                        ..
                     } = self.eyes_submitted[eye_index]
                         .unwrap_or_else(|| panic!("Eye {eye_index} has not been submitted!"));
-                    let mut fov = view.fov;
+                    let mut fov = fovs[eye_index];
                     if flip_vertically {
                         std::mem::swap(&mut fov.angle_up, &mut fov.angle_down);
                     }
@@ -1296,10 +1780,19 @@ impl<G: GraphicsBackend> FrameController<G> {
                             offset: xr::Offset2Di::default(),
                         });
 
-                    xr::CompositionLayerProjectionView::new()
+                    let view = xr::CompositionLayerProjectionView::new()
                         .fov(fov)
                         .pose(pose)
-                        .sub_image(sub_image)
+                        .sub_image(sub_image);
+                    match &depth_infos[eye_index] {
+                        Some(depth) => {
+                            let mut raw = view.into_raw();
+                            raw.next = std::ptr::from_ref(depth).cast();
+                            // SAFETY: depth_infos outlives the views.
+                            unsafe { xr::CompositionLayerProjectionView::from_raw(raw) }
+                        }
+                        None => view,
+                    }
                 })
                 .collect()
         }
@@ -1314,21 +1807,134 @@ impl<G: GraphicsBackend> FrameController<G> {
             );
         }
 
+        let hud_layer = perf_hud
+            .and(self.hud.as_ref())
+            .and_then(Option::as_ref)
+            .filter(|hud| hud.drawn_revision.is_some())
+            .map(|hud| {
+                xr::CompositionLayerQuad::new()
+                    .layer_flags(xr::CompositionLayerFlags::BLEND_TEXTURE_SOURCE_ALPHA)
+                    .space(&session_data.view_space)
+                    .eye_visibility(xr::EyeVisibility::BOTH)
+                    .sub_image(
+                        xr::SwapchainSubImage::new()
+                            .swapchain(&hud.swapchain)
+                            .image_rect(xr::Rect2Di {
+                                offset: xr::Offset2Di::default(),
+                                extent: xr::Extent2Di {
+                                    width: HUD_WIDTH as i32,
+                                    height: HUD_HEIGHT as i32,
+                                },
+                            }),
+                    )
+                    // Off to the lower left of the view, so it doesn't get in the way
+                    .pose(xr::Posef {
+                        orientation: xr::Quaternionf::IDENTITY,
+                        position: xr::Vector3f {
+                            x: -0.12,
+                            y: -0.1,
+                            z: -0.5,
+                        },
+                    })
+                    .size(xr::Extent2Df {
+                        width: 0.12,
+                        height: 0.12 * HUD_HEIGHT as f32 / HUD_WIDTH as f32,
+                    })
+            });
+
+        let loading_layer = self
+            .loading
+            .as_ref()
+            .and_then(Option::as_ref)
+            .filter(|_| show_loading)
+            .map(|swapchain| {
+                // Half a turn per second, so it's clear we haven't hung
+                let angle = (display_time.as_nanos() as f64 / 1e9 * std::f64::consts::PI) as f32;
+                let rotation = Quat::from_rotation_z(angle);
+                xr::CompositionLayerQuad::new()
+                    .space(&session_data.view_space)
+                    .eye_visibility(xr::EyeVisibility::BOTH)
+                    .sub_image(
+                        xr::SwapchainSubImage::new()
+                            .swapchain(swapchain)
+                            .image_rect(xr::Rect2Di {
+                                offset: xr::Offset2Di::default(),
+                                extent: xr::Extent2Di {
+                                    width: LOADING_SIZE as i32,
+                                    height: LOADING_SIZE as i32,
+                                },
+                            }),
+                    )
+                    .pose(xr::Posef {
+                        orientation: xr::Quaternionf {
+                            x: rotation.x,
+                            y: rotation.y,
+                            z: rotation.z,
+                            w: rotation.w,
+                        },
+                        position: xr::Vector3f {
+                            x: 0.0,
+                            y: 0.0,
+                            z: -1.0,
+                        },
+                    })
+                    .size(xr::Extent2Df {
+                        width: 0.05,
+                        height: 0.05,
+                    })
+            });
+
         let mut layers: Vec<&xr::CompositionLayerBase<_>> = Vec::new();
         if let Some(l) = proj_layer.as_ref() {
             layers.push(l);
         }
+        if let Some(l) = loading_layer.as_ref() {
+            layers.push(l);
+        }
         let overlay_layers;
         if let Some(overlay_man) = overlays {
             overlay_layers = overlay_man.get_layers(session_data, self.app_fade_grid);
             layers.extend(overlay_layers.iter().map(Deref::deref));
         }
+        if let Some(l) = hud_layer.as_ref() {
+            layers.push(l);
+        }
 
-        self.stream
-            .end(display_time, xr::EnvironmentBlendMode::OPAQUE, &layers)
-            .unwrap();
+        self.stream.end(display_time, blend_mode, &layers).unwrap();
 
         trace!("frame submitted");
+        proj_layer.is_some()
+    }
+}
+
+/// Environment blend modes supported by the runtime for the primary stereo view configuration.
+fn supported_blend_modes(openxr: &OpenXrData<Compositor>) -> Vec<xr::EnvironmentBlendMode> {
+    openxr
+        .instance
+        .enumerate_environment_blend_modes(
+            openxr.system_id,
+            xr::ViewConfigurationType::PRIMARY_STEREO,
+        )
+        .inspect_err(|e| warn!("Failed to enumerate environment blend modes: {e}"))
+        .unwrap_or_default()
+}
+
+fn select_blend_mode(
+    supported: &[xr::EnvironmentBlendMode],
+    requested: Option<xr::EnvironmentBlendMode>,
+) -> xr::EnvironmentBlendMode {
+    if let Some(mode) = requested {
+        if supported.contains(&mode) {
+            return mode;
+        }
+        warn!("Requested environment blend mode {mode:?} is not supported by the runtime");
+    }
+
+    if supported.is_empty() || supported.contains(&xr::EnvironmentBlendMode::OPAQUE) {
+        xr::EnvironmentBlendMode::OPAQUE
+    } else {
+        // Runtimes list their preferred mode first.
+        supported[0]
     }
 }
 
@@ -1358,6 +1964,7 @@ pub use tests::FakeGraphicsData;
 mod tests {
     use super::*;
     use crate::graphics_backends::{GraphicsBackend, VulkanData};
+    use glam::Mat4;
     use std::cell::Cell;
     use std::ffi::CStr;
     use std::mem::MaybeUninit;
@@ -1420,6 +2027,11 @@ mod tests {
         fn to_nice_format(format: <Self::Api as openxr::Graphics>::Format) -> Self::NiceFormat {
             VulkanData::to_nice_format(format)
         }
+        fn texture_format(
+            format: <Self::Api as openxr::Graphics>::Format,
+        ) -> Option<TextureFormat> {
+            VulkanData::texture_format(format)
+        }
         fn session_create_info(&self) -> <Self::Api as openxr::Graphics>::SessionCreateInfo {
             self.vk.session_create_info()
         }
@@ -1467,6 +2079,30 @@ mod tests {
             xr::Extent2Di::default()
         }
 
+        fn depth_swapchain_info_for_texture(
+            &self,
+            texture: Self::OpenVrTexture,
+            bounds: openvr::VRTextureBounds_t,
+        ) -> Option<openxr::SwapchainCreateInfo<Self::Api>> {
+            Some(self.swapchain_info_for_texture(texture, bounds, vr::EColorSpace::Auto))
+        }
+
+        fn store_depth_swapchain_images(
+            &mut self,
+            _images: Vec<<Self::Api as openxr::Graphics>::SwapchainImage>,
+        ) {
+        }
+
+        fn copy_depth_to_swapchain(
+            &self,
+            _eye: openvr::EVREye,
+            _texture: Self::OpenVrTexture,
+            _bounds: openvr::VRTextureBounds_t,
+            _image_index: usize,
+        ) -> openxr::Extent2Di {
+            xr::Extent2Di::default()
+        }
+
         fn copy_overlay_to_swapchain(
             &mut self,
             _texture: Self::OpenVrTexture,
@@ -1475,6 +2111,22 @@ mod tests {
         ) -> openxr::Extent2Di {
             xr::Extent2Di::default()
         }
+
+        fn rgba_swapchain_info(width: u32, height: u32) -> xr::SwapchainCreateInfo<Self::Api> {
+            xr::SwapchainCreateInfo {
+                create_flags: xr::SwapchainCreateFlags::EMPTY,
+                usage_flags: xr::SwapchainUsageFlags::EMPTY,
+                format: SWAPCHAIN_FORMAT.get(),
+                sample_count: 1,
+                width,
+                height,
+                face_count: 1,
+                array_size: 1,
+                mip_count: 1,
+            }
+        }
+
+        fn upload_rgba_to_swapchain_image(&self, _: &u64, _: u32, _: u32, _: &[u8]) {}
     }
 
     impl FakeGraphicsData {
@@ -1628,6 +2280,156 @@ mod tests {
         );
     }
 
+    #[test]
+    fn flipped_bounds_flip_projection_view() {
+        let f = Fixture::new();
+        f.ensure_real_session(false);
+        assert_eq!(f.wait_get_poses(), None);
+
+        let flipped = vr::VRTextureBounds_t {
+            uMin: 0.0,
+            vMin: 1.0,
+            uMax: 1.0,
+            vMax: 0.0,
+        };
+        let texture = FakeGraphicsData::texture(&f.vk);
+        assert_eq!(
+            f.comp.Submit(
+                vr::EVREye::Left,
+                &texture,
+                &flipped,
+                vr::EVRSubmitFlags::Default
+            ),
+            None
+        );
+        assert_eq!(f.submit(vr::EVREye::Right), None);
+
+        let data = f.comp.openxr.session_data.get();
+        let lock = data.comp_data.0.lock().unwrap();
+        let DynFrameController::Fake(ctrl) = lock.as_ref().unwrap() else {
+            panic!("Frame controller was not set up or not faked!");
+        };
+        let [left, right] = ctrl.eyes_submitted.map(|eye| eye.unwrap().flip_vertically);
+        assert!(left);
+        assert!(!right);
+    }
+
+    #[test]
+    fn perf_hud_adds_quad_layer() {
+        let f = Fixture::new();
+        f.ensure_real_session(false);
+
+        let submit_frame = || {
+            assert_eq!(f.submit(vr::EVREye::Left), None);
+            assert_eq!(f.submit(vr::EVREye::Right), None);
+            assert_eq!(f.wait_get_poses(), None);
+
+            let session = f.comp.openxr.session_data.get().session.as_raw();
+            fakexr::submitted_layer_types(session)
+        };
+
+        let layers = submit_frame();
+        assert!(!layers.contains(&xr::StructureType::COMPOSITION_LAYER_QUAD));
+
+        f.comp.set_perf_hud_enabled(true);
+        let layers = submit_frame();
+        assert!(layers.contains(&xr::StructureType::COMPOSITION_LAYER_QUAD));
+    }
+
+    #[test]
+    fn depth_submitted_with_projection() {
+        let f = Fixture::new();
+        f.ensure_real_session(false);
+
+        let depth = |near: f32, far: f32| vr::VRTextureDepthInfo_t {
+            handle: FakeGraphicsData::texture(&f.vk).handle,
+            mProjection: Mat4::perspective_rh(1.0, 1.0, near, far).into(),
+            vRange: vr::HmdVector2_t { v: [0.0, 1.0] },
+        };
+        let submit_with_depth = |eye| {
+            let texture = vr::VRTextureWithDepth_t {
+                _base: FakeGraphicsData::texture(&f.vk),
+                depth: depth(0.1, 100.0),
+            };
+            f.comp.Submit(
+                eye,
+                std::ptr::from_ref(&texture).cast(),
+                std::ptr::null(),
+                vr::EVRSubmitFlags::TextureWithDepth,
+            )
+        };
+        let depth_ranges = || {
+            let session = f.comp.openxr.session_data.get().session.as_raw();
+            fakexr::submitted_depth_ranges(session)
+        };
+
+        assert_eq!(f.submit(vr::EVREye::Left), None);
+        assert_eq!(f.submit(vr::EVREye::Right), None);
+        assert_eq!(f.wait_get_poses(), None);
+        assert!(depth_ranges().is_empty());
+
+        assert_eq!(submit_with_depth(vr::EVREye::Left), None);
+        assert_eq!(submit_with_depth(vr::EVREye::Right), None);
+        assert_eq!(f.wait_get_poses(), None);
+        let ranges = depth_ranges();
+        assert_eq!(ranges.len(), 2);
+        for (near, far) in ranges {
+            assert!((near - 0.1).abs() < 1e-4, "{near}");
+            assert!((far - 100.0).abs() < 0.1, "{far}");
+        }
+    }
+
+    #[test]
+    fn loading_layer_until_first_frame() {
+        let f = Fixture::new();
+        let layers = || {
+            let session = f.comp.openxr.session_data.get().session.as_raw();
+            fakexr::submitted_layer_types(session)
+        };
+
+        // The runtime isn't ready for us to render yet, so the app's first frame isn't shown.
+        f.ensure_real_session(false);
+        let loading = layers();
+        assert!(loading.contains(&xr::StructureType::COMPOSITION_LAYER_QUAD));
+        assert!(!loading.contains(&xr::StructureType::COMPOSITION_LAYER_PROJECTION));
+
+        assert_eq!(f.submit(vr::EVREye::Left), None);
+        assert_eq!(f.submit(vr::EVREye::Right), None);
+        assert_eq!(f.wait_get_poses(), None);
+        let presented = layers();
+        assert!(presented.contains(&xr::StructureType::COMPOSITION_LAYER_PROJECTION));
+        assert!(!presented.contains(&xr::StructureType::COMPOSITION_LAYER_QUAD));
+
+        // Once the app has presented a frame, the indicator stays hidden.
+        assert_eq!(f.wait_get_poses(), None);
+        assert!(!layers().contains(&xr::StructureType::COMPOSITION_LAYER_QUAD));
+    }
+
+    #[test]
+    fn alpha_blend_mode_passed_to_end_frame() {
+        let f = Fixture::new();
+        f.ensure_real_session(false);
+
+        assert!(super::supported_blend_modes(&f.comp.openxr)
+            .contains(&xr::EnvironmentBlendMode::ALPHA_BLEND));
+        f.comp.set_blend_mode(xr::EnvironmentBlendMode::ALPHA_BLEND);
+        assert_eq!(f.comp.blend_mode(), xr::EnvironmentBlendMode::ALPHA_BLEND);
+
+        assert_eq!(f.submit(vr::EVREye::Left), None);
+        assert_eq!(f.submit(vr::EVREye::Right), None);
+        assert_eq!(f.wait_get_poses(), None);
+
+        let session = f.comp.openxr.session_data.get().session.as_raw();
+        assert_eq!(
+            fakexr::submitted_blend_mode(session),
+            xr::EnvironmentBlendMode::ALPHA_BLEND
+        );
+
+        // Unsupported modes fall back to opaque
+        f.comp.set_blend_mode(xr::EnvironmentBlendMode::ADDITIVE);
+        assert_eq!(f.comp.blend_mode(), xr::EnvironmentBlendMode::OPAQUE);
+    }
+
     #[test]
     fn error_on_multiple_same_eye_submit() {
         let f = Fixture::new();
@@ -1711,6 +2513,73 @@ mod tests {
         assert!(!f.comp.GetFrameTiming(timing.as_mut_ptr(), 1));
     }
 
+    #[test]
+    fn extra_prediction_shifts_pose_time() {
+        let f = Fixture::new();
+        f.comp
+            .openxr
+            .display_time
+            .set(xr::Time::from_nanos(1_000_000_000));
+        assert_eq!(f.comp.pose_prediction_time(), None);
+
+        assert!(f.comp.set_extra_prediction_ms(5.0));
+        assert_eq!(
+            f.comp.pose_prediction_time(),
+            Some(xr::Time::from_nanos(1_005_000_000))
+        );
+
+        assert!(!f.comp.set_extra_prediction_ms(-5.0));
+        assert_eq!(
+            f.comp.pose_prediction_time(),
+            Some(xr::Time::from_nanos(1_005_000_000))
+        );
+
+        assert!(f.comp.set_extra_prediction_ms(0.0));
+        assert_eq!(f.comp.pose_prediction_time(), None);
+    }
+
+    #[test]
+    fn frame_counter_matches_frame_timing() {
+        use vr::IVRSystem023_Interface;
+
+        let f = Fixture::new();
+        let system = System::new(f.comp.openxr.clone(), &Injector::default());
+        f.ensure_real_session(false);
+
+        let get_counters = || {
+            let mut timing = MaybeUninit::new(vr::Compositor_FrameTiming::default());
+            unsafe {
+                (&raw mut (*timing.as_mut_ptr()).m_nSize)
+                    .write(std::mem::size_of::<vr::Compositor_FrameTiming>() as u32);
+            }
+            assert!(f.comp.GetFrameTiming(timing.as_mut_ptr(), 0));
+            let timing_index = unsafe { timing.assume_init() }.m_nFrameIndex;
+
+            let mut vsync_counter = 0;
+            let mut seconds = -1.0;
+            assert!(system.GetTimeSinceLastVsync(&mut seconds, &mut vsync_counter));
+            assert!(seconds >= 0.0);
+
+            assert_eq!(timing_index as u64, vsync_counter);
+            vsync_counter
+        };
+
+        let mut last = get_counters();
+        for _ in 0..3 {
+            assert_eq!(f.wait_get_poses(), None);
+            assert_eq!(f.submit(vr::EVREye::Left), None);
+            assert_eq!(f.submit(vr::EVREye::Right), None);
+            assert_eq!(f.wait_get_poses(), None);
+
+            let current = get_counters();
+            assert!(
+                current > last,
+                "counter did not advance ({current} <= {last})"
+            );
+            last = current;
+        }
+    }
+
     #[test]
     fn zero_dims_texture() {
         let f = Fixture::new();
@@ -1819,6 +2688,44 @@ mod tests {
         assert_eq!(data.info.format, 0);
     }
 
+    #[test]
+    fn recommended_swapchain_format() {
+        use ash::vk;
+
+        let f = Fixture::new();
+        f.ensure_real_session(false);
+        let session = f.comp.openxr.session_data.get().session.as_raw();
+        let set_formats = |formats: &[vk::Format]| {
+            let formats: Vec<i64> = formats.iter().map(|fmt| fmt.as_raw() as i64).collect();
+            fakexr::set_swapchain_formats(session, &formats);
+        };
+
+        // sRGB formats are recommended as their UNORM equivalent.
+        set_formats(&[vk::Format::R8G8B8A8_SRGB, vk::Format::B8G8R8A8_UNORM]);
+        assert_eq!(
+            f.comp.recommended_swapchain_format(),
+            Some(TextureFormat {
+                texture_type: vr::ETextureType::Vulkan,
+                format: vk::Format::R8G8B8A8_UNORM.as_raw() as i64,
+                dxgi_format: 28, // DXGI_FORMAT_R8G8B8A8_UNORM
+            })
+        );
+
+        // Formats OpenVR can't submit are skipped in favor of the next one the runtime supports.
+        set_formats(&[vk::Format::D32_SFLOAT, vk::Format::B8G8R8A8_SRGB]);
+        assert_eq!(
+            f.comp.recommended_swapchain_format(),
+            Some(TextureFormat {
+                texture_type: vr::ETextureType::Vulkan,
+                format: vk::Format::B8G8R8A8_UNORM.as_raw() as i64,
+                dxgi_format: 87, // DXGI_FORMAT_B8G8R8A8_UNORM
+            })
+        );
+
+        set_formats(&[vk::Format::D32_SFLOAT]);
+        assert_eq!(f.comp.recommended_swapchain_format(), None);
+    }
+
     #[test]
     fn explicit_timing() {
         let f = Fixture::new();
@@ -1945,4 +2852,69 @@ mod tests {
         f.comp.PostPresentHandoff();
         f.check_frame_state(fakexr::FrameState::Ended);
     }
+
+    #[test]
+    fn overlay_submitted_as_quad_layer() {
+        use crate::overlay::OverlayMan;
+        use vr::IVROverlay027_Interface;
+
+        let f = Fixture::new();
+        let overlays = Arc::new(OverlayMan::new(f.comp.openxr.clone(), &Injector::default()));
+        f.comp.overlays.set(Arc::downgrade(&overlays));
+        overlays.compositor.set(Arc::downgrade(&f.comp));
+        f.ensure_real_session(false);
+
+        let submit_frame = || {
+            assert_eq!(f.submit(vr::EVREye::Left), None);
+            assert_eq!(f.submit(vr::EVREye::Right), None);
+            assert_eq!(f.wait_get_poses(), None);
+
+            let session = f.comp.openxr.session_data.get().session.as_raw();
+            fakexr::submitted_layer_types(session)
+        };
+
+        let mut overlay = 0;
+        assert_eq!(
+            overlays.CreateOverlay(
+                c"test_overlay".as_ptr(),
+                c"TestOverlay".as_ptr(),
+                &mut overlay
+            ),
+            vr::EVROverlayError::None
+        );
+        assert_eq!(
+            overlays.SetOverlayTexture(overlay, &FakeGraphicsData::texture(&f.vk)),
+            vr::EVROverlayError::None
+        );
+        let transform: vr::HmdMatrix34_t = xr::Posef {
+            orientation: xr::Quaternionf::IDENTITY,
+            position: xr::Vector3f {
+                x: 0.0,
+                y: 1.0,
+                z: -1.0,
+            },
+        }
+        .into();
+        assert_eq!(
+            overlays.SetOverlayTransformAbsolute(
+                overlay,
+                vr::ETrackingUniverseOrigin::Standing,
+                &transform
+            ),
+            vr::EVROverlayError::None
+        );
+
+        // Hidden overlays aren't submitted.
+        let layers = submit_frame();
+        assert!(!layers.contains(&xr::StructureType::COMPOSITION_LAYER_QUAD));
+
+        assert_eq!(overlays.ShowOverlay(overlay), vr::EVROverlayError::None);
+        let layers = submit_frame();
+        assert!(layers.contains(&xr::StructureType::COMPOSITION_LAYER_PROJECTION));
+        assert!(layers.contains(&xr::StructureType::COMPOSITION_LAYER_QUAD));
+
+        assert_eq!(overlays.DestroyOverlay(overlay), vr::EVROverlayError::None);
+        let layers = submit_frame();
+        assert!(!layers.contains(&xr::StructureType::COMPOSITION_LAYER_QUAD));
+    }
 }
@@ -14,6 +14,12 @@ pub trait GraphicsBackend: Into<SupportedBackend> {
 
     fn to_nice_format(format: <Self::Api as xr::Graphics>::Format) -> Self::NiceFormat;
 
+    /// The closest format an OpenVR texture can be submitted in for a runtime swapchain format.
+    /// sRGB formats map to their UNORM equivalents, because OpenVR gives the color space
+    /// separately. Returns None if OpenVR has no equivalent.
+    #[cfg(test)]
+    fn texture_format(format: <Self::Api as xr::Graphics>::Format) -> Option<TextureFormat>;
+
     fn session_create_info(&self) -> <Self::Api as xr::Graphics>::SessionCreateInfo;
 
     /// Returns None if the texture is invalid.
@@ -42,12 +48,67 @@ pub trait GraphicsBackend: Into<SupportedBackend> {
         submit_flags: vr::EVRSubmitFlags,
     ) -> xr::Extent2Di;
 
+    /// Info for a depth swapchain matching a depth texture submitted alongside a scene texture.
+    /// Returns None if this backend can't forward depth to the runtime.
+    fn depth_swapchain_info_for_texture(
+        &self,
+        _texture: Self::OpenVrTexture,
+        _bounds: vr::VRTextureBounds_t,
+    ) -> Option<xr::SwapchainCreateInfo<Self::Api>> {
+        None
+    }
+
+    fn store_depth_swapchain_images(
+        &mut self,
+        images: Vec<<Self::Api as xr::Graphics>::SwapchainImage>,
+    );
+
+    fn copy_depth_to_swapchain(
+        &self,
+        eye: vr::EVREye,
+        texture: Self::OpenVrTexture,
+        bounds: vr::VRTextureBounds_t,
+        image_index: usize,
+    ) -> xr::Extent2Di;
+
     fn copy_overlay_to_swapchain(
         &mut self,
         texture: Self::OpenVrTexture,
         bounds: vr::VRTextureBounds_t,
         image_index: usize,
     ) -> xr::Extent2Di;
+
+    /// Info for a single layer RGBA8 swapchain, for images we draw ourselves (e.g. the perf HUD).
+    fn rgba_swapchain_info(width: u32, height: u32) -> xr::SwapchainCreateInfo<Self::Api>;
+
+    /// Uploads tightly packed, top-down RGBA8 pixels to a swapchain image created with
+    /// rgba_swapchain_info. Blocks until the upload is complete.
+    fn upload_rgba_to_swapchain_image(
+        &self,
+        image: &<Self::Api as xr::Graphics>::SwapchainImage,
+        width: u32,
+        height: u32,
+        pixels: &[u8],
+    );
+}
+
+// DXGI_FORMAT values, from dxgiformat.h
+#[cfg(test)]
+const DXGI_FORMAT_R16G16B16A16_FLOAT: u32 = 10;
+#[cfg(test)]
+const DXGI_FORMAT_R8G8B8A8_UNORM: u32 = 28;
+#[cfg(test)]
+const DXGI_FORMAT_B8G8R8A8_UNORM: u32 = 87;
+
+/// A swapchain format in the terms OpenVR textures are described with.
+#[cfg(test)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TextureFormat {
+    pub texture_type: vr::ETextureType,
+    /// A VkFormat or OpenGL internal format, depending on the texture type.
+    pub format: i64,
+    /// The equivalent DXGI_FORMAT, for engines submitting D3D textures.
+    pub dxgi_format: u32,
 }
 
 #[derive(macros::Backends, TryInto, From)]
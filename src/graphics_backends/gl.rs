@@ -1,4 +1,6 @@
 use super::GraphicsBackend;
+#[cfg(test)]
+use super::{TextureFormat, DXGI_FORMAT_R16G16B16A16_FLOAT, DXGI_FORMAT_R8G8B8A8_UNORM};
 use derive_more::Deref;
 use glutin_glx_sys::{
     glx::{self, Glx},
@@ -16,6 +18,7 @@ static GLX: LazyLock<Library> = LazyLock::new(|| Library::new(c"libGLX.so.0"));
 pub struct GlData {
     session_data: Arc<SessionCreateInfo>,
     images: Vec<u32>,
+    depth_images: Vec<u32>,
     format: u32,
     read_fbo: u32,
     draw_fbo: u32,
@@ -131,6 +134,7 @@ impl GlData {
         Some(GlData {
             session_data: Arc::new(SessionCreateInfo(session_info)),
             images: Default::default(),
+            depth_images: Default::default(),
             format: 0,
             read_fbo: fbos[0],
             draw_fbo: fbos[1],
@@ -147,6 +151,20 @@ impl GraphicsBackend for GlData {
         format
     }
 
+    #[cfg(test)]
+    fn texture_format(format: u32) -> Option<TextureFormat> {
+        let (format, dxgi_format) = match Self::to_nice_format(format) {
+            gl::RGBA8 | gl::SRGB8_ALPHA8 => (gl::RGBA8, DXGI_FORMAT_R8G8B8A8_UNORM),
+            gl::RGBA16F => (gl::RGBA16F, DXGI_FORMAT_R16G16B16A16_FLOAT),
+            _ => return None,
+        };
+        Some(TextureFormat {
+            texture_type: vr::ETextureType::OpenGL,
+            format: format as _,
+            dxgi_format,
+        })
+    }
+
     fn session_create_info(&self) -> <Self::Api as openxr::Graphics>::SessionCreateInfo {
         // SAFETY: SessionCreateInfo should be Copy anyway but doesn't work right
         // https://github.com/Ralith/openxrs/issues/183
@@ -284,6 +302,72 @@ impl GraphicsBackend for GlData {
         extent
     }
 
+    fn depth_swapchain_info_for_texture(
+        &self,
+        texture: Self::OpenVrTexture,
+        bounds: vr::VRTextureBounds_t,
+    ) -> Option<xr::SwapchainCreateInfo<Self::Api>> {
+        let mut fmt = 0;
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, texture);
+            gl::GetTexLevelParameteriv(gl::TEXTURE_2D, 0, gl::TEXTURE_INTERNAL_FORMAT, &mut fmt);
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+        let xr::Rect2Di { extent, .. } = texture_rect_from_bounds(texture, bounds);
+
+        Some(xr::SwapchainCreateInfo {
+            create_flags: xr::SwapchainCreateFlags::EMPTY,
+            usage_flags: xr::SwapchainUsageFlags::DEPTH_STENCIL_ATTACHMENT
+                | xr::SwapchainUsageFlags::TRANSFER_DST,
+            format: fmt as u32,
+            sample_count: 1,
+            width: extent.width as u32,
+            height: extent.height as u32,
+            face_count: 1,
+            array_size: 2,
+            mip_count: 1,
+        })
+    }
+
+    #[inline]
+    fn store_depth_swapchain_images(&mut self, images: Vec<u32>) {
+        self.depth_images = images;
+    }
+
+    fn copy_depth_to_swapchain(
+        &self,
+        eye: vr::EVREye,
+        texture: Self::OpenVrTexture,
+        bounds: vr::VRTextureBounds_t,
+        image_index: usize,
+    ) -> xr::Extent2Di {
+        let swapchain_texture = self.depth_images[image_index];
+        let xr::Rect2Di { extent, offset } = texture_rect_from_bounds(texture, bounds);
+
+        // The depth swapchain is created with the texture's format, so a plain copy works.
+        unsafe {
+            gl::CopyImageSubData(
+                texture,
+                gl::TEXTURE_2D,
+                0, // level
+                offset.x,
+                offset.y,
+                0, // z
+                swapchain_texture,
+                gl::TEXTURE_2D_ARRAY,
+                0, // x
+                0, // y
+                0, // z
+                eye as i32,
+                extent.width,
+                extent.height,
+                1,
+            );
+        }
+
+        extent
+    }
+
     fn copy_overlay_to_swapchain(
         &mut self,
         texture: Self::OpenVrTexture,
@@ -299,6 +383,42 @@ impl GraphicsBackend for GlData {
             vr::EVRSubmitFlags::Default,
         )
     }
+
+    fn rgba_swapchain_info(width: u32, height: u32) -> xr::SwapchainCreateInfo<Self::Api> {
+        xr::SwapchainCreateInfo {
+            create_flags: xr::SwapchainCreateFlags::EMPTY,
+            usage_flags: xr::SwapchainUsageFlags::TRANSFER_DST,
+            format: gl::RGBA8,
+            sample_count: 1,
+            width,
+            height,
+            face_count: 1,
+            array_size: 1,
+            mip_count: 1,
+        }
+    }
+
+    fn upload_rgba_to_swapchain_image(&self, image: &u32, width: u32, height: u32, pixels: &[u8]) {
+        let row_size = width as usize * 4;
+        unsafe {
+            gl::BindTexture(gl::TEXTURE_2D, *image);
+            // GL textures start at the bottom left, so upload the rows in reverse.
+            for (row, data) in pixels.chunks_exact(row_size).enumerate() {
+                gl::TexSubImage2D(
+                    gl::TEXTURE_2D,
+                    0,
+                    0,
+                    (height as usize - 1 - row) as i32,
+                    width as i32,
+                    1,
+                    gl::RGBA,
+                    gl::UNSIGNED_BYTE,
+                    data.as_ptr().cast(),
+                );
+            }
+            gl::BindTexture(gl::TEXTURE_2D, 0);
+        }
+    }
 }
 
 fn texture_rect_from_bounds(
@@ -1,4 +1,9 @@
 use super::GraphicsBackend;
+#[cfg(test)]
+use super::{
+    TextureFormat, DXGI_FORMAT_B8G8R8A8_UNORM, DXGI_FORMAT_R16G16B16A16_FLOAT,
+    DXGI_FORMAT_R8G8B8A8_UNORM,
+};
 use ash::vk::{self, Handle};
 use log::warn;
 use openvr as vr;
@@ -15,6 +20,12 @@ struct RealSessionData {
     overlay_pipeline: Option<PipelineData>,
 }
 
+struct DepthSessionData {
+    images: Vec<vk::Image>,
+    pool: vk::CommandPool,
+    bufs: Vec<vk::CommandBuffer>,
+}
+
 pub struct VulkanData {
     _entry: ash::Entry,
     pub instance: ash::Instance,
@@ -23,6 +34,7 @@ pub struct VulkanData {
     pub queue: vk::Queue,
     pub queue_family_index: u32,
     real_data: Option<RealSessionData>,
+    depth_data: Option<DepthSessionData>,
 }
 
 impl Drop for VulkanData {
@@ -39,6 +51,9 @@ impl Drop for VulkanData {
             // Real session - the handles come from the app, only destroy the command pool we created
             Some(data) => unsafe {
                 self.device.destroy_command_pool(data.pool, None);
+                if let Some(depth) = &self.depth_data {
+                    self.device.destroy_command_pool(depth.pool, None);
+                }
                 if let Some(data) = &data.overlay_pipeline {
                     self.device.destroy_pipeline(data.pipeline, None);
                     self.device.destroy_pipeline_layout(data.layout, None);
@@ -61,6 +76,29 @@ impl GraphicsBackend for VulkanData {
         vk::Format::from_raw(format as _)
     }
 
+    #[cfg(test)]
+    fn texture_format(format: u32) -> Option<TextureFormat> {
+        // https://github.com/ValveSoftware/openvr/wiki/Vulkan#image-formats
+        let (format, dxgi_format) = match Self::to_nice_format(format) {
+            vk::Format::R8G8B8A8_UNORM | vk::Format::R8G8B8A8_SRGB => {
+                (vk::Format::R8G8B8A8_UNORM, DXGI_FORMAT_R8G8B8A8_UNORM)
+            }
+            vk::Format::B8G8R8A8_UNORM | vk::Format::B8G8R8A8_SRGB => {
+                (vk::Format::B8G8R8A8_UNORM, DXGI_FORMAT_B8G8R8A8_UNORM)
+            }
+            vk::Format::R16G16B16A16_SFLOAT => (
+                vk::Format::R16G16B16A16_SFLOAT,
+                DXGI_FORMAT_R16G16B16A16_FLOAT,
+            ),
+            _ => return None,
+        };
+        Some(TextureFormat {
+            texture_type: vr::ETextureType::Vulkan,
+            format: format.as_raw() as _,
+            dxgi_format,
+        })
+    }
+
     fn session_create_info(&self) -> <Self::Api as openxr::Graphics>::SessionCreateInfo {
         let queue_families = unsafe {
             self.instance
@@ -311,6 +349,163 @@ impl GraphicsBackend for VulkanData {
         }
     }
 
+    fn depth_swapchain_info_for_texture(
+        &self,
+        texture: *const vr::VRVulkanTextureData_t,
+        bounds: vr::VRTextureBounds_t,
+    ) -> Option<xr::SwapchainCreateInfo<Self::Api>> {
+        let texture = unsafe { texture.as_ref() }.unwrap();
+        // Multisampled depth can't be resolved with a plain copy.
+        if texture.m_nSampleCount > 1 {
+            return None;
+        }
+        let (extent, _) = texture_extent_from_bounds(texture, bounds);
+        Some(xr::SwapchainCreateInfo {
+            create_flags: xr::SwapchainCreateFlags::EMPTY,
+            usage_flags: xr::SwapchainUsageFlags::DEPTH_STENCIL_ATTACHMENT
+                | xr::SwapchainUsageFlags::TRANSFER_DST,
+            format: texture.m_nFormat,
+            sample_count: 1,
+            width: extent.width,
+            height: extent.height,
+            face_count: 1,
+            array_size: 2,
+            mip_count: 1,
+        })
+    }
+
+    fn store_depth_swapchain_images(&mut self, images: Vec<u64>) {
+        let images: Vec<vk::Image> = images.into_iter().map(vk::Image::from_raw).collect();
+        let pool = unsafe {
+            self.device
+                .create_command_pool(
+                    &vk::CommandPoolCreateInfo::default()
+                        .flags(vk::CommandPoolCreateFlags::RESET_COMMAND_BUFFER)
+                        .queue_family_index(self.queue_family_index),
+                    None,
+                )
+                .unwrap()
+        };
+        let bufs = unsafe {
+            self.device
+                .allocate_command_buffers(
+                    &vk::CommandBufferAllocateInfo::default()
+                        .command_pool(pool)
+                        .level(vk::CommandBufferLevel::PRIMARY)
+                        .command_buffer_count(images.len() as u32 * 2),
+                )
+                .unwrap()
+        };
+
+        if let Some(data) = self
+            .depth_data
+            .replace(DepthSessionData { images, pool, bufs })
+        {
+            unsafe {
+                self.device.destroy_command_pool(data.pool, None);
+            }
+        }
+    }
+
+    fn copy_depth_to_swapchain(
+        &self,
+        eye: vr::EVREye,
+        texture: *const vr::VRVulkanTextureData_t,
+        bounds: vr::VRTextureBounds_t,
+        image_index: usize,
+    ) -> xr::Extent2Di {
+        let texture = unsafe { texture.as_ref() }.unwrap();
+        let data = self.depth_data.as_ref().unwrap();
+        let swapchain_image = data.images[image_index];
+        let buf = data.bufs[2 * image_index + eye as usize];
+
+        let (extent, offset) = texture_extent_from_bounds(texture, bounds);
+        let format = vk::Format::from_raw(texture.m_nFormat as _);
+        let aspect_mask = match format {
+            vk::Format::D16_UNORM_S8_UINT
+            | vk::Format::D24_UNORM_S8_UINT
+            | vk::Format::D32_SFLOAT_S8_UINT => {
+                vk::ImageAspectFlags::DEPTH | vk::ImageAspectFlags::STENCIL
+            }
+            _ => vk::ImageAspectFlags::DEPTH,
+        };
+
+        self.record_commands(buf, || unsafe {
+            let swapchain_res = vk::ImageSubresourceRange {
+                aspect_mask,
+                base_mip_level: 0,
+                level_count: 1,
+                base_array_layer: eye as u32,
+                layer_count: 1,
+            };
+
+            self.device.cmd_pipeline_barrier(
+                buf,
+                vk::PipelineStageFlags::LATE_FRAGMENT_TESTS,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[vk::ImageMemoryBarrier {
+                    src_access_mask: vk::AccessFlags::DEPTH_STENCIL_ATTACHMENT_WRITE,
+                    dst_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+                    old_layout: vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL,
+                    new_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    image: swapchain_image,
+                    subresource_range: swapchain_res,
+                    ..Default::default()
+                }],
+            );
+
+            let subresource = vk::ImageSubresourceLayers {
+                aspect_mask,
+                mip_level: 0,
+                base_array_layer: 0,
+                layer_count: 1,
+            };
+            self.device.cmd_copy_image(
+                buf,
+                vk::Image::from_raw(texture.m_nImage),
+                vk::ImageLayout::TRANSFER_SRC_OPTIMAL,
+                swapchain_image,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                &[vk::ImageCopy {
+                    src_subresource: subresource,
+                    src_offset: offset,
+                    dst_subresource: vk::ImageSubresourceLayers {
+                        base_array_layer: eye as u32,
+                        ..subresource
+                    },
+                    dst_offset: vk::Offset3D::default(),
+                    extent,
+                }],
+            );
+
+            self.device.cmd_pipeline_barrier(
+                buf,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[vk::ImageMemoryBarrier {
+                    src_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+                    dst_access_mask: vk::AccessFlags::empty(),
+                    old_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    new_layout: vk::ImageLayout::DEPTH_STENCIL_ATTACHMENT_OPTIMAL,
+                    image: swapchain_image,
+                    subresource_range: swapchain_res,
+                    ..Default::default()
+                }],
+            );
+        });
+
+        xr::Extent2Di {
+            width: extent.width as _,
+            height: extent.height as _,
+        }
+    }
+
     fn copy_overlay_to_swapchain(
         &mut self,
         texture: *const vr::VRVulkanTextureData_t,
@@ -460,6 +655,172 @@ impl GraphicsBackend for VulkanData {
             height: extent.height as _,
         }
     }
+
+    fn rgba_swapchain_info(width: u32, height: u32) -> xr::SwapchainCreateInfo<Self::Api> {
+        xr::SwapchainCreateInfo {
+            create_flags: xr::SwapchainCreateFlags::EMPTY,
+            usage_flags: xr::SwapchainUsageFlags::COLOR_ATTACHMENT
+                | xr::SwapchainUsageFlags::TRANSFER_DST,
+            format: vk::Format::R8G8B8A8_UNORM.as_raw() as _,
+            sample_count: 1,
+            width,
+            height,
+            face_count: 1,
+            array_size: 1,
+            mip_count: 1,
+        }
+    }
+
+    fn upload_rgba_to_swapchain_image(&self, image: &u64, width: u32, height: u32, pixels: &[u8]) {
+        let image = vk::Image::from_raw(*image);
+        let size = pixels.len() as vk::DeviceSize;
+
+        // Stage the pixels in a host visible buffer
+        let (buffer, memory) = unsafe {
+            let buffer = self
+                .device
+                .create_buffer(
+                    &vk::BufferCreateInfo::default()
+                        .size(size)
+                        .usage(vk::BufferUsageFlags::TRANSFER_SRC)
+                        .sharing_mode(vk::SharingMode::EXCLUSIVE),
+                    None,
+                )
+                .unwrap();
+            let requirements = self.device.get_buffer_memory_requirements(buffer);
+            let memory_properties = self
+                .instance
+                .get_physical_device_memory_properties(self.physical_device);
+            let wanted =
+                vk::MemoryPropertyFlags::HOST_VISIBLE | vk::MemoryPropertyFlags::HOST_COHERENT;
+            let memory_type = (0..memory_properties.memory_type_count)
+                .find(|idx| {
+                    requirements.memory_type_bits & (1 << idx) != 0
+                        && memory_properties.memory_types[*idx as usize]
+                            .property_flags
+                            .contains(wanted)
+                })
+                .expect("No host visible memory type available");
+            let memory = self
+                .device
+                .allocate_memory(
+                    &vk::MemoryAllocateInfo::default()
+                        .allocation_size(requirements.size)
+                        .memory_type_index(memory_type),
+                    None,
+                )
+                .unwrap();
+            self.device.bind_buffer_memory(buffer, memory, 0).unwrap();
+
+            let ptr = self
+                .device
+                .map_memory(memory, 0, size, vk::MemoryMapFlags::empty())
+                .unwrap();
+            std::ptr::copy_nonoverlapping(pixels.as_ptr(), ptr.cast::<u8>(), pixels.len());
+            self.device.unmap_memory(memory);
+
+            (buffer, memory)
+        };
+
+        let pool = unsafe {
+            self.device
+                .create_command_pool(
+                    &vk::CommandPoolCreateInfo::default()
+                        .flags(vk::CommandPoolCreateFlags::TRANSIENT)
+                        .queue_family_index(self.queue_family_index),
+                    None,
+                )
+                .unwrap()
+        };
+        let buf = unsafe {
+            self.device
+                .allocate_command_buffers(
+                    &vk::CommandBufferAllocateInfo::default()
+                        .command_pool(pool)
+                        .level(vk::CommandBufferLevel::PRIMARY)
+                        .command_buffer_count(1),
+                )
+                .unwrap()[0]
+        };
+
+        self.record_commands(buf, || unsafe {
+            let subresource_range = vk::ImageSubresourceRange {
+                aspect_mask: vk::ImageAspectFlags::COLOR,
+                base_mip_level: 0,
+                level_count: 1,
+                base_array_layer: 0,
+                layer_count: 1,
+            };
+
+            // The old contents are being overwritten anyway, so we can discard them
+            self.device.cmd_pipeline_barrier(
+                buf,
+                vk::PipelineStageFlags::TOP_OF_PIPE,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[vk::ImageMemoryBarrier {
+                    src_access_mask: vk::AccessFlags::empty(),
+                    dst_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+                    old_layout: vk::ImageLayout::UNDEFINED,
+                    new_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    image,
+                    subresource_range,
+                    ..Default::default()
+                }],
+            );
+
+            self.device.cmd_copy_buffer_to_image(
+                buf,
+                buffer,
+                image,
+                vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                &[vk::BufferImageCopy {
+                    buffer_offset: 0,
+                    buffer_row_length: 0,
+                    buffer_image_height: 0,
+                    image_subresource: vk::ImageSubresourceLayers {
+                        aspect_mask: vk::ImageAspectFlags::COLOR,
+                        mip_level: 0,
+                        base_array_layer: 0,
+                        layer_count: 1,
+                    },
+                    image_offset: vk::Offset3D::default(),
+                    image_extent: vk::Extent3D {
+                        width,
+                        height,
+                        depth: 1,
+                    },
+                }],
+            );
+
+            self.device.cmd_pipeline_barrier(
+                buf,
+                vk::PipelineStageFlags::TRANSFER,
+                vk::PipelineStageFlags::BOTTOM_OF_PIPE,
+                vk::DependencyFlags::empty(),
+                &[],
+                &[],
+                &[vk::ImageMemoryBarrier {
+                    src_access_mask: vk::AccessFlags::TRANSFER_WRITE,
+                    dst_access_mask: vk::AccessFlags::empty(),
+                    old_layout: vk::ImageLayout::TRANSFER_DST_OPTIMAL,
+                    new_layout: vk::ImageLayout::COLOR_ATTACHMENT_OPTIMAL,
+                    image,
+                    subresource_range,
+                    ..Default::default()
+                }],
+            );
+        });
+
+        unsafe {
+            self.device.queue_wait_idle(self.queue).unwrap();
+            self.device.destroy_command_pool(pool, None);
+            self.device.destroy_buffer(buffer, None);
+            self.device.free_memory(memory, None);
+        }
+    }
 }
 impl VulkanData {
     pub fn record_commands(&self, buf: vk::CommandBuffer, cmds: impl FnOnce()) {
@@ -511,6 +872,7 @@ impl VulkanData {
             queue: vk::Queue::from_raw(data.m_pQueue as _),
             queue_family_index: data.m_nQueueFamilyIndex,
             real_data: Default::default(),
+            depth_data: Default::default(),
         }
     }
 
@@ -591,6 +953,7 @@ impl VulkanData {
             queue,
             queue_family_index,
             real_data: Default::default(),
+            depth_data: Default::default(),
         }
     }
 }
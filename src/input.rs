@@ -2,6 +2,7 @@ mod action_manifest;
 mod custom_bindings;
 mod devices;
 mod legacy;
+mod pose_log;
 mod profiles;
 mod skeletal;
 
@@ -11,7 +12,7 @@ mod tests;
 pub use devices::TrackedDeviceType;
 pub use profiles::{InteractionProfile, Profiles};
 
-use devices::{SubactionPaths, TrackedDevice, TrackedDeviceList};
+use devices::{SubactionPaths, TrackedDevice, TrackedDeviceList, VelocityLimit};
 use skeletal::FingerState;
 use skeletal::SkeletalInputActionData;
 
@@ -20,11 +21,12 @@ use crate::{
     tracy_span, AtomicF32,
 };
 use custom_bindings::{BindingData, GrabActions};
-use glam::Quat;
+use glam::{EulerRot, Mat4, Quat, Vec3};
 use legacy::LegacyActionData;
 use log::{debug, info, trace, warn};
 use openvr as vr;
 use openxr as xr;
+use pose_log::PoseLog;
 use slotmap::{new_key_type, Key, KeyData, SecondaryMap, SlotMap};
 use std::collections::{HashMap, HashSet, VecDeque};
 use std::ffi::{c_char, CStr, CString};
@@ -41,6 +43,23 @@ new_key_type! {
     struct ActionSetKey;
 }
 
+/// OpenXR has no way to query the frequencies a controller supports, so this is just beyond the
+/// range of common controller actuators - the runtime will clamp it further if needed.
+const MAX_HAPTIC_FREQUENCY: f32 = 1000.0;
+
+/// Squeeze value at which the legacy grip button is pressed on controllers that only have an
+/// analog squeeze (e.g. Touch controllers).
+const DEFAULT_LEGACY_GRIP_THRESHOLD: f32 = 0.7;
+
+/// Trigger value at which the legacy trigger button is pressed on controllers that only have an
+/// analog trigger (e.g. Touch controllers).
+const DEFAULT_LEGACY_TRIGGER_THRESHOLD: f32 = 0.75;
+
+/// Added by XR_EXT_eye_gaze_interaction. Gaze isn't tied to a controller, so this isn't one of the
+/// interaction profiles in [`Profiles`], but pose actions can still be bound to its gaze pose.
+const EYE_GAZE_PROFILE_PATH: &str = "/interaction_profiles/ext/eye_gaze_interaction";
+const EYE_GAZE_POSE_PATH: &str = "/user/eyes_ext/input/gaze_ext/pose";
+
 #[derive(macros::InterfaceImpl)]
 #[interface = "IVRInput"]
 #[versions(010, 007, 006, 005)]
@@ -52,7 +71,14 @@ pub struct Input<C: openxr_data::Compositor> {
     right_hand_key: InputSourceKey,
     action_map: RwLock<SlotMap<ActionKey, Action>>,
     set_map: RwLock<SlotMap<ActionSetKey, String>>,
-    loaded_actions_path: OnceLock<PathBuf>,
+    /// The action sets passed to the last UpdateActionState call
+    active_sets: RwLock<HashSet<ActionSetKey>>,
+    /// Actions sharing an input source with an action in a higher priority active set, which
+    /// should be reported as inactive
+    masked_actions: RwLock<HashSet<ActionKey>>,
+    loaded_actions_path: RwLock<Option<PathBuf>>,
+    /// Manifest to fall back to if the game never provides one - see XRIZER_DEFAULT_ACTION_MANIFEST
+    default_manifest_path: OnceLock<Option<PathBuf>>,
     legacy_state: legacy::LegacyState,
     skeletal_tracking_level: RwLock<vr::EVRSkeletalTrackingLevel>,
     profile_map: HashMap<xr::Path, &'static profiles::ProfileProperties>,
@@ -61,6 +87,37 @@ pub struct Input<C: openxr_data::Compositor> {
     events: Mutex<VecDeque<InputEvent>>,
     devices: RwLock<TrackedDeviceList>,
     loading_actions: AtomicBool,
+    /// Only set if XRIZER_POSE_LOG is set
+    pose_log: OnceLock<Mutex<PoseLog>>,
+    /// Vector2 values with a magnitude below this are reported as zero
+    thumbstick_deadzone: AtomicF32,
+    /// Swaps which controller is reported for the left and right controller roles
+    swap_hands: AtomicBool,
+    /// The legacy axis (rAxis index) squeeze force is reported on, if any
+    legacy_grip_force_axis: Mutex<Option<usize>>,
+    /// Squeeze value at which the legacy grip button is pressed, for controllers without a grip button
+    legacy_grip_threshold: AtomicF32,
+    /// Trigger value at which the legacy trigger is clicked, for controllers without a trigger click
+    legacy_trigger_threshold: AtomicF32,
+    /// Set by the game via SetDominantHand, used for hand selection priority
+    dominant_hand: Mutex<Hand>,
+    /// Which pose is reported for controllers (XRIZER_CONTROLLER_POSE)
+    controller_pose_mode: ControllerPoseMode,
+    /// Extra offsets applied to the left and right controller poses (XRIZER_CONTROLLER_OFFSET)
+    controller_offsets: Mutex<[Mat4; 2]>,
+    /// Report the Index controller thumbstick as a trackpad (XRIZER_TRACKPAD_EMULATION)
+    trackpad_emulation: AtomicBool,
+    /// Emulate A/B/X/Y with the Vive wand trackpad quadrants in legacy input (XRIZER_TRACKPAD_BUTTONS)
+    trackpad_buttons: AtomicBool,
+    /// Interaction profiles that won't have bindings suggested (XRIZER_DISABLED_PROFILES)
+    disabled_profiles: RwLock<HashSet<String>>,
+    /// Binding path prefixes that are suggested as is, without profile path translation
+    /// (XRIZER_UNTRANSLATED_PATHS)
+    untranslated_paths: RwLock<Vec<String>>,
+    /// The input path the built-in recenter action is bound to (XRIZER_RECENTER_BINDING)
+    recenter_binding: RwLock<Option<String>>,
+    /// Set when the recenter action is pressed, handled at the start of the next frame
+    recenter_requested: AtomicBool,
 }
 
 struct InputEvent {
@@ -99,7 +156,40 @@ impl<T> Drop for WriteOnDrop<T> {
 
 impl<C: openxr_data::Compositor> Input<C> {
     pub fn new(openxr: Arc<OpenXrData<C>>) -> Self {
-        let devices = RwLock::new(TrackedDeviceList::new());
+        let pose_smoothing = std::env::var("XRIZER_POSE_SMOOTHING")
+            .ok()
+            .and_then(|strength| {
+                strength
+                    .parse::<f32>()
+                    .ok()
+                    .filter(|strength| (0.0..1.0).contains(strength))
+                    .or_else(|| {
+                        warn!("Invalid XRIZER_POSE_SMOOTHING ({strength})");
+                        None
+                    })
+            })
+            .unwrap_or(0.0);
+        let max_velocity = |var: &str| {
+            std::env::var(var).ok().and_then(|max| {
+                max.parse::<f32>()
+                    .ok()
+                    .filter(|max| *max > 0.0)
+                    .or_else(|| {
+                        warn!("Invalid {var} ({max})");
+                        None
+                    })
+            })
+        };
+        let velocity_limit = VelocityLimit {
+            linear: max_velocity("XRIZER_MAX_VELOCITY"),
+            angular: max_velocity("XRIZER_MAX_ANGULAR_VELOCITY"),
+        };
+        let mut devices = TrackedDeviceList::new(pose_smoothing, velocity_limit);
+        if std::env::var("XRIZER_FORCE_CONNECTED_CONTROLLERS").is_ok_and(|s| s == "1") {
+            info!("Forcing controllers to be connected");
+            devices.force_connect_controllers();
+        }
+        let devices = RwLock::new(devices);
         let mut map = SlotMap::with_key();
         let left_hand_key = map.insert(c"/user/hand/left".into());
         let right_hand_key = map.insert(c"/user/hand/right".into());
@@ -116,10 +206,15 @@ impl<C: openxr_data::Compositor> Input<C> {
                 )
             })
             .collect();
+        let controller_pose_mode = ControllerPoseMode::from_env();
+        let controller_offsets = controller_offsets_from_env();
         let pose_data = PoseData::new(
             &openxr.instance,
             subaction_paths.left,
             subaction_paths.right,
+            controller_pose_mode,
+            controller_offsets,
+            openxr.enabled_extensions.ext_eye_gaze_interaction,
         );
         openxr
             .session_data
@@ -135,11 +230,14 @@ impl<C: openxr_data::Compositor> Input<C> {
             input_source_map: RwLock::new(map),
             action_map: Default::default(),
             set_map: Default::default(),
+            active_sets: Default::default(),
+            masked_actions: Default::default(),
             devices,
-            loaded_actions_path: OnceLock::new(),
+            loaded_actions_path: RwLock::default(),
+            default_manifest_path: OnceLock::new(),
             left_hand_key,
             right_hand_key,
-            legacy_state: Default::default(),
+            legacy_state: legacy::LegacyState::new(),
             skeletal_tracking_level: RwLock::new(vr::EVRSkeletalTrackingLevel::Estimated),
             profile_map,
             estimated_finger_state: [
@@ -149,6 +247,270 @@ impl<C: openxr_data::Compositor> Input<C> {
             subaction_paths,
             events: Mutex::default(),
             loading_actions: false.into(),
+            pose_log: PoseLog::from_env()
+                .map(|log| OnceLock::from(Mutex::new(log)))
+                .unwrap_or_default(),
+            thumbstick_deadzone: std::env::var("XRIZER_THUMBSTICK_DEADZONE")
+                .ok()
+                .and_then(|deadzone| {
+                    deadzone
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|deadzone| (0.0..1.0).contains(deadzone))
+                        .or_else(|| {
+                            warn!("Invalid XRIZER_THUMBSTICK_DEADZONE ({deadzone})");
+                            None
+                        })
+                })
+                .unwrap_or(0.0)
+                .into(),
+            dominant_hand: Mutex::new(Hand::Right),
+            swap_hands: std::env::var("XRIZER_SWAP_HANDS")
+                .is_ok_and(|swap| swap == "1")
+                .into(),
+            legacy_grip_force_axis: std::env::var("XRIZER_LEGACY_GRIP_FORCE_AXIS")
+                .ok()
+                .and_then(|axis| {
+                    axis.parse::<usize>()
+                        .ok()
+                        .filter(|axis| *axis < vr::k_unControllerStateAxisCount as usize)
+                        .or_else(|| {
+                            warn!("Invalid XRIZER_LEGACY_GRIP_FORCE_AXIS ({axis})");
+                            None
+                        })
+                })
+                .into(),
+            legacy_grip_threshold: std::env::var("XRIZER_LEGACY_GRIP_THRESHOLD")
+                .ok()
+                .and_then(|threshold| {
+                    threshold
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|threshold| (0.0..=1.0).contains(threshold))
+                        .or_else(|| {
+                            warn!("Invalid XRIZER_LEGACY_GRIP_THRESHOLD ({threshold})");
+                            None
+                        })
+                })
+                .unwrap_or(DEFAULT_LEGACY_GRIP_THRESHOLD)
+                .into(),
+            legacy_trigger_threshold: std::env::var("XRIZER_LEGACY_TRIGGER_THRESHOLD")
+                .ok()
+                .and_then(|threshold| {
+                    threshold
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|threshold| (0.0..=1.0).contains(threshold))
+                        .or_else(|| {
+                            warn!("Invalid XRIZER_LEGACY_TRIGGER_THRESHOLD ({threshold})");
+                            None
+                        })
+                })
+                .unwrap_or(DEFAULT_LEGACY_TRIGGER_THRESHOLD)
+                .into(),
+            controller_pose_mode,
+            controller_offsets: Mutex::new(controller_offsets),
+            trackpad_emulation: std::env::var("XRIZER_TRACKPAD_EMULATION")
+                .is_ok_and(|s| s == "1")
+                .into(),
+            trackpad_buttons: std::env::var("XRIZER_TRACKPAD_BUTTONS")
+                .is_ok_and(|s| s == "1")
+                .into(),
+            disabled_profiles: std::env::var("XRIZER_DISABLED_PROFILES")
+                .map(|profiles| {
+                    profiles
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|path| !path.is_empty())
+                        .filter(|path| {
+                            let known = Profiles::get().profile_from_name(path).is_some();
+                            if !known {
+                                warn!("Unknown profile in XRIZER_DISABLED_PROFILES ({path})");
+                            }
+                            known
+                        })
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default()
+                .into(),
+            recenter_binding: std::env::var("XRIZER_RECENTER_BINDING")
+                .ok()
+                .map(|path| path.trim().to_string())
+                .filter(|path| !path.is_empty())
+                .into(),
+            recenter_requested: false.into(),
+            untranslated_paths: std::env::var("XRIZER_UNTRANSLATED_PATHS")
+                .map(|paths| {
+                    paths
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|path| !path.is_empty())
+                        .map(String::from)
+                        .collect()
+                })
+                .unwrap_or_default()
+                .into(),
+        }
+    }
+
+    /// Whether bindings should be suggested for this profile.
+    fn is_profile_enabled(&self, profile: &dyn InteractionProfile) -> bool {
+        profile.is_supported(&self.openxr.enabled_extensions)
+            && !self
+                .disabled_profiles
+                .read()
+                .unwrap()
+                .contains(profile.profile_path())
+    }
+
+    /// Stops bindings from being suggested for the profile at this path, starting with the next
+    /// time bindings are loaded.
+    #[cfg(test)]
+    pub fn disable_profile(&self, path: &str) {
+        self.disabled_profiles
+            .write()
+            .unwrap()
+            .insert(path.to_string());
+    }
+
+    /// Stops bindings with paths starting with this prefix from being translated, starting with
+    /// the next time bindings are loaded.
+    #[cfg(test)]
+    pub fn add_untranslated_path(&self, prefix: &str) {
+        self.untranslated_paths
+            .write()
+            .unwrap()
+            .push(prefix.to_string());
+    }
+
+    /// Binds the recenter action to this input path, starting with the next time bindings are
+    /// loaded.
+    #[cfg(test)]
+    pub fn set_recenter_binding(&self, path: &str) {
+        *self.recenter_binding.write().unwrap() = Some(path.to_string());
+    }
+
+    /// The binding for the recenter action in this profile, if one is configured and the profile
+    /// has that input.
+    fn recenter_binding(&self, profile: &dyn InteractionProfile) -> Option<xr::Path> {
+        let binding = self.recenter_binding.read().unwrap();
+        let binding = binding.as_deref()?;
+        if !profile.legal_paths().iter().any(|path| path == binding) {
+            debug!(
+                "{} has no input {binding}, not binding recenter action",
+                profile.profile_path()
+            );
+            return None;
+        }
+        Some(self.openxr.instance.string_to_path(binding).unwrap())
+    }
+
+    /// Requests a recenter if the recenter action was pressed in the last action sync.
+    fn check_recenter_action(&self, data: &SessionData) {
+        if self.recenter_binding.read().unwrap().is_none() {
+            return;
+        }
+        let recenter = &data.input_data.pose_data.get().unwrap().recenter;
+        let Ok(state) = recenter
+            .state(&data.session, xr::Path::NULL)
+            .inspect_err(|e| warn!("Couldn't get recenter action state: {e}"))
+        else {
+            return;
+        };
+        if state.changed_since_last_sync && state.current_state {
+            debug!("recenter action pressed");
+            self.recenter_requested.store(true, Ordering::Relaxed);
+        }
+    }
+
+    #[cfg(test)]
+    pub fn set_thumbstick_deadzone(&self, deadzone: f32) {
+        assert!((0.0..1.0).contains(&deadzone));
+        self.thumbstick_deadzone.store(deadzone);
+    }
+
+    /// Smooths the poses of tracked devices other than the headset. 0 disables smoothing, values
+    /// closer to 1 smooth more at the cost of latency.
+    #[cfg(test)]
+    pub fn set_pose_smoothing(&self, strength: f32) {
+        assert!((0.0..1.0).contains(&strength));
+        self.devices.write().unwrap().set_pose_smoothing(strength);
+    }
+
+    #[cfg(test)]
+    pub fn force_connect_controllers(&self) {
+        self.devices.write().unwrap().force_connect_controllers();
+    }
+
+    /// Decouples legacy action syncing from the frame rate, syncing at a fixed rate (in Hz) instead.
+    #[cfg(test)]
+    pub fn set_input_rate(&self, rate: Option<f32>) {
+        assert!(rate.is_none_or(|rate| rate > 0.0));
+        self.legacy_state.set_sync_rate(rate);
+    }
+
+    /// Sets which legacy axis squeeze force is reported on, for controllers that have it.
+    #[cfg(test)]
+    pub fn set_legacy_grip_force_axis(&self, axis: Option<usize>) {
+        assert!(axis.is_none_or(|axis| axis < vr::k_unControllerStateAxisCount as usize));
+        *self.legacy_grip_force_axis.lock().unwrap() = axis;
+    }
+
+    /// Sets the squeeze value the legacy grip button is synthesized at.
+    #[cfg(test)]
+    pub fn set_legacy_grip_threshold(&self, threshold: f32) {
+        assert!((0.0..=1.0).contains(&threshold));
+        self.legacy_grip_threshold.store(threshold);
+    }
+
+    /// Sets the trigger value the legacy trigger click is synthesized at.
+    #[cfg(test)]
+    pub fn set_legacy_trigger_threshold(&self, threshold: f32) {
+        assert!((0.0..=1.0).contains(&threshold));
+        self.legacy_trigger_threshold.store(threshold);
+    }
+
+    #[cfg(test)]
+    pub fn set_trackpad_emulation(&self, enabled: bool) {
+        self.trackpad_emulation.store(enabled, Ordering::Relaxed);
+    }
+
+    #[cfg(test)]
+    pub fn set_trackpad_buttons(&self, enabled: bool) {
+        self.trackpad_buttons.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Sets an offset applied to the controller's pose for this hand, in the controller's space.
+    #[cfg(test)]
+    pub fn set_controller_offset(&self, hand: Hand, offset: Mat4) {
+        self.controller_offsets.lock().unwrap()[hand as usize - 1] = offset;
+        let data = self.openxr.session_data.get();
+        if let Some(pose_data) = data.input_data.pose_data.get() {
+            let space = match hand {
+                Hand::Left => &pose_data.left_space,
+                Hand::Right => &pose_data.right_space,
+            };
+            *space.offset.lock().unwrap() = offset;
+            space.reset_raw();
+        }
+    }
+
+    /// Changes which pose is reported for controllers, for the current session only.
+    #[cfg(test)]
+    pub fn set_controller_pose_mode(&self, mode: ControllerPoseMode) {
+        let data = self.openxr.session_data.get();
+        let pose_data = data.input_data.pose_data.get().unwrap();
+        *pose_data.mode.lock().unwrap() = mode;
+        pose_data.reset_spaces();
+    }
+
+    fn apply_thumbstick_deadzone(&self, value: xr::Vector2f) -> xr::Vector2f {
+        let deadzone = self.thumbstick_deadzone.load();
+        if value.x.hypot(value.y) < deadzone {
+            xr::Vector2f::default()
+        } else {
+            value
         }
     }
 
@@ -159,6 +521,89 @@ impl<C: openxr_data::Compositor> Input<C> {
         }
     }
 
+    /// Whether the action set the action belongs to was activated in the last UpdateActionState.
+    fn is_action_set_active(&self, action: vr::VRActionHandle_t) -> bool {
+        let key = ActionKey::from(KeyData::from_ffi(action));
+        let action_map = self.action_map.read().unwrap();
+        let Some(Action { path }) = action_map.get(key) else {
+            return false;
+        };
+
+        let set_map = self.set_map.read().unwrap();
+        self.active_sets.read().unwrap().iter().any(|set| {
+            set_map
+                .get(*set)
+                .and_then(|set| path.strip_prefix(set.as_str()))
+                .is_some_and(|rest| rest.starts_with('/'))
+        })
+    }
+
+    /// Whether the action is overridden by an action in a higher priority set bound to the same
+    /// input source, as of the last UpdateActionState.
+    fn is_action_masked(&self, action: vr::VRActionHandle_t) -> bool {
+        let key = ActionKey::from(KeyData::from_ffi(action));
+        self.masked_actions.read().unwrap().contains(&key)
+    }
+
+    /// Finds the actions that should be masked by higher priority action sets. OpenXR only
+    /// supports action set priorities at creation time, but OpenVR sets them every sync.
+    fn update_masked_actions(
+        &self,
+        loaded: &ManifestLoadedActions,
+        set_priorities: &[(ActionSetKey, i32)],
+        profiles: impl IntoIterator<Item = xr::Path>,
+    ) {
+        let mut masked = self.masked_actions.write().unwrap();
+        masked.clear();
+        if set_priorities
+            .iter()
+            .all(|(_, priority)| *priority == set_priorities[0].1)
+        {
+            return;
+        }
+
+        let action_map = self.action_map.read().unwrap();
+        let set_map = self.set_map.read().unwrap();
+        let action_priority = |key| {
+            let Action { path } = action_map.get(key)?;
+            set_priorities
+                .iter()
+                .filter(|(set, _)| {
+                    set_map
+                        .get(*set)
+                        .and_then(|set| path.strip_prefix(set.as_str()))
+                        .is_some_and(|rest| rest.starts_with('/'))
+                })
+                .map(|(_, priority)| *priority)
+                .max()
+        };
+
+        for profile in profiles {
+            let Some(sources) = loaded.per_profile_bound_sources.get(&profile) else {
+                continue;
+            };
+
+            let priorities: Vec<_> = sources
+                .iter()
+                .filter_map(|(key, paths)| Some((key, paths, action_priority(key)?)))
+                .collect();
+            let mut highest: HashMap<xr::Path, i32> = HashMap::new();
+            for (_, paths, priority) in &priorities {
+                for path in *paths {
+                    let highest = highest.entry(*path).or_insert(*priority);
+                    *highest = (*highest).max(*priority);
+                }
+            }
+
+            masked.extend(
+                priorities
+                    .into_iter()
+                    .filter(|(_, paths, priority)| paths.iter().any(|p| highest[p] > *priority))
+                    .map(|(key, _, _)| key),
+            );
+        }
+    }
+
     fn subaction_path_from_handle(&self, handle: vr::VRInputValueHandle_t) -> Option<xr::Path> {
         if handle == vr::k_ulInvalidInputValueHandle {
             Some(xr::Path::NULL)
@@ -267,7 +712,7 @@ impl InputSessionData {
     fn get_legacy_actions(&self) -> Option<&LegacyActionData> {
         match self.actions.get()? {
             LoadedActions::Legacy(l) => Some(l),
-            _ => None,
+            LoadedActions::Manifest(m) => m.legacy.as_ref(),
         }
     }
 
@@ -310,6 +755,7 @@ struct ExtraActionData {
 struct BoundPose {
     left: Option<BoundPoseType>,
     right: Option<BoundPoseType>,
+    gaze: bool,
 }
 
 #[derive(Clone, Copy, Debug)]
@@ -483,14 +929,14 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
     }
     fn TriggerHapticVibrationAction(
         &self,
-        action: vr::VRActionHandle_t,
+        handle: vr::VRActionHandle_t,
         start_seconds_from_now: f32,
         duration_seconds: f32,
         frequency: f32,
         amplitude: f32,
         restrict_to_device: vr::VRInputValueHandle_t,
     ) -> vr::EVRInputError {
-        get_action_from_handle!(self, action, session_data, action);
+        get_action_from_handle!(self, handle, session_data, action);
         let Some(subaction_path) = self.subaction_path_from_handle(restrict_to_device) else {
             return vr::EVRInputError::None;
         };
@@ -499,16 +945,33 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
             return vr::EVRInputError::WrongType;
         };
 
+        if !self.is_action_set_active(handle) {
+            trace!("not triggering haptic action {handle}, its action set is inactive");
+            return vr::EVRInputError::None;
+        }
+
         if start_seconds_from_now > 0.0 {
             warn!("start_seconds_from_now: {start_seconds_from_now}")
         }
 
+        // A frequency of 0 means the default in both OpenVR and OpenXR.
+        let frequency = if frequency.is_finite() && frequency > 0.0 {
+            frequency.min(MAX_HAPTIC_FREQUENCY)
+        } else {
+            xr::FREQUENCY_UNSPECIFIED
+        };
+        let amplitude = if amplitude.is_nan() {
+            0.0
+        } else {
+            amplitude.clamp(0.0, 1.0)
+        };
+
         action
             .apply_feedback(
                 &session_data.session,
                 subaction_path,
                 &xr::HapticVibration::new()
-                    .amplitude(amplitude.clamp(0.0, 1.0))
+                    .amplitude(amplitude)
                     .frequency(frequency)
                     .duration(xr::Duration::from_nanos((duration_seconds * 1e9) as _)),
             )
@@ -556,7 +1019,7 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
         &self,
         handle: vr::VRActionHandle_t,
         transform_space: vr::EVRSkeletalTransformSpace,
-        _motion_range: vr::EVRSkeletalMotionRange,
+        motion_range: vr::EVRSkeletalMotionRange,
         transform_array: *mut vr::VRBoneTransform_t,
         transform_array_count: u32,
     ) -> vr::EVRInputError {
@@ -577,12 +1040,19 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
             self.get_bones_from_hand_tracking(
                 &session_data,
                 transform_space,
+                motion_range,
                 hand_tracker,
                 *hand,
                 transforms,
             )
         } else {
-            self.get_estimated_bones(&session_data, transform_space, *hand, transforms);
+            self.get_estimated_bones(
+                &session_data,
+                transform_space,
+                motion_range,
+                *hand,
+                transforms,
+            );
         }
 
         vr::EVRInputError::None
@@ -670,12 +1140,18 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
 
         vr::EVRInputError::None
     }
-    fn SetDominantHand(&self, _: vr::ETrackedControllerRole) -> vr::EVRInputError {
-        crate::warn_unimplemented!("SetDominantHand");
+    fn SetDominantHand(&self, role: vr::ETrackedControllerRole) -> vr::EVRInputError {
+        let Ok(hand) = Hand::try_from(role) else {
+            return vr::EVRInputError::InvalidParam;
+        };
+        *self.dominant_hand.lock().unwrap() = hand;
         vr::EVRInputError::None
     }
-    fn GetDominantHand(&self, _: *mut vr::ETrackedControllerRole) -> vr::EVRInputError {
-        crate::warn_unimplemented!("GetDominantHand");
+    fn GetDominantHand(&self, role: *mut vr::ETrackedControllerRole) -> vr::EVRInputError {
+        let Some(role) = (unsafe { role.as_mut() }) else {
+            return vr::EVRInputError::InvalidParam;
+        };
+        *role = (*self.dominant_hand.lock().unwrap()).into();
         vr::EVRInputError::None
     }
     fn GetSkeletalActionData(
@@ -794,6 +1270,19 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
                     no_data!()
                 };
 
+                // Gaze isn't tied to either hand, so it's only reported when not restricted to one.
+                if hand.is_none() && bound.gaze {
+                    let pose = self.get_gaze_pose(&data, origin);
+                    unsafe {
+                        action_data.write(vr::InputPoseActionData_t {
+                            bActive: pose.is_some(),
+                            activeOrigin: 0,
+                            pose: pose.unwrap_or_default(),
+                        });
+                    }
+                    return vr::EVRInputError::None;
+                }
+
                 let origin = hand.is_some().then_some(restrict_to_device);
                 let pose_type = match hand {
                     Some(Hand::Left) => bound.left,
@@ -886,6 +1375,10 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
         let mut out = WriteOnDrop::new(action_data);
         get_action_from_handle!(self, handle, session_data, action, loaded);
         let subaction_path = get_subaction_path!(self, restrict_to_device, action_data);
+        if self.is_action_masked(handle) {
+            trace!("analog action {handle} is masked by a higher priority action set");
+            return vr::EVRInputError::None;
+        }
 
         let mut active_hand = restrict_to_device;
         let (state, delta) = match action {
@@ -933,7 +1426,8 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
                 )
             }
             ActionData::Vector2 { action, last_value } => {
-                let state = action.state(&session_data.session, subaction_path).unwrap();
+                let mut state = action.state(&session_data.session, subaction_path).unwrap();
+                state.current_state = self.apply_thumbstick_deadzone(state.current_state);
                 let delta = xr::Vector2f {
                     x: state.current_state.x - last_value.0.swap(state.current_state.x),
                     y: state.current_state.y - last_value.1.swap(state.current_state.y),
@@ -975,6 +1469,10 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
         let ActionData::Bool(action) = &action else {
             return vr::EVRInputError::WrongType;
         };
+        if self.is_action_masked(handle) {
+            trace!("digital action {handle} is masked by a higher priority action set");
+            return vr::EVRInputError::None;
+        }
 
         let mut state = action.state(&session_data.session, subaction_path).unwrap();
 
@@ -1033,10 +1531,13 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
 
         let set_map = self.set_map.read().unwrap();
         let mut sync_sets = Vec::with_capacity(active_sets.len() + 1);
+        let mut active_set_keys = HashSet::with_capacity(active_sets.len());
+        let mut set_priorities = Vec::with_capacity(active_sets.len());
         {
             tracy_span!("UpdateActionState generate active sets");
             for set in active_sets {
                 let key = ActionSetKey::from(KeyData::from_ffi(set.ulActionSet));
+                let priority = set.nPriority;
                 let name = set_map.get(key);
                 let Some(set) = actions.sets.get(key) else {
                     debug!("Application passed invalid action set key: {key:?} ({name:?})");
@@ -1044,13 +1545,20 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
                 };
                 debug!("Activating set {}", name.unwrap());
                 sync_sets.push(set.into());
+                active_set_keys.insert(key);
+                set_priorities.push((key, priority));
             }
+            *self.active_sets.write().unwrap() = active_set_keys;
+            drop(set_map);
 
             let skeletal_input = data.input_data.estimated_skeleton_actions.get().unwrap();
             sync_sets.push(xr::ActiveActionSet::new(
                 &data.input_data.pose_data.get().unwrap().set,
             ));
             sync_sets.push(xr::ActiveActionSet::new(&skeletal_input.set));
+            if let Some(legacy) = &actions.legacy {
+                sync_sets.push(xr::ActiveActionSet::new(&legacy.set));
+            }
             self.legacy_state.on_action_sync();
         }
 
@@ -1058,6 +1566,7 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
             tracy_span!("xrSyncActions");
             data.session.sync_actions(&sync_sets).unwrap();
         }
+        self.check_recenter_action(&data);
 
         let devices = self.devices.read().unwrap();
         let left_profile = devices
@@ -1066,6 +1575,11 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
         let right_profile = devices
             .get_controller(Hand::Right)
             .map(|dev| dev.profile_path);
+        self.update_masked_actions(
+            actions,
+            &set_priorities,
+            left_profile.into_iter().chain(right_profile),
+        );
         for key in &actions.actions_with_custom_bindings {
             let unsync_custom_bindings = |key, profile| {
                 if profile == xr::Path::NULL {
@@ -1192,10 +1706,23 @@ impl<C: openxr_data::Compositor> vr::IVRInput010_Interface for Input<C> {
         let path = std::path::Path::new(&*path);
         info!("loading action manifest from {path:?}");
 
-        // We need to restart the session if the legacy actions have already been attached.
+        // We need to restart the session if the legacy actions (or the default manifest actions, or
+        // another manifest's actions) have already been attached.
         self.loading_actions.store(true, Ordering::Relaxed);
         let mut data = self.openxr.session_data.get();
-        if data.input_data.get_legacy_actions().is_some() {
+        let loaded_path = self.loaded_actions_path.read().unwrap().clone();
+        let default_actions_loaded =
+            loaded_path.is_none() && data.input_data.get_loaded_actions().is_some();
+        let other_manifest_loaded = loaded_path.is_some_and(|p| p.as_path() != path);
+        if other_manifest_loaded {
+            info!("replacing previously loaded action manifest");
+            // Don't reload the old manifest when the session restarts.
+            *self.loaded_actions_path.write().unwrap() = None;
+        }
+        if data.input_data.get_legacy_actions().is_some()
+            || default_actions_loaded
+            || other_manifest_loaded
+        {
             drop(data);
             self.openxr.restart_session();
             data = self.openxr.session_data.get();
@@ -1249,8 +1776,25 @@ impl<C: openxr_data::Compositor> vr::IVRInput005On006 for Input<C> {
 }
 
 impl<C: openxr_data::Compositor> Input<C> {
+    /// The interaction profile the runtime currently reports for a hand, if it's one we support.
+    /// Useful for figuring out which bindings are in use.
+    pub fn active_interaction_profile(
+        &self,
+        session_data: &SessionData,
+        hand: Hand,
+    ) -> Option<&'static dyn InteractionProfile> {
+        let path = session_data
+            .session
+            .current_interaction_profile(self.get_subaction_path(hand))
+            .ok()
+            .filter(|path| *path != xr::Path::NULL)?;
+        let name = self.openxr.instance.path_to_string(path).ok()?;
+        Profiles::get().profile_from_name(&name)
+    }
+
     pub fn interaction_profile_changed(&self, session_data: &SessionData) {
         let mut devices = self.devices.write().unwrap();
+        let force_connected = devices.force_connected();
 
         let mut devices_to_create = vec![];
 
@@ -1270,7 +1814,7 @@ impl<C: openxr_data::Compositor> Input<C> {
             let profile_name = match profile_path {
                 xr::Path::NULL => {
                     if let Some(controller) = controller.as_mut() {
-                        controller.connected = false;
+                        controller.connected = force_connected;
                     }
                     "<null>".to_owned()
                 }
@@ -1282,19 +1826,18 @@ impl<C: openxr_data::Compositor> Input<C> {
                 }
             };
 
-            let profile = Profiles::get().profile_from_name(&profile_name);
-
-            if let Some(p) = profile {
-                if let Some(controller) = controller.as_mut() {
-                    controller.interaction_profile = Some(p);
-                } else {
-                    devices_to_create.push((
-                        TrackedDeviceType::Controller { hand },
-                        Some(profile_path),
-                        Some(p),
-                    ));
-                }
-            };
+            let profile = self.active_interaction_profile(session_data, hand);
+
+            if let Some(controller) = controller.as_mut() {
+                // Don't keep around the properties of a previous profile if we don't know this one.
+                controller.interaction_profile = profile;
+            } else if let Some(p) = profile {
+                devices_to_create.push((
+                    TrackedDeviceType::Controller { hand },
+                    Some(profile_path),
+                    Some(p),
+                ));
+            }
 
             session_data.input_data.interaction_profile_changed();
 
@@ -1320,6 +1863,13 @@ impl<C: openxr_data::Compositor> Input<C> {
 
     pub fn frame_start_update(&self) {
         tracy_span!();
+        // Recentering replaces the seated space, so it has to happen before we grab the session.
+        if self.recenter_requested.swap(false, Ordering::Relaxed) {
+            info!("recentering from recenter action");
+            self.openxr
+                .reset_tracking_space(vr::ETrackingUniverseOrigin::Seated);
+        }
+
         let data = self.openxr.session_data.get();
         let devices = self.devices.read().unwrap();
 
@@ -1332,6 +1882,12 @@ impl<C: openxr_data::Compositor> Input<C> {
 
         let input_data = &data.input_data;
         if let Some(loaded) = input_data.get_loaded_actions() {
+            // The default manifest is loaded alongside legacy input, which still needs syncing.
+            if let Some(legacy) = &loaded.legacy {
+                self.frame_sync_legacy_actions(&data, legacy);
+                return;
+            }
+
             // If the game has loaded actions, we shouldn't need to sync the state because the game
             // should be doing it itself with UpdateActionState. However, some games (Tea for God)
             // don't actually call UpdateActionState if no controllers are reported as connected,
@@ -1349,16 +1905,7 @@ impl<C: openxr_data::Compositor> Input<C> {
         }
 
         match input_data.get_legacy_actions() {
-            Some(actions) => {
-                data.session
-                    .sync_actions(&[
-                        xr::ActiveActionSet::new(&actions.set),
-                        xr::ActiveActionSet::new(&input_data.pose_data.get().unwrap().set),
-                    ])
-                    .unwrap();
-
-                self.legacy_state.on_action_sync();
-            }
+            Some(actions) => self.frame_sync_legacy_actions(&data, actions),
             None => {
                 if self.loading_actions.load(Ordering::Relaxed) {
                     return;
@@ -1383,11 +1930,34 @@ impl<C: openxr_data::Compositor> Input<C> {
                     );
                     return;
                 }
+
                 self.setup_legacy_actions();
             }
         }
     }
 
+    fn get_gaze_pose(
+        &self,
+        session_data: &SessionData,
+        origin: vr::ETrackingUniverseOrigin,
+    ) -> Option<vr::TrackedDevicePose_t> {
+        let pose_data = session_data.input_data.pose_data.get()?;
+        let space = pose_data.try_get_or_init_gaze_space(session_data)?;
+        let (location, velocity) = space
+            .relate(
+                session_data.get_space_for_origin(origin),
+                self.openxr.display_time.get(),
+            )
+            .ok()?;
+        if !location
+            .location_flags
+            .contains(xr::SpaceLocationFlags::POSITION_VALID)
+        {
+            return None;
+        }
+        Some(vr::space_relation_to_openvr_pose(location, velocity))
+    }
+
     pub fn post_session_restart(&self, data: &SessionData) {
         // This function is called while a write lock is called on the session, and as such should
         // not use self.openxr.session_data.get().
@@ -1397,13 +1967,29 @@ impl<C: openxr_data::Compositor> Input<C> {
                 &self.openxr.instance,
                 self.subaction_paths.left,
                 self.subaction_paths.right,
+                self.controller_pose_mode,
+                *self.controller_offsets.lock().unwrap(),
+                self.openxr.enabled_extensions.ext_eye_gaze_interaction,
             ))
             .unwrap_or_else(|_| panic!("PoseData already setup"));
-        if let Some(path) = self.loaded_actions_path.get() {
-            let _ = self.load_action_manifest(data, path);
+        let path = self.loaded_actions_path.read().unwrap().clone();
+        if let Some(path) = path {
+            let _ = self.load_action_manifest(data, &path);
         }
     }
 
+    pub fn user_presence_changed(&self, present: bool) {
+        self.events.lock().unwrap().push_back(InputEvent {
+            ty: if present {
+                vr::EVREventType::TrackedDeviceUserInteractionStarted
+            } else {
+                vr::EVREventType::TrackedDeviceUserInteractionEnded
+            },
+            index: vr::k_unTrackedDeviceIndex_Hmd,
+            data: Default::default(),
+        });
+    }
+
     pub fn get_next_event(&self, size: u32, out: *mut vr::VREvent_t) -> bool {
         const FUNC: &str = "get_next_event";
         if out.is_null() {
@@ -1411,12 +1997,15 @@ impl<C: openxr_data::Compositor> Input<C> {
             return false;
         }
 
+        let session_data = self.openxr.session_data.get();
         let mut devices = self.devices.write().unwrap();
+        let force_connected = devices.force_connected();
 
         for (i, device) in devices.iter_mut().enumerate() {
-            let current = device.connected;
+            let connectable = force_connected || device.is_connectable(&session_data);
+            let current = device.connected && connectable;
 
-            if device.has_connected_changed() {
+            if device.has_connected_changed(connectable) {
                 debug!(
                     "sending {:?} {}connected",
                     device.get_type(),
@@ -1470,8 +2059,12 @@ struct ManifestLoadedActions {
     actions_with_custom_bindings: HashSet<ActionKey>,
     per_profile_pose_bindings: HashMap<xr::Path, SecondaryMap<ActionKey, BoundPose>>,
     per_profile_bindings: HashMap<xr::Path, SecondaryMap<ActionKey, Vec<BindingData>>>,
+    /// The input sources each action is directly bound to, per interaction profile
+    per_profile_bound_sources: HashMap<xr::Path, SecondaryMap<ActionKey, Vec<xr::Path>>>,
     info_set: xr::ActionSet,
     _info_action: xr::Action<bool>,
+    /// Only set for the default manifest, which is loaded alongside legacy input
+    legacy: Option<LegacyActionData>,
 }
 
 impl ManifestLoadedActions {
@@ -1523,36 +2116,144 @@ impl ManifestLoadedActions {
     }
 }
 
+/// Which OpenXR pose is reported as the controller's pose.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ControllerPoseMode {
+    /// The grip pose, offset to match SteamVR's raw pose for the controller.
+    #[default]
+    OffsetGrip,
+    /// The grip pose, as given by the runtime.
+    Grip,
+    /// The aim pose, as given by the runtime.
+    Aim,
+}
+
+impl ControllerPoseMode {
+    fn from_env() -> Self {
+        match std::env::var("XRIZER_CONTROLLER_POSE").as_deref() {
+            Err(_) | Ok("offset_grip") => Self::OffsetGrip,
+            Ok("grip") => Self::Grip,
+            Ok("aim") => Self::Aim,
+            Ok(other) => {
+                warn!("Invalid XRIZER_CONTROLLER_POSE ({other})");
+                Self::OffsetGrip
+            }
+        }
+    }
+}
+
+/// Reads XRIZER_CONTROLLER_OFFSET as `x,y,z,pitch,yaw,roll` (meters and degrees) for the right
+/// controller. The left controller gets the mirrored offset.
+fn controller_offsets_from_env() -> [Mat4; 2] {
+    let Ok(var) = std::env::var("XRIZER_CONTROLLER_OFFSET") else {
+        return [Mat4::IDENTITY; 2];
+    };
+    let values: Option<Vec<f32>> = var.split(',').map(|v| v.trim().parse().ok()).collect();
+    let Some(&[x, y, z, pitch, yaw, roll]) = values.as_deref() else {
+        warn!("Invalid XRIZER_CONTROLLER_OFFSET ({var})");
+        return [Mat4::IDENTITY; 2];
+    };
+    let offset = |x: f32, yaw: f32, roll: f32| {
+        Mat4::from_rotation_translation(
+            Quat::from_euler(
+                EulerRot::YXZ,
+                yaw.to_radians(),
+                pitch.to_radians(),
+                roll.to_radians(),
+            ),
+            Vec3::new(x, y, z),
+        )
+    };
+    [offset(-x, -yaw, -roll), offset(x, yaw, roll)]
+}
+
 struct PoseData {
     set: xr::ActionSet,
     grip: xr::Action<xr::Posef>,
+    aim: xr::Action<xr::Posef>,
+    /// Only created if XR_EXT_eye_gaze_interaction is enabled.
+    gaze: Option<xr::Action<xr::Posef>>,
+    /// Only bound if XRIZER_RECENTER_BINDING is set.
+    recenter: xr::Action<bool>,
+    mode: Mutex<ControllerPoseMode>,
     left_space: HandSpace,
     right_space: HandSpace,
+    gaze_space: RwLock<Option<xr::Space>>,
 }
 
 impl PoseData {
-    fn new(instance: &xr::Instance, left_path: xr::Path, right_path: xr::Path) -> Self {
+    fn new(
+        instance: &xr::Instance,
+        left_path: xr::Path,
+        right_path: xr::Path,
+        mode: ControllerPoseMode,
+        [left_offset, right_offset]: [Mat4; 2],
+        eye_gaze: bool,
+    ) -> Self {
         let set = instance
             .create_action_set("xrizer-pose-data", "xrizer pose data", 0)
             .unwrap();
         let grip = set
             .create_action("grip-pose", "Grip Pose", &[left_path, right_path])
             .unwrap();
+        let aim = set
+            .create_action("aim-pose", "Aim Pose", &[left_path, right_path])
+            .unwrap();
+        let gaze = eye_gaze.then(|| {
+            set.create_action("eye-gaze-pose", "Eye Gaze Pose", &[])
+                .unwrap()
+        });
+        let recenter = set.create_action("recenter", "Recenter", &[]).unwrap();
         Self {
             set,
             grip,
+            aim,
+            gaze,
+            recenter,
+            mode: Mutex::new(mode),
             left_space: HandSpace {
                 hand: Hand::Left,
                 hand_path: left_path,
+                offset: Mutex::new(left_offset),
                 raw: RwLock::default(),
             },
             right_space: HandSpace {
                 hand: Hand::Right,
                 hand_path: right_path,
+                offset: Mutex::new(right_offset),
                 raw: RwLock::default(),
             },
+            gaze_space: RwLock::default(),
         }
     }
+
+    /// Suggests the gaze action's binding for the eye gaze interaction profile, if it's enabled.
+    fn suggest_gaze_binding(&self, instance: &xr::Instance) {
+        let Some(gaze) = &self.gaze else {
+            return;
+        };
+        let profile = instance.string_to_path(EYE_GAZE_PROFILE_PATH).unwrap();
+        let path = instance.string_to_path(EYE_GAZE_POSE_PATH).unwrap();
+        instance
+            .suggest_interaction_profile_bindings(profile, &[xr::Binding::new(gaze, path)])
+            .expect("Couldn't suggest eye gaze binding");
+    }
+
+    fn try_get_or_init_gaze_space(&self, session_data: &SessionData) -> Option<SpaceReadGuard<'_>> {
+        let gaze = self.gaze.as_ref()?;
+        {
+            let space = self.gaze_space.read().unwrap();
+            if space.is_some() {
+                return Some(SpaceReadGuard(space));
+            }
+        }
+        *self.gaze_space.write().unwrap() = Some(
+            gaze.create_space(&session_data.session, xr::Path::NULL, xr::Posef::IDENTITY)
+                .unwrap(),
+        );
+        Some(SpaceReadGuard(self.gaze_space.read().unwrap()))
+    }
+
     fn reset_spaces(&self) {
         self.left_space.reset_raw();
         self.right_space.reset_raw();
@@ -1562,6 +2263,8 @@ impl PoseData {
 struct HandSpace {
     hand: Hand,
     hand_path: xr::Path,
+    /// Applied on top of the pose selected by the pose mode
+    offset: Mutex<Mat4>,
 
     /// Based on the controller jsons in SteamVR, the "raw" pose
     /// This is stored as a space so we can locate hand joints relative to it for skeletal data.
@@ -1595,7 +2298,14 @@ impl HandSpace {
                 return None;
             };
 
-            let offset = profile.offset_grip_pose(self.hand);
+            let (action, offset) = match *pose_data.mode.lock().unwrap() {
+                ControllerPoseMode::OffsetGrip => {
+                    (&pose_data.grip, profile.offset_grip_pose(self.hand))
+                }
+                ControllerPoseMode::Grip => (&pose_data.grip, Mat4::IDENTITY),
+                ControllerPoseMode::Aim => (&pose_data.aim, Mat4::IDENTITY),
+            };
+            let offset = offset * *self.offset.lock().unwrap();
             let translation = offset.w_axis.truncate();
             let rotation = Quat::from_mat4(&offset);
 
@@ -1614,8 +2324,7 @@ impl HandSpace {
             };
 
             *self.raw.write().unwrap() = Some(
-                pose_data
-                    .grip
+                action
                     .create_space(&session_data.session, self.hand_path, offset_pose)
                     .unwrap(),
             );
@@ -1,8 +1,9 @@
 use super::{
     custom_bindings::DpadDirection,
+    legacy::LegacyActionData,
     profiles::{PathTranslation, Profiles},
     skeletal::SkeletalInputActionData,
-    ActionData, ActionKey, BoundPoseType, Input,
+    ActionData, ActionKey, BoundPoseType, Input, EYE_GAZE_POSE_PATH,
 };
 use crate::{
     input::{
@@ -46,13 +47,42 @@ fn action_map_to_secondary<T>(
         .collect()
 }
 
+/// Writes the default manifest shipped with xrizer (and its bindings) to disk, so it can be loaded
+/// like any other manifest. Returns the path to the manifest.
+fn write_bundled_default_manifest() -> std::io::Result<PathBuf> {
+    static FILES: &[(&str, &str)] = &[
+        ("actions.json", include_str!("default_actions/actions.json")),
+        (
+            "knuckles.json",
+            include_str!("default_actions/knuckles.json"),
+        ),
+        (
+            "oculus_touch.json",
+            include_str!("default_actions/oculus_touch.json"),
+        ),
+        (
+            "vive_controller.json",
+            include_str!("default_actions/vive_controller.json"),
+        ),
+    ];
+
+    let dir = std::env::temp_dir().join("xrizer_default_actions");
+    std::fs::create_dir_all(&dir)?;
+    for (name, contents) in FILES {
+        std::fs::write(dir.join(name), contents)?;
+    }
+
+    Ok(dir.join("actions.json"))
+}
+
 impl<C: openxr_data::Compositor> Input<C> {
     pub(super) fn load_action_manifest(
         &self,
         session_data: &SessionData,
         manifest_path: &Path,
     ) -> Result<(), vr::EVRInputError> {
-        match self.loaded_actions_path.get() {
+        let loaded_path = self.loaded_actions_path.read().unwrap().clone();
+        match loaded_path {
             Some(p) => {
                 assert_eq!(p, manifest_path);
                 if session_data.input_data.actions.get().is_some() {
@@ -71,12 +101,55 @@ impl<C: openxr_data::Compositor> Input<C> {
                     );
                     return Err(vr::EVRInputError::MismatchedActionManifest);
                 }
-                self.loaded_actions_path
-                    .set(manifest_path.to_path_buf())
-                    .unwrap();
+                *self.loaded_actions_path.write().unwrap() = Some(manifest_path.to_path_buf());
             }
         }
 
+        self.load_action_manifest_from_path(session_data, manifest_path, None)
+    }
+
+    /// Loads a manifest on the game's behalf, for games that never provide one. The legacy actions
+    /// are set up alongside it, since these games still use legacy input.
+    /// Unlike [`Self::load_action_manifest`], this doesn't record the manifest path, so the game
+    /// is still free to load its own manifest later.
+    pub(super) fn load_default_action_manifest(
+        &self,
+        session_data: &SessionData,
+        manifest_path: &Path,
+    ) -> Result<(), vr::EVRInputError> {
+        if session_data.input_data.actions.get().is_some() {
+            return Ok(());
+        }
+
+        let legacy = LegacyActionData::new(
+            &self.openxr.instance,
+            self.get_subaction_path(Hand::Left),
+            self.get_subaction_path(Hand::Right),
+        );
+        self.load_action_manifest_from_path(session_data, manifest_path, Some(legacy))
+    }
+
+    pub(super) fn get_default_manifest_path(&self) -> Option<&Path> {
+        self.default_manifest_path
+            .get_or_init(|| {
+                let value = std::env::var("XRIZER_DEFAULT_ACTION_MANIFEST").ok()?;
+                if value != "bundled" {
+                    return Some(PathBuf::from(value));
+                }
+
+                write_bundled_default_manifest()
+                    .inspect_err(|e| error!("Failed to write bundled default manifest: {e}"))
+                    .ok()
+            })
+            .as_deref()
+    }
+
+    fn load_action_manifest_from_path(
+        &self,
+        session_data: &SessionData,
+        manifest_path: &Path,
+        legacy: Option<LegacyActionData>,
+    ) -> Result<(), vr::EVRInputError> {
         let data = std::fs::read(manifest_path).map_err(|e| {
             error!("Failed to read manifest {}: {e}", manifest_path.display());
             vr::EVRInputError::InvalidParam
@@ -134,13 +207,17 @@ impl<C: openxr_data::Compositor> Input<C> {
             .create_action::<bool>("xrizer-info-action", "XRizer info action", &[])
             .unwrap();
 
+        let pose_data = session_data.input_data.pose_data.get().unwrap();
         let mut binding_context = BindingsLoadContext::new(
             &sets,
             actions,
-            &session_data.input_data.pose_data.get().unwrap().grip,
+            &pose_data.grip,
+            &pose_data.aim,
             &info_action,
+            &pose_data.recenter,
             skeletal_input,
         );
+        binding_context.legacy = legacy.as_ref();
 
         self.load_bindings(
             manifest_path.parent().unwrap(),
@@ -153,9 +230,17 @@ impl<C: openxr_data::Compositor> Input<C> {
             extra_actions,
             per_profile_bindings,
             per_profile_pose_bindings,
+            per_profile_bound_sources,
             ..
         } = binding_context;
 
+        // Profiles the manifest has no bindings for still need the legacy bindings.
+        if let Some(legacy) = &legacy {
+            self.suggest_legacy_bindings(legacy, pose_data, |profile| {
+                !per_profile_bindings.contains_key(&profile)
+            });
+        }
+
         let xr_sets: Vec<_> = sets
             .values()
             .chain([
@@ -163,7 +248,9 @@ impl<C: openxr_data::Compositor> Input<C> {
                 &info_set,
                 &skeletal_input.set,
             ])
+            .chain(legacy.as_ref().map(|legacy| &legacy.set))
             .collect();
+        pose_data.suggest_gaze_binding(&self.openxr.instance);
         session_data.session.attach_action_sets(&xr_sets).unwrap();
 
         // Try forcing an interaction profile now
@@ -223,6 +310,26 @@ impl<C: openxr_data::Compositor> Input<C> {
             .map(|(k, v)| (k, action_map_to_secondary(&mut act_guard, v)))
             .collect();
 
+        // Only keep sources for the manifest's own actions, not ones we created for bindings.
+        let per_profile_bound_sources = per_profile_bound_sources
+            .into_iter()
+            .map(|(profile, sources)| {
+                let sources = sources
+                    .into_iter()
+                    .filter_map(|(name, paths)| {
+                        let key = act_guard
+                            .iter()
+                            .find_map(|(key, super::Action { path })| {
+                                (*path == name).then_some(key)
+                            })
+                            .filter(|key| actions.contains_key(*key))?;
+                        Some((key, paths))
+                    })
+                    .collect();
+                (profile, sources)
+            })
+            .collect();
+
         let loaded = super::ManifestLoadedActions {
             sets,
             actions,
@@ -230,8 +337,10 @@ impl<C: openxr_data::Compositor> Input<C> {
             extra_actions,
             per_profile_bindings,
             per_profile_pose_bindings,
+            per_profile_bound_sources,
             _info_action: info_action,
             info_set,
+            legacy,
         };
 
         session_data
@@ -549,14 +658,22 @@ impl<'de> Deserialize<'de> for ActionPath {
 struct PoseBinding {
     output: ActionPath,
     #[serde(deserialize_with = "parse_pose_binding")]
-    path: (Hand, BoundPoseType),
+    path: PoseSource,
+}
+
+enum PoseSource {
+    Hand(Hand, BoundPoseType),
+    /// The gaze pose from XR_EXT_eye_gaze_interaction.
+    EyeGaze,
 }
 
-fn parse_pose_binding<'de, D: serde::Deserializer<'de>>(
-    d: D,
-) -> Result<(Hand, BoundPoseType), D::Error> {
+fn parse_pose_binding<'de, D: serde::Deserializer<'de>>(d: D) -> Result<PoseSource, D::Error> {
     let pose_path: &str = Deserialize::deserialize(d)?;
 
+    if pose_path == EYE_GAZE_POSE_PATH {
+        return Ok(PoseSource::EyeGaze);
+    }
+
     let (hand, pose) = pose_path.rsplit_once('/').ok_or(D::Error::invalid_value(
         Unexpected::Str(pose_path),
         &"a value matching /user/hand/{left,right}/pose/<pose>",
@@ -583,7 +700,7 @@ fn parse_pose_binding<'de, D: serde::Deserializer<'de>>(
         }
     };
 
-    Ok((hand, pose))
+    Ok(PoseSource::Hand(hand, pose))
 }
 
 #[derive(Deserialize)]
@@ -866,7 +983,8 @@ impl<C: openxr_data::Compositor> Input<C> {
                     let profiles = Profiles::get()
                         .list
                         .iter()
-                        .filter_map(|(ty, p)| (*ty == *other).then_some(*p));
+                        .filter_map(|(ty, p)| (*ty == *other).then_some(*p))
+                        .filter(|p| self.is_profile_enabled(*p));
                     let bindings = LazyCell::new(load_bindings);
                     for profile in profiles {
                         if let Some(bindings) = bindings.as_ref() {
@@ -913,13 +1031,20 @@ impl<C: openxr_data::Compositor> Input<C> {
         let profile_path = stp(profile.profile_path());
         let legal_paths = profile.legal_paths();
         let translate_map = profile.translate_map();
+        let dominant_hand = *self.dominant_hand.lock().unwrap();
+        let untranslated_paths = self.untranslated_paths.read().unwrap();
         let path_translator = |path: &str| {
-            let mut translated = path.to_string();
-            for PathTranslation { from, to, stop } in translate_map {
-                if translated.contains(from) {
-                    translated = translated.replace(from, to);
-                    if *stop {
-                        break;
+            let mut translated = resolve_hand_alias(path, dominant_hand);
+            let untranslated = untranslated_paths
+                .iter()
+                .any(|prefix| translated.starts_with(prefix.as_str()));
+            if !untranslated {
+                for PathTranslation { from, to, stop } in translate_map {
+                    if translated.contains(from) {
+                        translated = translated.replace(from, to);
+                        if *stop {
+                            break;
+                        }
                     }
                 }
             }
@@ -963,12 +1088,31 @@ impl<C: openxr_data::Compositor> Input<C> {
             );
         }
 
-        let info_action_binding = *legacy_bindings.trigger_click.first().unwrap_or_else(|| {
-            panic!(
-                "Missing trigger_click binding for {}",
-                profile.profile_path()
-            )
-        });
+        let info_action_binding = *legacy_bindings
+            .trigger_click
+            .first()
+            .or(legacy_bindings.trigger.first())
+            .unwrap_or_else(|| panic!("Missing trigger binding for {}", profile.profile_path()));
+        // When legacy input is used alongside the manifest, its bindings have to be suggested
+        // together with the manifest's, since suggesting again would replace them.
+        let legacy_bindings: Vec<xr::Binding<'_>> = match context.legacy {
+            Some(legacy) => legacy
+                .bindings(legacy_bindings, context.grip_action, context.aim_action)
+                .collect(),
+            None => legacy_bindings
+                .extra
+                .grip_pose
+                .into_iter()
+                .map(|path| xr::Binding::new(context.grip_action, path))
+                .chain(
+                    legacy_bindings
+                        .extra
+                        .aim_pose
+                        .into_iter()
+                        .map(|path| xr::Binding::new(context.aim_action, path)),
+                )
+                .collect(),
+        };
         let bindings: Vec<xr::Binding<'_>> = context
             .bindings
             .iter()
@@ -987,17 +1131,15 @@ impl<C: openxr_data::Compositor> Input<C> {
                     Skeleton { .. } | Pose => unreachable!(),
                 }
             })
-            .chain(
-                legacy_bindings
-                    .extra
-                    .grip_pose
-                    .into_iter()
-                    .map(|path| xr::Binding::new(context.grip_action, path)),
-            )
+            .chain(legacy_bindings)
             .chain(std::iter::once(xr::Binding::new(
                 context.info_action,
                 info_action_binding,
             )))
+            .chain(
+                self.recenter_binding(profile)
+                    .map(|path| xr::Binding::new(context.recenter_action, path)),
+            )
             .chain(skeletal_bindings.binding_iter(&context.skeletal_input.actions))
             .collect();
 
@@ -1010,6 +1152,14 @@ impl<C: openxr_data::Compositor> Input<C> {
             bindings.len(),
             profile.profile_path()
         );
+
+        for (name, path) in &context.bindings {
+            context
+                .bound_sources
+                .entry(name.clone())
+                .or_default()
+                .push(*path);
+        }
     }
 }
 
@@ -1113,12 +1263,65 @@ fn handle_dpad_binding(
     }
 }
 
+/// Binds a click input to its translated source. Some profiles translate clicks to an analog
+/// source (e.g. the Index trackpad, which only has force), in which case the click is synthesized
+/// from the analog value with a threshold, leaving the source free to be bound as analog too.
+fn bind_click(
+    context: &mut BindingsProfileLoadContext,
+    output: &ActionPath,
+    translated: String,
+    action_set_name: &str,
+    action_set: &xr::ActionSet,
+) {
+    if translated.ends_with("/click") || translated.ends_with("/touch") {
+        context.try_get_bool_binding(output.path.clone(), translated);
+        return;
+    }
+
+    if !context.find_action(&output.path) {
+        return;
+    }
+
+    let hand = helpers::parse_hand_from_path(context.instance, &translated).unwrap();
+    let float_name_with_as = context.add_custom_binding::<ThresholdBindingFloat>(
+        output,
+        hand,
+        action_set_name,
+        action_set,
+        None,
+    );
+    trace!(
+        "suggesting {translated} for {} (synthesized click)",
+        output.path
+    );
+    context.push_binding(
+        float_name_with_as,
+        context.instance.string_to_path(&translated).unwrap(),
+    );
+}
+
 fn translate_warn(action: &str) -> impl FnOnce(&InvalidActionPath) + '_ {
     move |e| warn!("{} ({action})", e.0)
 }
 
 struct InvalidActionPath(String);
 
+/// Resolves SteamVR's /user/hand/primary and /user/hand/secondary to the dominant and
+/// non-dominant hand.
+fn resolve_hand_alias(path: &str, dominant_hand: Hand) -> String {
+    let (primary, secondary) = match dominant_hand {
+        Hand::Left => ("/user/hand/left", "/user/hand/right"),
+        Hand::Right => ("/user/hand/right", "/user/hand/left"),
+    };
+    if let Some(rest) = path.strip_prefix("/user/hand/primary") {
+        format!("{primary}{rest}")
+    } else if let Some(rest) = path.strip_prefix("/user/hand/secondary") {
+        format!("{secondary}{rest}")
+    } else {
+        path.to_string()
+    }
+}
+
 fn handle_sources(
     path_translator: impl Fn(&str) -> Result<String, InvalidActionPath>,
     context: &mut BindingsProfileLoadContext,
@@ -1292,6 +1495,9 @@ fn handle_sources(
                     .filter_map(|(sfx, input)| Some(sfx).zip(input.as_ref().map(|i| &i.output)));
                 for (suffix, output) in suffixes_and_outputs {
                     match path_translator(&format!("{path}/{suffix}")) {
+                        Ok(translated) if suffix == "click" => {
+                            bind_click(context, output, translated, action_set_name, action_set);
+                        }
                         Ok(translated) => {
                             context.try_get_bool_binding(output.path.clone(), translated);
                         }
@@ -1438,7 +1644,7 @@ fn handle_sources(
                             .ok(),
                     )
                 }) {
-                    context.try_get_bool_binding(output.path.clone(), click_path);
+                    bind_click(context, output, click_path, action_set_name, action_set);
                 }
 
                 if let Some((output, touch_path)) = touch.as_ref().and_then(|b| {
@@ -1520,11 +1726,7 @@ fn handle_haptic_bindings(
 }
 
 fn handle_pose_bindings(context: &mut BindingsProfileLoadContext, bindings: &[PoseBinding]) {
-    for PoseBinding {
-        output,
-        path: (hand, pose_ty),
-    } in bindings
-    {
+    for PoseBinding { output, path } in bindings {
         if !context.find_action(&output.path) {
             continue;
         };
@@ -1543,15 +1745,23 @@ fn handle_pose_bindings(context: &mut BindingsProfileLoadContext, bindings: &[Po
             .entry(output.path.clone())
             .or_default();
 
-        let b = match hand {
-            Hand::Left => &mut bound.left,
-            Hand::Right => &mut bound.right,
-        };
-        *b = Some(*pose_ty);
-        trace!(
-            "bound {:?} to pose {} for hand {hand:?}",
-            *pose_ty,
-            output.path
-        );
+        match path {
+            PoseSource::Hand(hand, pose_ty) => {
+                let b = match hand {
+                    Hand::Left => &mut bound.left,
+                    Hand::Right => &mut bound.right,
+                };
+                *b = Some(*pose_ty);
+                trace!(
+                    "bound {:?} to pose {} for hand {hand:?}",
+                    *pose_ty,
+                    output.path
+                );
+            }
+            PoseSource::EyeGaze => {
+                bound.gaze = true;
+                trace!("bound eye gaze to pose {}", output.path);
+            }
+        }
     }
 }
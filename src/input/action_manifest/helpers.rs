@@ -2,6 +2,7 @@ use crate::input::action_manifest::{ActionPath, ControllerType, LoadedActionData
 use crate::input::custom_bindings::{
     AsActionData, AsIter, BindingData, CustomBindingHelper, Names,
 };
+use crate::input::legacy::LegacyActionData;
 use crate::input::skeletal::SkeletalInputActionData;
 use crate::input::ActionData::{Bool, Vector1, Vector2};
 use crate::input::{ActionData, BoundPose, ExtraActionData, Input, InteractionProfile};
@@ -16,9 +17,14 @@ pub(super) struct BindingsLoadContext<'a> {
     pub extra_actions: HashMap<String, ExtraActionData>,
     pub per_profile_bindings: HashMap<xr::Path, HashMap<String, Vec<BindingData>>>,
     pub per_profile_pose_bindings: HashMap<xr::Path, HashMap<String, BoundPose>>,
+    pub per_profile_bound_sources: HashMap<xr::Path, HashMap<String, Vec<xr::Path>>>,
     pub grip_action: &'a xr::Action<xr::Posef>,
+    pub aim_action: &'a xr::Action<xr::Posef>,
     pub info_action: &'a xr::Action<bool>,
+    pub recenter_action: &'a xr::Action<bool>,
     pub skeletal_input: &'a SkeletalInputActionData,
+    /// Only set if legacy input is used alongside the manifest
+    pub legacy: Option<&'a LegacyActionData>,
 }
 
 impl<'a> BindingsLoadContext<'a> {
@@ -26,7 +32,9 @@ impl<'a> BindingsLoadContext<'a> {
         action_sets: &'a HashMap<String, xr::ActionSet>,
         actions: LoadedActionDataMap,
         grip_action: &'a xr::Action<xr::Posef>,
+        aim_action: &'a xr::Action<xr::Posef>,
         info_action: &'a xr::Action<bool>,
+        recenter_action: &'a xr::Action<bool>,
         skeletal_input: &'a SkeletalInputActionData,
     ) -> Self {
         BindingsLoadContext {
@@ -35,9 +43,13 @@ impl<'a> BindingsLoadContext<'a> {
             extra_actions: Default::default(),
             per_profile_bindings: Default::default(),
             per_profile_pose_bindings: Default::default(),
+            per_profile_bound_sources: Default::default(),
             grip_action,
+            aim_action,
             info_action,
+            recenter_action,
             skeletal_input,
+            legacy: None,
         }
     }
 }
@@ -69,6 +81,10 @@ impl BindingsLoadContext<'_> {
             .per_profile_pose_bindings
             .entry(interaction_profile)
             .or_default();
+        let bound_sources = self
+            .per_profile_bound_sources
+            .entry(interaction_profile)
+            .or_default();
         Some(BindingsProfileLoadContext {
             profile,
             controller_type,
@@ -77,9 +93,13 @@ impl BindingsLoadContext<'_> {
             extra_actions: &mut self.extra_actions,
             bindings_parsed,
             pose_bindings,
+            bound_sources,
             grip_action: self.grip_action,
+            aim_action: self.aim_action,
             info_action: self.info_action,
+            recenter_action: self.recenter_action,
             skeletal_input: self.skeletal_input,
+            legacy: self.legacy,
             instance,
             hands,
             bindings: Vec::new(),
@@ -95,9 +115,13 @@ pub(super) struct BindingsProfileLoadContext<'a> {
     extra_actions: &'a mut HashMap<String, ExtraActionData>,
     bindings_parsed: &'a mut HashMap<String, Vec<BindingData>>,
     pub pose_bindings: &'a mut HashMap<String, BoundPose>,
+    pub bound_sources: &'a mut HashMap<String, Vec<xr::Path>>,
     pub grip_action: &'a xr::Action<xr::Posef>,
+    pub aim_action: &'a xr::Action<xr::Posef>,
     pub info_action: &'a xr::Action<bool>,
+    pub recenter_action: &'a xr::Action<bool>,
     pub skeletal_input: &'a SkeletalInputActionData,
+    pub legacy: Option<&'a LegacyActionData>,
     pub instance: &'a xr::Instance,
     pub hands: [xr::Path; 2],
     pub bindings: Vec<(String, xr::Path)>,
@@ -1057,6 +1057,58 @@ mod tests {
         assert!(state.bChanged);
     }
 
+    #[test]
+    fn dpad_input_all_directions() {
+        let mut f = Fixture::new();
+
+        let set1 = f.get_action_set_handle(c"/actions/set1");
+        let north = f.get_action_handle(c"/actions/set1/in/north");
+        let east = f.get_action_handle(c"/actions/set1/in/east");
+        let south = f.get_action_handle(c"/actions/set1/in/south");
+        let west = f.get_action_handle(c"/actions/set1/in/west");
+        let center = f.get_action_handle(c"/actions/set1/in/center");
+
+        f.load_actions(c"actions_dpad_directions.json");
+        f.input.openxr.restart_session();
+
+        get_dpad_action!(f, north, dpad_data, ViveWands);
+
+        f.set_interaction_profile(&ViveWands, LeftHand);
+        fakexr::set_action_state(
+            dpad_data.click_or_touch.as_ref().unwrap().as_raw(),
+            fakexr::ActionState::Float(1.0),
+            LeftHand,
+        );
+
+        for (position, expected) in [
+            ((0.0, 0.8), north),
+            ((0.8, 0.0), east),
+            ((0.0, -0.8), south),
+            ((-0.8, 0.0), west),
+            ((0.1, 0.1), center),
+        ] {
+            fakexr::set_action_state(
+                dpad_data.xy.as_raw(),
+                fakexr::ActionState::Vector2(position.0, position.1),
+                LeftHand,
+            );
+            f.sync(vr::VRActiveActionSet_t {
+                ulActionSet: set1,
+                ..Default::default()
+            });
+
+            for action in [north, east, south, west, center] {
+                let state = f.get_bool_state(action).unwrap();
+                assert!(state.bActive);
+                assert_eq!(
+                    state.bState,
+                    action == expected,
+                    "unexpected state for {action} at {position:?}"
+                );
+            }
+        }
+    }
+
     #[test]
     fn dpad_input_different_sets_have_different_actions() {
         let f = Fixture::new();
@@ -1438,6 +1490,47 @@ mod tests {
         value_state_check(0.0, 1.0, false, false, line!());
     }
 
+    #[test]
+    fn trackpad_click_from_force() {
+        let mut f = Fixture::new();
+        let set1 = f.get_action_set_handle(c"/actions/set1");
+        let boolact = f.get_action_handle(c"/actions/set1/in/boolact");
+        f.load_actions(c"actions_trackpad_force.json");
+        get_analog_action!(f, boolact, analog_data);
+
+        // The force sensor is bound to both the click and the analog action.
+        f.verify_extra_bindings(
+            Knuckles.profile_path(),
+            c"/actions/set1/in/boolact",
+            ExtraActionType::Analog,
+            ["/user/hand/left/input/trackpad/force".into()],
+        );
+        f.verify_bindings::<f32>(
+            Knuckles.profile_path(),
+            c"/actions/set1/in/vec1act",
+            ["/user/hand/left/input/trackpad/force".into()],
+        );
+
+        f.set_interaction_profile(&Knuckles, LeftHand);
+        let act = analog_data.as_raw();
+        let mut check = |force, state: BoolState| {
+            fakexr::set_action_state(act, ActionState::Float(force), LeftHand);
+            f.sync(vr::VRActiveActionSet_t {
+                ulActionSet: set1,
+                ..Default::default()
+            });
+            f.verify_bool_state(boolact, state);
+        };
+
+        check(0.1, BoolState::default().set_active());
+        check(
+            0.5,
+            BoolState::default().set_active().set_state().set_changed(),
+        );
+        check(0.22, BoolState::default().set_active().set_state());
+        check(0.1, BoolState::default().set_active().set_changed());
+    }
+
     #[test]
     fn toggle_button() {
         let mut f = Fixture::new();
@@ -1,5 +1,10 @@
-use std::{ffi::CStr, sync::Mutex};
+use std::{
+    ffi::CStr,
+    sync::{atomic::Ordering, Mutex},
+    time::{Duration, Instant},
+};
 
+use glam::{Quat, Vec3};
 use openvr as vr;
 use openxr as xr;
 
@@ -7,7 +12,94 @@ use crate::openxr_data::{self, Hand, OpenXrData, SessionData};
 use crate::tracy_span;
 use log::trace;
 
-use super::{profiles::MainAxisType, Input, InteractionProfile};
+use super::{
+    legacy::button_mask_from_id,
+    profiles::{knuckles::Knuckles, vive_controller::ViveWands, MainAxisType, ProfileProperties},
+    Input, InteractionProfile,
+};
+
+/// Changes in the device's pose smaller than this (in meters, or in rotation matrix elements)
+/// aren't considered user interaction, so tracking jitter doesn't keep devices awake.
+const MOVEMENT_THRESHOLD: f32 = 0.005;
+/// Activity level thresholds, based on the descriptions of EDeviceActivityLevel.
+const USER_INTERACTION_TIMEOUT: Duration = Duration::from_millis(500);
+const IDLE_THRESHOLD: Duration = Duration::from_secs(10);
+pub(super) const IDLE_TIMEOUT: Duration = Duration::from_secs(300);
+
+struct ActivityTimer {
+    last_interaction: Instant,
+    /// The pose at the time of the last interaction
+    last_pose: Option<[[f32; 4]; 3]>,
+}
+
+/// Exponential smoothing for jittery devices - see XRIZER_POSE_SMOOTHING.
+#[derive(Default)]
+struct PoseFilter {
+    /// How much of the previous pose is kept each frame, from 0 (off) to just under 1.
+    strength: f32,
+    last: Option<(Vec3, Quat)>,
+}
+
+impl PoseFilter {
+    fn apply(&mut self, pose: &mut vr::TrackedDevicePose_t) {
+        if self.strength <= 0.0 {
+            self.last = None;
+            return;
+        }
+
+        let xr::Posef {
+            orientation: o,
+            position: p,
+        } = pose.mDeviceToAbsoluteTracking.into();
+        let mut position = Vec3::new(p.x, p.y, p.z);
+        let mut orientation = Quat::from_xyzw(o.x, o.y, o.z, o.w);
+
+        if let Some((last_position, last_orientation)) = self.last {
+            let t = 1.0 - self.strength;
+            position = last_position.lerp(position, t);
+            orientation = last_orientation.slerp(orientation, t);
+        }
+        self.last = Some((position, orientation));
+
+        pose.mDeviceToAbsoluteTracking = xr::Posef {
+            orientation: xr::Quaternionf {
+                x: orientation.x,
+                y: orientation.y,
+                z: orientation.z,
+                w: orientation.w,
+            },
+            position: xr::Vector3f {
+                x: position.x,
+                y: position.y,
+                z: position.z,
+            },
+        }
+        .into();
+    }
+}
+
+/// Caps the reported velocities of non-headset devices, since tracking recovery can produce
+/// implausible spikes that physics based games turn into launched objects - see
+/// XRIZER_MAX_VELOCITY and XRIZER_MAX_ANGULAR_VELOCITY.
+#[derive(Copy, Clone, Default)]
+pub(super) struct VelocityLimit {
+    /// In meters per second
+    pub linear: Option<f32>,
+    /// In radians per second
+    pub angular: Option<f32>,
+}
+
+impl VelocityLimit {
+    fn apply(&self, pose: &mut vr::TrackedDevicePose_t) {
+        fn clamp(velocity: &mut vr::HmdVector3_t, max: Option<f32>) {
+            if let Some(max) = max {
+                velocity.v = Vec3::from(velocity.v).clamp_length_max(max).into();
+            }
+        }
+        clamp(&mut pose.vVelocity, self.linear);
+        clamp(&mut pose.vAngularVelocity, self.angular);
+    }
+}
 
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum TrackedDeviceType {
@@ -21,20 +113,20 @@ pub struct TrackedDevice {
     pub connected: bool,
     pub previous_connected: bool,
     pose_cache: Mutex<Option<vr::TrackedDevicePose_t>>,
+    activity: Mutex<ActivityTimer>,
+    pose_filter: Mutex<PoseFilter>,
+    velocity_limit: VelocityLimit,
 }
 
 fn get_hmd_pose(
-    xr_data: &OpenXrData<impl crate::openxr_data::Compositor>,
     session_data: &SessionData,
     origin: vr::ETrackingUniverseOrigin,
+    time: xr::Time,
 ) -> Option<vr::TrackedDevicePose_t> {
     let (location, velocity) = {
         session_data
             .view_space
-            .relate(
-                session_data.get_space_for_origin(origin),
-                xr_data.display_time.get(),
-            )
+            .relate(session_data.get_space_for_origin(origin), time)
             .ok()?
     };
 
@@ -42,10 +134,10 @@ fn get_hmd_pose(
 }
 
 fn get_controller_pose(
-    xr_data: &OpenXrData<impl crate::openxr_data::Compositor>,
     session_data: &SessionData,
     controller: &TrackedDevice,
     origin: vr::ETrackingUniverseOrigin,
+    time: xr::Time,
 ) -> Option<vr::TrackedDevicePose_t> {
     let pose_data = session_data.input_data.pose_data.get()?;
 
@@ -57,11 +149,8 @@ fn get_controller_pose(
     let (location, velocity) = if let Some(raw) =
         spaces.try_get_or_init_raw(&controller.interaction_profile, session_data, pose_data)
     {
-        raw.relate(
-            session_data.get_space_for_origin(origin),
-            xr_data.display_time.get(),
-        )
-        .ok()?
+        raw.relate(session_data.get_space_for_origin(origin), time)
+            .ok()?
     } else {
         trace!("Failed to get raw space, returning empty pose");
         (xr::SpaceLocation::default(), xr::SpaceVelocity::default())
@@ -83,26 +172,52 @@ impl TrackedDevice {
             connected: device_type == TrackedDeviceType::Hmd,
             previous_connected: false,
             pose_cache: Mutex::new(None),
+            activity: Mutex::new(ActivityTimer {
+                last_interaction: Instant::now(),
+                last_pose: None,
+            }),
+            pose_filter: Mutex::default(),
+            velocity_limit: VelocityLimit::default(),
         }
     }
 
+    /// If `predicted_time` is provided, the pose is located at that time and the cache is bypassed,
+    /// otherwise the current display time is used.
     pub fn get_pose(
         &self,
         xr_data: &OpenXrData<impl crate::openxr_data::Compositor>,
         session_data: &SessionData,
         origin: vr::ETrackingUniverseOrigin,
+        predicted_time: Option<xr::Time>,
     ) -> Option<vr::TrackedDevicePose_t> {
+        let locate = |time| match self.device_type {
+            TrackedDeviceType::Hmd => get_hmd_pose(session_data, origin, time),
+            TrackedDeviceType::Controller { .. } => {
+                get_controller_pose(session_data, self, origin, time)
+            }
+        };
+
+        if let Some(time) = predicted_time {
+            return locate(time).map(|mut pose| {
+                self.velocity_limit.apply(&mut pose);
+                pose
+            });
+        }
+
         let mut pose_cache = self.pose_cache.lock().unwrap();
         if let Some(pose) = *pose_cache {
             return Some(pose);
         }
 
-        *pose_cache = match self.device_type {
-            TrackedDeviceType::Hmd => get_hmd_pose(xr_data, session_data, origin),
-            TrackedDeviceType::Controller { .. } => {
-                get_controller_pose(xr_data, session_data, self, origin)
+        *pose_cache = locate(xr_data.display_time.get());
+        if let Some(pose) = pose_cache.as_mut().filter(|pose| pose.bPoseIsValid) {
+            // Smoothing the headset would only add latency.
+            if self.device_type != TrackedDeviceType::Hmd {
+                self.pose_filter.lock().unwrap().apply(pose);
+                self.velocity_limit.apply(pose);
             }
-        };
+            self.update_activity(&pose.mDeviceToAbsoluteTracking);
+        }
 
         *pose_cache
     }
@@ -111,9 +226,71 @@ impl TrackedDevice {
         std::mem::take(&mut *self.pose_cache.lock().unwrap());
     }
 
-    pub fn has_connected_changed(&mut self) -> bool {
-        if self.previous_connected != self.connected {
-            self.previous_connected = self.connected;
+    fn update_activity(&self, pose: &vr::HmdMatrix34_t) {
+        let mut activity = self.activity.lock().unwrap();
+        let moved = activity.last_pose.is_none_or(|last| {
+            last.iter()
+                .flatten()
+                .zip(pose.m.iter().flatten())
+                .any(|(last, current)| (last - current).abs() > MOVEMENT_THRESHOLD)
+        });
+
+        if moved {
+            activity.last_interaction = Instant::now();
+            activity.last_pose = Some(pose.m);
+        }
+    }
+
+    /// How recently this device was moved, as a graduated activity level.
+    pub fn activity_level(&self) -> vr::EDeviceActivityLevel {
+        let idle_time = self.activity.lock().unwrap().last_interaction.elapsed();
+        match idle_time {
+            t if t < USER_INTERACTION_TIMEOUT => vr::EDeviceActivityLevel::UserInteraction,
+            t if t < IDLE_THRESHOLD => vr::EDeviceActivityLevel::UserInteraction_Timeout,
+            t if t < IDLE_TIMEOUT => vr::EDeviceActivityLevel::Idle,
+            _ => vr::EDeviceActivityLevel::Idle_Timeout,
+        }
+    }
+
+    /// Pretends the device was last interacted with `duration` earlier than it actually was.
+    #[cfg(test)]
+    pub(super) fn backdate_activity(&self, duration: Duration) {
+        let mut activity = self.activity.lock().unwrap();
+        activity.last_interaction = activity
+            .last_interaction
+            .checked_sub(duration)
+            .expect("Couldn't backdate activity timer");
+    }
+
+    /// Whether this device has everything it needs to actually be tracked. Controllers need a
+    /// bound interaction profile and a valid space - otherwise they were only partially
+    /// enumerated, and shouldn't be reported as connected yet.
+    pub fn is_connectable(&self, session_data: &SessionData) -> bool {
+        let TrackedDeviceType::Controller { hand } = self.device_type else {
+            return true;
+        };
+
+        if self.interaction_profile.is_none() || self.profile_path == xr::Path::NULL {
+            return false;
+        }
+
+        let Some(pose_data) = session_data.input_data.pose_data.get() else {
+            return false;
+        };
+        let spaces = match hand {
+            Hand::Left => &pose_data.left_space,
+            Hand::Right => &pose_data.right_space,
+        };
+
+        spaces
+            .try_get_or_init_raw(&self.interaction_profile, session_data, pose_data)
+            .is_some()
+    }
+
+    pub fn has_connected_changed(&mut self, connectable: bool) -> bool {
+        let current = self.connected && connectable;
+        if self.previous_connected != current {
+            self.previous_connected = current;
             true
         } else {
             false
@@ -152,12 +329,50 @@ impl SubactionPaths {
 
 pub struct TrackedDeviceList {
     devices: Vec<TrackedDevice>,
+    pose_smoothing: f32,
+    velocity_limit: VelocityLimit,
+    /// Report controllers as connected even before the runtime gives them an interaction
+    /// profile - see XRIZER_FORCE_CONNECTED_CONTROLLERS.
+    force_connected: bool,
 }
 
 impl TrackedDeviceList {
-    pub(super) fn new() -> Self {
+    pub(super) fn new(pose_smoothing: f32, velocity_limit: VelocityLimit) -> Self {
         Self {
             devices: vec![TrackedDevice::new(TrackedDeviceType::Hmd, None, None)],
+            pose_smoothing,
+            velocity_limit,
+            force_connected: false,
+        }
+    }
+
+    pub(super) fn force_connected(&self) -> bool {
+        self.force_connected
+    }
+
+    /// Creates both controllers up front and keeps them connected, for games that won't start
+    /// without them and for testing without controllers.
+    pub(super) fn force_connect_controllers(&mut self) {
+        self.force_connected = true;
+        for hand in [Hand::Left, Hand::Right] {
+            if let Some(controller) = self.get_controller_mut(hand) {
+                controller.connected = true;
+                continue;
+            }
+
+            let mut device = TrackedDevice::new(TrackedDeviceType::Controller { hand }, None, None);
+            device.connected = true;
+            self.push_device(device).unwrap_or_else(|e| {
+                panic!("Failed to create forced controller: {e:?}");
+            });
+        }
+    }
+
+    #[cfg(test)]
+    pub(super) fn set_pose_smoothing(&mut self, strength: f32) {
+        self.pose_smoothing = strength;
+        for device in &mut self.devices {
+            device.pose_filter.get_mut().unwrap().strength = strength;
         }
     }
 
@@ -176,7 +391,7 @@ impl TrackedDeviceList {
 
     pub(super) fn push_device(
         &mut self,
-        device: TrackedDevice,
+        mut device: TrackedDevice,
     ) -> Result<vr::TrackedDeviceIndex_t, vr::EVRInputError> {
         let index = self.devices.len() as vr::TrackedDeviceIndex_t;
 
@@ -184,6 +399,8 @@ impl TrackedDeviceList {
             return Err(vr::EVRInputError::MaxCapacityReached);
         }
 
+        device.pose_filter.get_mut().unwrap().strength = self.pose_smoothing;
+        device.velocity_limit = self.velocity_limit;
         self.devices.push(device);
 
         Ok(index)
@@ -220,6 +437,7 @@ impl<C: openxr_data::Compositor> Input<C> {
         &self,
         poses: &mut [vr::TrackedDevicePose_t],
         origin: Option<vr::ETrackingUniverseOrigin>,
+        predicted_time: Option<xr::Time>,
     ) {
         tracy_span!();
         let devices = self.devices.read().unwrap();
@@ -234,10 +452,22 @@ impl<C: openxr_data::Compositor> Input<C> {
                         &self.openxr,
                         &session_data,
                         origin.unwrap_or(session_data.current_origin),
+                        predicted_time,
                     )
                     .unwrap_or_default();
             }
         }
+
+        if let Some(log) = self.pose_log.get() {
+            log.lock().unwrap().log_poses(
+                self.openxr.frame_metrics.frame_index(),
+                poses
+                    .iter()
+                    .enumerate()
+                    .map(|(i, pose)| (i as vr::TrackedDeviceIndex_t, pose))
+                    .filter(|(i, _)| devices.get_device(*i).is_some()),
+            );
+        }
     }
 
     pub fn get_controller_pose(
@@ -263,6 +493,7 @@ impl<C: openxr_data::Compositor> Input<C> {
             &self.openxr,
             &session_data,
             origin.unwrap_or(session_data.current_origin),
+            None,
         )
     }
 
@@ -272,6 +503,19 @@ impl<C: openxr_data::Compositor> Input<C> {
         devices.get_device(index).is_some_and(|d| d.connected)
     }
 
+    /// Returns None if the device doesn't exist or isn't connected.
+    pub fn get_device_activity_level(
+        &self,
+        index: vr::TrackedDeviceIndex_t,
+    ) -> Option<vr::EDeviceActivityLevel> {
+        let devices = self.devices.read().unwrap();
+
+        devices
+            .get_device(index)
+            .filter(|d| d.connected)
+            .map(TrackedDevice::activity_level)
+    }
+
     pub fn device_index_to_device_type(
         &self,
         index: vr::TrackedDeviceIndex_t,
@@ -295,13 +539,76 @@ impl<C: openxr_data::Compositor> Input<C> {
         devices.get_controller_index(hand)
     }
 
-    fn get_profile_data(&self, hand: Hand) -> Option<&super::profiles::ProfileProperties> {
+    #[cfg(test)]
+    pub fn set_swap_hands(&self, swap: bool) {
+        self.swap_hands.store(swap, Ordering::Relaxed);
+    }
+
+    /// Maps between controller roles and the hand of the controller filling that role.
+    /// This is its own inverse.
+    fn role_hand(&self, hand: Hand) -> Hand {
+        match (self.swap_hands.load(Ordering::Relaxed), hand) {
+            (false, hand) => hand,
+            (true, Hand::Left) => Hand::Right,
+            (true, Hand::Right) => Hand::Left,
+        }
+    }
+
+    /// Like [`Self::get_controller_device_index`], but respects XRIZER_SWAP_HANDS.
+    /// Should be used for anything that reports controller roles to the game.
+    pub fn get_controller_device_index_for_role(
+        &self,
+        role: Hand,
+    ) -> Option<vr::TrackedDeviceIndex_t> {
+        self.get_controller_device_index(self.role_hand(role))
+    }
+
+    /// Like [`Self::device_index_to_hand`], but respects XRIZER_SWAP_HANDS.
+    /// Should be used for anything that reports controller roles to the game.
+    pub fn device_index_to_role(&self, index: vr::TrackedDeviceIndex_t) -> Option<Hand> {
+        self.device_index_to_hand(index)
+            .map(|hand| self.role_hand(hand))
+    }
+
+    fn get_profile_data(&self, hand: Hand) -> Option<&ProfileProperties> {
         let devices = self.devices.read().unwrap();
         let controller = devices.get_controller(hand)?;
 
         self.profile_map.get(&controller.profile_path).map(|v| &**v)
     }
 
+    /// Whether the controller's main axis is a thumbstick or a trackpad.
+    pub fn get_controller_main_axis(&self, hand: Hand) -> Option<MainAxisType> {
+        self.get_profile_data(hand).map(|data| self.main_axis(data))
+    }
+
+    /// With trackpad emulation, Index controllers report their thumbstick as a trackpad, for games
+    /// that only support the Vive wands.
+    fn main_axis(&self, data: &ProfileProperties) -> MainAxisType {
+        if self.trackpad_emulation.load(Ordering::Relaxed)
+            && std::ptr::eq(data, Knuckles.properties())
+        {
+            MainAxisType::Trackpad
+        } else {
+            data.main_axis
+        }
+    }
+
+    /// With trackpad buttons, Vive wands press A/B/X/Y with the top and bottom of the trackpad, for
+    /// games that expect Touch or Index controllers.
+    pub fn has_trackpad_buttons(&self, hand: Hand) -> bool {
+        self.trackpad_buttons.load(Ordering::Relaxed)
+            && self
+                .get_profile_data(hand)
+                .is_some_and(|data| std::ptr::eq(data, ViveWands.properties()))
+    }
+
+    /// The controller's Axis2 stick, if it has one in addition to its main axis.
+    pub fn get_controller_secondary_axis(&self, hand: Hand) -> Option<MainAxisType> {
+        self.get_profile_data(hand)
+            .and_then(|data| data.secondary_axis)
+    }
+
     pub fn get_controller_string_tracked_property(
         &self,
         hand: Hand,
@@ -332,7 +639,8 @@ impl<C: openxr_data::Compositor> Input<C> {
                     Some(*data.serial_number.get(hand))
                 }
                 vr::ETrackedDeviceProperty::ManufacturerName_String => Some(data.manufacturer_name),
-                _ => None,
+                // SteamVR's status window shows these
+                other => data.icons.get(hand).get(other),
             }
         })
     }
@@ -343,22 +651,40 @@ impl<C: openxr_data::Compositor> Input<C> {
         property: vr::ETrackedDeviceProperty,
     ) -> Option<i32> {
         self.get_profile_data(hand).and_then(|data| match property {
-            vr::ETrackedDeviceProperty::Axis0Type_Int32 => match data.main_axis {
-                MainAxisType::Thumbstick => Some(vr::EVRControllerAxisType::Joystick as _),
-                MainAxisType::Trackpad => Some(vr::EVRControllerAxisType::TrackPad as _),
-            },
+            vr::ETrackedDeviceProperty::Axis0Type_Int32 => {
+                Some(self.main_axis(data).axis_type() as _)
+            }
             vr::ETrackedDeviceProperty::Axis1Type_Int32 => {
                 Some(vr::EVRControllerAxisType::Trigger as _)
             }
-            vr::ETrackedDeviceProperty::Axis2Type_Int32 => {
+            vr::ETrackedDeviceProperty::Axis2Type_Int32 => match data.secondary_axis {
+                Some(axis) => Some(axis.axis_type() as _),
                 // This is actually the grip, and gets recognized as such
-                Some(vr::EVRControllerAxisType::Trigger as _)
-            }
+                None => Some(vr::EVRControllerAxisType::Trigger as _),
+            },
             // TODO: report knuckles trackpad?
             vr::ETrackedDeviceProperty::Axis3Type_Int32
             | vr::ETrackedDeviceProperty::Axis4Type_Int32 => {
                 Some(vr::EVRControllerAxisType::None as _)
             }
+            // Higher numbers are preferred - prefer the dominant hand
+            vr::ETrackedDeviceProperty::ControllerHandSelectionPriority_Int32 => {
+                Some((*self.dominant_hand.lock().unwrap() == hand) as i32)
+            }
+            _ => None,
+        })
+    }
+
+    pub fn get_controller_bool_tracked_property(
+        &self,
+        hand: Hand,
+        property: vr::ETrackedDeviceProperty,
+    ) -> Option<bool> {
+        self.get_profile_data(hand).and_then(|_| match property {
+            // OpenXR has no way of powering off controllers.
+            vr::ETrackedDeviceProperty::DeviceCanPowerOff_Bool => Some(false),
+            // None of the controllers we support are tethered.
+            vr::ETrackedDeviceProperty::DeviceIsWireless_Bool => Some(true),
             _ => None,
         })
     }
@@ -369,8 +695,106 @@ impl<C: openxr_data::Compositor> Input<C> {
         property: vr::ETrackedDeviceProperty,
     ) -> Option<u64> {
         self.get_profile_data(hand).and_then(|data| match property {
-            vr::ETrackedDeviceProperty::SupportedButtons_Uint64 => Some(data.legacy_buttons_mask),
+            vr::ETrackedDeviceProperty::SupportedButtons_Uint64 => {
+                let emulated = if self.has_trackpad_buttons(hand) {
+                    button_mask_from_id(vr::EVRButtonId::A)
+                } else {
+                    0
+                };
+                Some(data.legacy_buttons_mask | emulated)
+            }
             _ => None,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{PoseFilter, VelocityLimit};
+    use glam::Vec3;
+    use openvr as vr;
+    use openxr as xr;
+
+    #[test]
+    fn pose_filter_reduces_jitter() {
+        // Deterministic noise around x = 1.0
+        let noise = [
+            0.02, -0.015, 0.03, -0.025, 0.01, -0.03, 0.02, -0.01, 0.025, -0.02,
+        ];
+        let poses = noise.iter().cycle().take(50).map(|n| {
+            let mut pose = vr::TrackedDevicePose_t {
+                bPoseIsValid: true,
+                ..Default::default()
+            };
+            pose.mDeviceToAbsoluteTracking = xr::Posef {
+                orientation: xr::Quaternionf::IDENTITY,
+                position: xr::Vector3f {
+                    x: 1.0 + n,
+                    y: 0.0,
+                    z: 0.0,
+                },
+            }
+            .into();
+            pose
+        });
+
+        let variance = |xs: &[f32]| {
+            let mean = xs.iter().sum::<f32>() / xs.len() as f32;
+            xs.iter().map(|x| (x - mean).powi(2)).sum::<f32>() / xs.len() as f32
+        };
+
+        let mut off = PoseFilter::default();
+        let mut on = PoseFilter {
+            strength: 0.8,
+            last: None,
+        };
+        let (mut raw, mut filtered) = (Vec::new(), Vec::new());
+        for pose in poses {
+            let mut raw_pose = pose;
+            off.apply(&mut raw_pose);
+            raw.push(raw_pose.mDeviceToAbsoluteTracking.m[0][3]);
+
+            let mut filtered_pose = pose;
+            on.apply(&mut filtered_pose);
+            filtered.push(filtered_pose.mDeviceToAbsoluteTracking.m[0][3]);
+        }
+
+        // Disabled filter leaves poses untouched
+        assert_eq!(raw[0], 1.0 + noise[0]);
+        // Skip the first few poses while the filter settles
+        assert!(
+            variance(&filtered[10..]) < variance(&raw[10..]) / 2.0,
+            "raw: {raw:?}\nfiltered: {filtered:?}"
+        );
+    }
+
+    #[test]
+    fn velocity_spikes_are_clamped() {
+        let mut pose = vr::TrackedDevicePose_t {
+            bPoseIsValid: true,
+            ..Default::default()
+        };
+        pose.vVelocity.v = [30.0, 0.0, 40.0];
+        pose.vAngularVelocity.v = [0.0, 2.0, 0.0];
+
+        let limit = VelocityLimit {
+            linear: Some(10.0),
+            angular: Some(20.0),
+        };
+        limit.apply(&mut pose);
+
+        // Scaled down to the limit, keeping the direction
+        let velocity = Vec3::from(pose.vVelocity.v);
+        assert!(
+            velocity.abs_diff_eq(Vec3::new(6.0, 0.0, 8.0), 1e-5),
+            "{velocity}"
+        );
+        // Below the limit, so untouched
+        assert_eq!(pose.vAngularVelocity.v, [0.0, 2.0, 0.0]);
+
+        let mut unlimited = pose;
+        unlimited.vVelocity.v = [30.0, 0.0, 40.0];
+        VelocityLimit::default().apply(&mut unlimited);
+        assert_eq!(unlimited.vVelocity.v, [30.0, 0.0, 40.0]);
+    }
+}
@@ -1,26 +1,108 @@
-use super::{Input, PoseData, Profiles, WriteOnDrop};
+use super::{profiles::MainAxisType, Input, PoseData, Profiles, WriteOnDrop};
 use crate::{
     input::LoadedActions,
-    openxr_data::{self, Hand},
+    openxr_data::{self, Hand, SessionData},
 };
-use log::{debug, warn};
+use log::{debug, info, warn};
 use openvr as vr;
 use openxr as xr;
 use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
 
 #[derive(Default)]
 pub(super) struct LegacyState {
     packet_num: AtomicU32,
     got_state_this_frame: [AtomicBool; 2],
+    /// Last grip state synthesized from the squeeze value, for controllers without a grip button
+    synthesized_grip: [AtomicBool; 2],
+    /// Last trigger click synthesized from the trigger value, for controllers without a click
+    synthesized_trigger: [AtomicBool; 2],
+    /// Last A, application menu and trackpad click states emulated from trackpad quadrants
+    /// (XRIZER_TRACKPAD_BUTTONS)
+    emulated_a: [AtomicBool; 2],
+    emulated_app_menu: [AtomicBool; 2],
+    emulated_trackpad_click: [AtomicBool; 2],
+    /// Only set if the action sync rate is decoupled from the frame rate (XRIZER_INPUT_RATE)
+    sync_timer: Mutex<Option<SyncTimer>>,
 }
 
 impl LegacyState {
+    pub fn new() -> Self {
+        let rate = std::env::var("XRIZER_INPUT_RATE").ok().and_then(|rate| {
+            rate.parse::<f32>()
+                .ok()
+                .filter(|rate| *rate > 0.0)
+                .or_else(|| {
+                    warn!("Invalid XRIZER_INPUT_RATE ({rate})");
+                    None
+                })
+        });
+        Self {
+            sync_timer: rate.map(SyncTimer::new).into(),
+            ..Default::default()
+        }
+    }
+
     pub fn on_action_sync(&self) {
         self.packet_num.fetch_add(1, Ordering::Relaxed);
         for state in &self.got_state_this_frame {
             state.store(false, Ordering::Relaxed);
         }
     }
+
+    #[cfg(test)]
+    pub fn set_sync_rate(&self, rate: Option<f32>) {
+        *self.sync_timer.lock().unwrap() = rate.map(SyncTimer::new);
+    }
+
+    /// Whether the per frame update should sync actions - always true without a fixed rate.
+    fn frame_sync_due(&self) -> bool {
+        self.sync_timer
+            .lock()
+            .unwrap()
+            .as_mut()
+            .is_none_or(|timer| timer.tick(Instant::now()))
+    }
+
+    /// Whether a state query should sync actions - only if a fixed rate is set, so input can be
+    /// synced more often than the frame rate.
+    fn query_sync_due(&self) -> bool {
+        self.sync_timer
+            .lock()
+            .unwrap()
+            .as_mut()
+            .is_some_and(|timer| timer.tick(Instant::now()))
+    }
+}
+
+struct SyncTimer {
+    period: Duration,
+    next: Instant,
+}
+
+impl SyncTimer {
+    fn new(rate: f32) -> Self {
+        assert!(rate > 0.0);
+        Self {
+            period: Duration::from_secs_f32(1.0 / rate),
+            next: Instant::now(),
+        }
+    }
+
+    /// Returns true if a sync is due, scheduling the next one.
+    fn tick(&mut self, now: Instant) -> bool {
+        if now < self.next {
+            return false;
+        }
+
+        self.next += self.period;
+        // Don't try to catch up on syncs we missed.
+        if self.next <= now {
+            self.next = now + self.period;
+        }
+        true
+    }
 }
 
 // Adapted from openvr.h
@@ -39,15 +121,61 @@ impl<C: openxr_data::Compositor> Input<C> {
         debug!("setting up legacy actions");
 
         let session_data = self.openxr.session_data.get();
-        let session = &session_data.session;
+
+        // Games that use legacy input may still probe for actions, so the default manifest is
+        // loaded next to the legacy actions if there is one.
+        if let Some(path) = self.get_default_manifest_path() {
+            info!(
+                "No action manifest provided, loading default manifest from {}",
+                path.display()
+            );
+            match self.load_default_action_manifest(&session_data, path) {
+                Ok(()) => return,
+                Err(e) => {
+                    warn!("Failed to load default action manifest ({e:?}), using legacy input only")
+                }
+            }
+        }
+
         let legacy = LegacyActionData::new(
             &self.openxr.instance,
             self.subaction_paths.left,
             self.subaction_paths.right,
         );
+        let session = &session_data.session;
         let input_data = &session_data.input_data;
+        let pose_data = input_data.pose_data.get().unwrap();
+        self.suggest_legacy_bindings(&legacy, pose_data, |_| true);
+
+        let pose_set = &pose_data.set;
+
+        session
+            .attach_action_sets(&[&legacy.set, pose_set])
+            .unwrap();
+        session
+            .sync_actions(&[
+                xr::ActiveActionSet::new(&legacy.set),
+                xr::ActiveActionSet::new(pose_set),
+            ])
+            .unwrap();
 
-        for profile in Profiles::get().profiles_iter() {
+        input_data
+            .actions
+            .set(LoadedActions::Legacy(legacy))
+            .unwrap_or_else(|_| panic!("Actions unexpectedly set up"));
+    }
+
+    /// Suggests the legacy bindings for every enabled interaction profile accepted by `filter`.
+    pub(super) fn suggest_legacy_bindings(
+        &self,
+        legacy: &LegacyActionData,
+        pose_data: &PoseData,
+        filter: impl Fn(xr::Path) -> bool,
+    ) {
+        for profile in Profiles::get()
+            .profiles_iter()
+            .filter(|p| self.is_profile_enabled(*p))
+        {
             const fn constrain<F>(f: F) -> F
             where
                 F: for<'a> Fn(&'a str) -> xr::Path,
@@ -55,35 +183,56 @@ impl<C: openxr_data::Compositor> Input<C> {
                 f
             }
             let stp = constrain(|s| self.openxr.instance.string_to_path(s).unwrap());
+            let profile_path = stp(profile.profile_path());
+            if !filter(profile_path) {
+                continue;
+            }
+
             let bindings = profile.legacy_bindings(&stp);
-            let profile = stp(profile.profile_path());
+            let recenter_binding = self
+                .recenter_binding(profile)
+                .map(|path| xr::Binding::new(&pose_data.recenter, path));
             self.openxr
                 .instance
                 .suggest_interaction_profile_bindings(
-                    profile,
-                    &bindings
-                        .into_iter(&legacy.actions, input_data.pose_data.get().unwrap())
+                    profile_path,
+                    &legacy
+                        .bindings(bindings, &pose_data.grip, &pose_data.aim)
+                        .chain(recenter_binding)
                         .collect::<Vec<_>>(),
                 )
                 .unwrap();
         }
+    }
 
-        let pose_set = &input_data.pose_data.get().unwrap().set;
+    /// Syncs the legacy actions, unless the action sync rate is decoupled from the frame rate and
+    /// a sync isn't due yet.
+    pub(super) fn frame_sync_legacy_actions(&self, data: &SessionData, legacy: &LegacyActionData) {
+        if self.legacy_state.frame_sync_due() {
+            self.sync_legacy_actions(data, legacy);
+        }
+    }
 
-        session
-            .attach_action_sets(&[&legacy.set, pose_set])
-            .unwrap();
-        session
-            .sync_actions(&[
-                xr::ActiveActionSet::new(&legacy.set),
-                xr::ActiveActionSet::new(pose_set),
-            ])
-            .unwrap();
+    fn sync_legacy_actions(&self, data: &SessionData, legacy: &LegacyActionData) {
+        let mut sync_sets = vec![
+            xr::ActiveActionSet::new(&legacy.set),
+            xr::ActiveActionSet::new(&data.input_data.pose_data.get().unwrap().set),
+        ];
+        // Keep the sets the game activated from the default manifest active as well, since
+        // syncing deactivates any sets that aren't passed in.
+        if let Some(loaded) = data.input_data.get_loaded_actions() {
+            let active_sets = self.active_sets.read().unwrap();
+            sync_sets.extend(
+                active_sets
+                    .iter()
+                    .filter_map(|key| loaded.sets.get(*key))
+                    .map(xr::ActiveActionSet::new),
+            );
+        }
+        data.session.sync_actions(&sync_sets).unwrap();
+        self.check_recenter_action(data);
 
-        input_data
-            .actions
-            .set(LoadedActions::Legacy(legacy))
-            .unwrap_or_else(|_| panic!("Actions unexpectedly set up"));
+        self.legacy_state.on_action_sync();
     }
 
     pub fn legacy_haptic(
@@ -93,7 +242,11 @@ impl<C: openxr_data::Compositor> Input<C> {
         duration_us: std::ffi::c_ushort,
     ) {
         let data = self.openxr.session_data.get();
-        if data.input_data.get_loaded_actions().is_some() {
+        if data
+            .input_data
+            .get_loaded_actions()
+            .is_some_and(|loaded| loaded.legacy.is_none())
+        {
             debug!("not trigger legacy haptic due to loaded actions");
             return;
         }
@@ -154,7 +307,11 @@ impl<C: openxr_data::Compositor> Input<C> {
         let state = &mut state.value;
 
         let data = self.openxr.session_data.get();
-        if data.input_data.get_loaded_actions().is_some() {
+        if data
+            .input_data
+            .get_loaded_actions()
+            .is_some_and(|loaded| loaded.legacy.is_none())
+        {
             debug!("not returning legacy controller state due to loaded actions");
             return false;
         }
@@ -163,6 +320,9 @@ impl<C: openxr_data::Compositor> Input<C> {
             debug!("tried getting controller state, but legacy actions aren't ready");
             return false;
         };
+        if self.legacy_state.query_sync_due() {
+            self.sync_legacy_actions(&data, legacy);
+        }
         let actions = &legacy.actions;
 
         let Some(hand) = self.device_index_to_hand(device_index) else {
@@ -182,13 +342,13 @@ impl<C: openxr_data::Compositor> Input<C> {
             .is_ok()
             .then(|| self.events.lock().unwrap());
 
+        let click = |action: &xr::Action<bool>| action.state(&data.session, hand_path).unwrap();
         let mut read_button =
-            |id, click_action: &xr::Action<bool>, touch_action: Option<&xr::Action<bool>>| {
+            |id, click_state: xr::ActionState<bool>, touch_action: Option<&xr::Action<bool>>| {
                 let touch_state = touch_action.map(|a| a.state(&data.session, hand_path).unwrap());
                 let touched = touch_state.is_some_and(|s| s.current_state);
                 state.ulButtonTouched |= button_mask_from_id(id) & (touched as u64 * u64::MAX);
 
-                let click_state = click_action.state(&data.session, hand_path).unwrap();
                 let pressed = click_state.current_state;
                 state.ulButtonPressed |= button_mask_from_id(id) & (pressed as u64 * u64::MAX);
 
@@ -218,25 +378,124 @@ impl<C: openxr_data::Compositor> Input<C> {
                 }
             };
 
+        // For buttons that aren't bound (e.g. Touch controllers only have an analog squeeze and
+        // trigger), press them past a threshold of the analog value instead.
+        let click_or_threshold = |click_action: &xr::Action<bool>,
+                                  value: &xr::Action<f32>,
+                                  threshold: f32,
+                                  last: &[AtomicBool; 2]| {
+            let state = click(click_action);
+            if state.is_active {
+                return state;
+            }
+            let value = value.state(&data.session, hand_path).unwrap();
+            let pressed = value.is_active && value.current_state >= threshold;
+            let was_pressed = last[hand as usize - 1].swap(pressed, Ordering::Relaxed);
+            xr::ActionState {
+                current_state: pressed,
+                changed_since_last_sync: pressed != was_pressed,
+                last_change_time: value.last_change_time,
+                is_active: value.is_active,
+            }
+        };
+        let grip_state = click_or_threshold(
+            &actions.squeeze_click,
+            &actions.squeeze,
+            self.legacy_grip_threshold.load(),
+            &self.legacy_state.synthesized_grip,
+        );
+        let trigger_state = click_or_threshold(
+            &actions.trigger_click,
+            &actions.trigger,
+            self.legacy_trigger_threshold.load(),
+            &self.legacy_state.synthesized_trigger,
+        );
+
+        let mut trackpad_click = click(&actions.main_xy_click);
+        let mut app_menu = click(&actions.app_menu);
+        let mut a = click(&actions.a);
+        if self.has_trackpad_buttons(hand) {
+            // The top of the trackpad is B/Y (the application menu, like on Touch controllers)
+            // and the bottom is A/X. The sides are still a regular trackpad click.
+            let xy = actions
+                .main_xy
+                .state(&data.session, hand_path)
+                .unwrap()
+                .current_state;
+            let clicked = trackpad_click.current_state;
+            let vertical = xy.y.abs() >= xy.x.abs();
+            let emulate = |pressed: bool, base: xr::ActionState<bool>, last: &[AtomicBool; 2]| {
+                let pressed = base.current_state || pressed;
+                let was_pressed = last[hand as usize - 1].swap(pressed, Ordering::Relaxed);
+                xr::ActionState {
+                    current_state: pressed,
+                    changed_since_last_sync: pressed != was_pressed,
+                    ..base
+                }
+            };
+
+            app_menu = emulate(
+                clicked && vertical && xy.y > 0.0,
+                app_menu,
+                &self.legacy_state.emulated_app_menu,
+            );
+            a = emulate(
+                clicked && vertical && xy.y <= 0.0,
+                a,
+                &self.legacy_state.emulated_a,
+            );
+            trackpad_click = emulate(
+                clicked && !vertical,
+                xr::ActionState {
+                    current_state: false,
+                    ..trackpad_click
+                },
+                &self.legacy_state.emulated_trackpad_click,
+            );
+        }
+
         read_button(
             vr::EVRButtonId::Axis0,
-            &actions.main_xy_click,
+            trackpad_click,
             Some(&actions.main_xy_touch),
         );
         read_button(
             vr::EVRButtonId::SteamVR_Trigger,
-            &actions.trigger_click,
-            None,
+            trigger_state,
+            Some(&actions.trigger_touch),
+        );
+        read_button(
+            vr::EVRButtonId::ApplicationMenu,
+            app_menu,
+            Some(&actions.app_menu_touch),
         );
-        read_button(vr::EVRButtonId::ApplicationMenu, &actions.app_menu, None);
-        read_button(vr::EVRButtonId::A, &actions.a, None);
-        read_button(vr::EVRButtonId::Grip, &actions.squeeze_click, None);
-        read_button(vr::EVRButtonId::Axis2, &actions.squeeze_click, None);
+        read_button(vr::EVRButtonId::A, a, Some(&actions.a_touch));
+        read_button(vr::EVRButtonId::Grip, grip_state, None);
+        let secondary_axis = self.get_controller_secondary_axis(hand);
+        // Controllers with a second stick (WMR) report its click as Axis2, like SteamVR does.
+        let axis2_click = match secondary_axis {
+            Some(_) => click(&actions.secondary_xy_click),
+            None => grip_state,
+        };
+        read_button(vr::EVRButtonId::Axis2, axis2_click, None);
 
         let j = actions.main_xy.state(&data.session, hand_path).unwrap();
+        let main_xy = match self.get_controller_main_axis(hand) {
+            // Like the wands, report the finger position whenever the trackpad is touched,
+            // whether or not it's clicked - teleport arcs are aimed this way.
+            Some(MainAxisType::Trackpad) => {
+                let axis0 = button_mask_from_id(vr::EVRButtonId::Axis0);
+                if (state.ulButtonTouched | state.ulButtonPressed) & axis0 != 0 {
+                    j.current_state
+                } else {
+                    xr::Vector2f::default()
+                }
+            }
+            _ => self.apply_thumbstick_deadzone(j.current_state),
+        };
         state.rAxis[0] = vr::VRControllerAxis_t {
-            x: j.current_state.x,
-            y: j.current_state.y,
+            x: main_xy.x,
+            y: main_xy.y,
         };
 
         let t = actions.trigger.state(&data.session, hand_path).unwrap();
@@ -245,12 +504,73 @@ impl<C: openxr_data::Compositor> Input<C> {
             y: 0.0,
         };
 
-        let s = actions.squeeze.state(&data.session, hand_path).unwrap();
-        state.rAxis[2] = vr::VRControllerAxis_t {
-            x: s.current_state,
-            y: 0.0,
+        state.rAxis[2] = match secondary_axis {
+            Some(_) => {
+                let s = actions
+                    .secondary_xy
+                    .state(&data.session, hand_path)
+                    .unwrap();
+                let xy = self.apply_thumbstick_deadzone(s.current_state);
+                vr::VRControllerAxis_t { x: xy.x, y: xy.y }
+            }
+            None => {
+                let s = actions.squeeze.state(&data.session, hand_path).unwrap();
+                vr::VRControllerAxis_t {
+                    x: s.current_state,
+                    y: 0.0,
+                }
+            }
+        };
+
+        if let Some(axis) = *self.legacy_grip_force_axis.lock().unwrap() {
+            let f = actions
+                .squeeze_force
+                .state(&data.session, hand_path)
+                .unwrap();
+            // Only controllers with a force sensor have this bound.
+            if f.is_active {
+                state.rAxis[axis] = vr::VRControllerAxis_t {
+                    x: f.current_state,
+                    y: 0.0,
+                };
+            }
+        }
+
+        true
+    }
+
+    /// Like [`Self::get_legacy_controller_state`], but also writes the controller's pose. If the
+    /// pose can't be located, it's marked invalid and disconnected rather than left at the origin,
+    /// while the state is still returned.
+    pub fn get_legacy_controller_state_with_pose(
+        &self,
+        device_index: vr::TrackedDeviceIndex_t,
+        state: *mut vr::VRControllerState_t,
+        state_size: u32,
+        origin: vr::ETrackingUniverseOrigin,
+        pose: *mut vr::TrackedDevicePose_t,
+    ) -> bool {
+        let Some(hand) = self.device_index_to_hand(device_index) else {
+            return false;
         };
 
+        if !self.get_legacy_controller_state(device_index, state, state_size) {
+            return false;
+        }
+
+        if let Some(pose) = unsafe { pose.as_mut() } {
+            *pose = self
+                .get_controller_pose(hand, Some(origin))
+                .unwrap_or_else(|| {
+                    debug!("couldn't locate {hand:?} controller for legacy state");
+                    vr::TrackedDevicePose_t {
+                        bPoseIsValid: false,
+                        bDeviceIsConnected: false,
+                        eTrackingResult: vr::ETrackingResult::Running_OutOfRange,
+                        ..Default::default()
+                    }
+                });
+        }
         true
     }
 }
@@ -263,9 +583,10 @@ mod marker {
     }
     pub struct Actions;
     pub struct Bindings {
-        // This pose is handled separately, in the PoseData struct,
-        // so we don't use an action for it, but we still need the binding.
+        // These poses are handled separately, in the PoseData struct,
+        // so we don't use an action for them, but we still need the bindings.
         pub grip_pose: Vec<xr::Path>,
+        pub aim_pose: Vec<xr::Path>,
     }
     impl ActionsMarker for Actions {
         type T<U: xr::ActionTy> = xr::Action<U>;
@@ -285,15 +606,23 @@ use marker::*;
 #[allow(private_interfaces, private_bounds)]
 pub(super) struct Legacy<M: ActionsMarker> {
     pub app_menu: Action<bool, M>,
+    pub app_menu_touch: Action<bool, M>,
     pub a: Action<bool, M>,
+    pub a_touch: Action<bool, M>,
     pub trigger_click: Action<bool, M>,
+    pub trigger_touch: Action<bool, M>,
     pub squeeze_click: Action<bool, M>,
     pub trigger: Action<f32, M>,
     pub squeeze: Action<f32, M>,
+    /// Only exposed to the game if XRIZER_LEGACY_GRIP_FORCE_AXIS is set
+    pub squeeze_force: Action<f32, M>,
     // This can be a stick or a trackpad, so we'll just call it "xy"
     pub main_xy: Action<xr::Vector2f, M>,
     pub main_xy_touch: Action<bool, M>,
     pub main_xy_click: Action<bool, M>,
+    /// Only bound for controllers with a second stick, reported as Axis2
+    pub secondary_xy: Action<xr::Vector2f, M>,
+    pub secondary_xy_click: Action<bool, M>,
     pub haptic: Action<xr::Haptic, M>,
     pub extra: M,
 }
@@ -305,7 +634,8 @@ impl LegacyBindings {
     fn into_iter<'a>(
         self,
         actions: &'a LegacyActions,
-        pose_data: &'a PoseData,
+        grip_action: &'a xr::Action<xr::Posef>,
+        aim_action: &'a xr::Action<xr::Posef>,
     ) -> impl Iterator<Item = xr::Binding<'a>> {
         macro_rules! bindings {
             ($begin:expr, $($field:ident),+$(,)?) => {
@@ -322,16 +652,28 @@ impl LegacyBindings {
             self.extra
                 .grip_pose
                 .into_iter()
-                .map(|path| xr::Binding::new(&pose_data.grip, path)),
+                .map(|path| xr::Binding::new(grip_action, path))
+                .chain(
+                    self.extra
+                        .aim_pose
+                        .into_iter()
+                        .map(|path| xr::Binding::new(aim_action, path))
+                ),
             app_menu,
+            app_menu_touch,
             a,
+            a_touch,
             trigger_click,
+            trigger_touch,
             squeeze_click,
             trigger,
             squeeze,
+            squeeze_force,
             main_xy,
             main_xy_touch,
             main_xy_click,
+            secondary_xy,
+            secondary_xy_click,
             haptic,
         ]
     }
@@ -343,6 +685,16 @@ pub(super) struct LegacyActionData {
 }
 
 impl LegacyActionData {
+    /// The bindings for the legacy actions and the grip and aim poses on an interaction profile.
+    pub fn bindings<'a>(
+        &'a self,
+        bindings: LegacyBindings,
+        grip_action: &'a xr::Action<xr::Posef>,
+        aim_action: &'a xr::Action<xr::Posef>,
+    ) -> impl Iterator<Item = xr::Binding<'a>> {
+        bindings.into_iter(&self.actions, grip_action, aim_action)
+    }
+
     pub fn new(instance: &xr::Instance, left_hand: xr::Path, right_hand: xr::Path) -> Self {
         debug!("creating legacy actions");
         let leftright = [left_hand, right_hand];
@@ -355,12 +707,24 @@ impl LegacyActionData {
             trigger_click: set
                 .create_action("trigger-click", "Trigger Click", &leftright)
                 .unwrap(),
+            trigger_touch: set
+                .create_action("trigger-touch", "Trigger Touch", &leftright)
+                .unwrap(),
             trigger: set.create_action("trigger", "Trigger", &leftright).unwrap(),
             squeeze: set.create_action("squeeze", "Squeeze", &leftright).unwrap(),
+            squeeze_force: set
+                .create_action("squeeze-force", "Squeeze Force", &leftright)
+                .unwrap(),
             app_menu: set
                 .create_action("app-menu", "Application Menu", &leftright)
                 .unwrap(),
+            app_menu_touch: set
+                .create_action("app-menu-touch", "Application Menu Touch", &leftright)
+                .unwrap(),
             a: set.create_action("a", "A Button", &leftright).unwrap(),
+            a_touch: set
+                .create_action("a-touch", "A Button Touch", &leftright)
+                .unwrap(),
             squeeze_click: set
                 .create_action("grip-click", "Grip Click", &leftright)
                 .unwrap(),
@@ -373,6 +737,16 @@ impl LegacyActionData {
             main_xy_touch: set
                 .create_action("main-joystick-touch", "Main Joystick Touch", &leftright)
                 .unwrap(),
+            secondary_xy: set
+                .create_action("secondary-joystick", "Secondary Joystick", &leftright)
+                .unwrap(),
+            secondary_xy_click: set
+                .create_action(
+                    "secondary-joystick-click",
+                    "Secondary Joystick Click",
+                    &leftright,
+                )
+                .unwrap(),
             haptic: set.create_action("haptic", "Haptic", &leftright).unwrap(),
             extra: Actions,
         };
@@ -383,8 +757,12 @@ impl LegacyActionData {
 
 #[cfg(test)]
 mod tests {
-    use crate::input::profiles::{knuckles::Knuckles, simple_controller::SimpleController};
+    use crate::input::profiles::{
+        holographic_controller::HolographicController, knuckles::Knuckles, oculus_touch::Touch,
+        simple_controller::SimpleController, vive_controller::ViveWands, InteractionProfile,
+    };
     use crate::input::tests::{compare_pose, Fixture};
+    use fakexr::UserPath;
     use openvr as vr;
     use openxr as xr;
 
@@ -445,42 +823,53 @@ mod tests {
         assert!(!got_input);
     }
 
-    fn legacy_input(
-        get_action: impl FnOnce(&super::LegacyActions) -> openxr::sys::Action,
-        ids: &[vr::EVRButtonId],
-        touch: bool,
-    ) {
-        use fakexr::UserPath::*;
+    /// A session with both controllers using `profile` and connected, with legacy input set up.
+    fn legacy_fixture(profile: &dyn InteractionProfile) -> Fixture {
         let mut f = Fixture::new();
         f.input.openxr.restart_session();
 
-        f.set_interaction_profile(&Knuckles, LeftHand);
-        f.set_interaction_profile(&Knuckles, RightHand);
+        f.set_interaction_profile(profile, UserPath::LeftHand);
+        f.set_interaction_profile(profile, UserPath::RightHand);
         f.input.frame_start_update();
         f.input.openxr.poll_events();
-        let action = get_action(
-            &f.input
-                .openxr
-                .session_data
-                .get()
-                .input_data
-                .get_legacy_actions()
-                .unwrap()
-                .actions,
-        );
+        f.input.frame_start_update();
+        f
+    }
 
-        let get_state = |hand: fakexr::UserPath| {
-            let mut state = vr::VRControllerState_t::default();
-            assert!(f.input.get_legacy_controller_state(
-                match hand {
-                    LeftHand => 1,
-                    RightHand => 2,
-                },
-                &mut state,
-                std::mem::size_of_val(&state) as u32
-            ));
-            state
-        };
+    fn legacy_actions<T>(f: &Fixture, get: impl FnOnce(&super::LegacyActions) -> T) -> T {
+        get(&f
+            .input
+            .openxr
+            .session_data
+            .get()
+            .input_data
+            .get_legacy_actions()
+            .unwrap()
+            .actions)
+    }
+
+    fn get_state(f: &Fixture, hand: UserPath) -> vr::VRControllerState_t {
+        let mut state = vr::VRControllerState_t::default();
+        assert!(f.input.get_legacy_controller_state(
+            match hand {
+                UserPath::LeftHand => 1,
+                UserPath::RightHand => 2,
+            },
+            &mut state,
+            std::mem::size_of_val(&state) as u32
+        ));
+        state
+    }
+
+    fn legacy_input(
+        get_action: impl FnOnce(&super::LegacyActions) -> openxr::sys::Action,
+        ids: &[vr::EVRButtonId],
+        touch: bool,
+    ) {
+        use fakexr::UserPath::*;
+        let f = legacy_fixture(&Knuckles);
+        let action = legacy_actions(&f, get_action);
+        let get_state = |hand| get_state(&f, hand);
 
         let get_event = || {
             let mut event = MyEvent::default();
@@ -661,13 +1050,278 @@ mod tests {
     }
 
     test_button!(main_xy_click, vr::EVRButtonId::Axis0, main_xy_touch);
-    test_button!(trigger_click, vr::EVRButtonId::SteamVR_Trigger);
-    test_button!(app_menu, vr::EVRButtonId::ApplicationMenu);
+    test_button!(
+        trigger_click,
+        vr::EVRButtonId::SteamVR_Trigger,
+        trigger_touch
+    );
+    test_button!(app_menu, vr::EVRButtonId::ApplicationMenu, app_menu_touch);
     test_button!(
         squeeze_click,
         vr::EVRButtonId::Grip | vr::EVRButtonId::Axis2
     );
-    test_button!(a, vr::EVRButtonId::A);
+    test_button!(a, vr::EVRButtonId::A, a_touch);
+
+    #[test]
+    fn touch_without_click() {
+        use fakexr::UserPath::*;
+        let f = legacy_fixture(&Knuckles);
+
+        let touch = legacy_actions(&f, |actions| actions.main_xy_touch.as_raw());
+        fakexr::set_action_state(touch, fakexr::ActionState::Bool(true), LeftHand);
+        f.input.frame_start_update();
+
+        let state = get_state(&f, LeftHand);
+        // Braces to copy out of the packed struct
+        assert_eq!(
+            { state.ulButtonTouched },
+            super::button_mask_from_id(vr::EVRButtonId::Axis0)
+        );
+        assert_eq!({ state.ulButtonPressed }, 0);
+    }
+
+    #[test]
+    fn grip_force_axis() {
+        use fakexr::UserPath::*;
+        let f = legacy_fixture(&Knuckles);
+        f.input.set_legacy_grip_force_axis(Some(3));
+
+        let force = legacy_actions(&f, |actions| actions.squeeze_force.as_raw());
+        fakexr::set_action_state(force, fakexr::ActionState::Float(0.5), LeftHand);
+        f.input.frame_start_update();
+
+        // Braces to copy out of the packed struct
+        let axis = { get_state(&f, LeftHand).rAxis }[3];
+        assert_eq!(axis.x, 0.5);
+    }
+
+    #[test]
+    fn grip_synthesized_from_squeeze() {
+        use fakexr::UserPath::*;
+        let f = legacy_fixture(&Touch);
+        f.input.set_legacy_grip_threshold(0.6);
+
+        let squeeze = legacy_actions(&f, |actions| actions.squeeze.as_raw());
+        let get_pressed = |value| {
+            fakexr::set_action_state(squeeze, fakexr::ActionState::Float(value), LeftHand);
+            f.input.frame_start_update();
+            get_state(&f, LeftHand).ulButtonPressed
+        };
+
+        let grip = super::button_mask_from_id(vr::EVRButtonId::Grip);
+        assert_eq!(get_pressed(0.59) & grip, 0);
+        assert_eq!(get_pressed(0.6) & grip, grip);
+        assert_eq!(get_pressed(0.2) & grip, 0);
+    }
+
+    #[test]
+    fn trigger_click_synthesized_from_value() {
+        use fakexr::UserPath::*;
+        let f = legacy_fixture(&Touch);
+        f.input.set_legacy_trigger_threshold(0.5);
+
+        let trigger = legacy_actions(&f, |actions| actions.trigger.as_raw());
+        let get_pressed = |value| {
+            fakexr::set_action_state(trigger, fakexr::ActionState::Float(value), LeftHand);
+            f.input.frame_start_update();
+            get_state(&f, LeftHand).ulButtonPressed
+        };
+
+        let trigger = super::button_mask_from_id(vr::EVRButtonId::SteamVR_Trigger);
+        assert_eq!(get_pressed(0.49) & trigger, 0);
+        assert_eq!(get_pressed(0.5) & trigger, trigger);
+        assert_eq!(get_pressed(0.9) & trigger, trigger);
+        assert_eq!(get_pressed(0.1) & trigger, 0);
+    }
+
+    /// The left controller's main axis, as reported by GetControllerState.
+    fn main_axis(f: &Fixture) -> (f32, f32) {
+        // Braces to copy out of the packed struct
+        let axis = { get_state(f, UserPath::LeftHand).rAxis }[0];
+        (axis.x, axis.y)
+    }
+
+    #[test]
+    fn trackpad_position_reported_on_touch() {
+        use fakexr::UserPath::*;
+        let f = legacy_fixture(&ViveWands);
+
+        let (main_xy, main_xy_touch) = legacy_actions(&f, |actions| {
+            (actions.main_xy.as_raw(), actions.main_xy_touch.as_raw())
+        });
+
+        fakexr::set_action_state(main_xy, fakexr::ActionState::Vector2(0.5, 0.5), LeftHand);
+        fakexr::set_action_state(main_xy_touch, fakexr::ActionState::Bool(true), LeftHand);
+        f.input.frame_start_update();
+        assert_eq!(main_axis(&f), (0.5, 0.5));
+
+        fakexr::set_action_state(main_xy_touch, fakexr::ActionState::Bool(false), LeftHand);
+        f.input.frame_start_update();
+        assert_eq!(main_axis(&f), (0.0, 0.0));
+    }
+
+    #[test]
+    fn thumbstick_trackpad_emulation() {
+        use fakexr::UserPath::*;
+        let f = legacy_fixture(&Knuckles);
+        f.input.set_trackpad_emulation(true);
+
+        assert_eq!(
+            f.input.get_controller_int_tracked_property(
+                crate::openxr_data::Hand::Left,
+                vr::ETrackedDeviceProperty::Axis0Type_Int32
+            ),
+            Some(vr::EVRControllerAxisType::TrackPad as i32)
+        );
+
+        let (main_xy, main_xy_touch) = legacy_actions(&f, |actions| {
+            (actions.main_xy.as_raw(), actions.main_xy_touch.as_raw())
+        });
+
+        // Moving the thumbstick moves the trackpad, as long as it's touched.
+        fakexr::set_action_state(main_xy, fakexr::ActionState::Vector2(0.5, -0.5), LeftHand);
+        fakexr::set_action_state(main_xy_touch, fakexr::ActionState::Bool(true), LeftHand);
+        f.input.frame_start_update();
+        assert_eq!(main_axis(&f), (0.5, -0.5));
+
+        fakexr::set_action_state(main_xy_touch, fakexr::ActionState::Bool(false), LeftHand);
+        f.input.frame_start_update();
+        assert_eq!(main_axis(&f), (0.0, 0.0));
+    }
+
+    #[test]
+    fn trackpad_quadrant_buttons() {
+        use fakexr::UserPath::*;
+        let f = legacy_fixture(&ViveWands);
+        f.input.set_trackpad_buttons(true);
+
+        let a = super::button_mask_from_id(vr::EVRButtonId::A);
+        let menu = super::button_mask_from_id(vr::EVRButtonId::ApplicationMenu);
+        let axis0 = super::button_mask_from_id(vr::EVRButtonId::Axis0);
+        assert_eq!(
+            f.input
+                .get_controller_uint_tracked_property(
+                    crate::openxr_data::Hand::Left,
+                    vr::ETrackedDeviceProperty::SupportedButtons_Uint64
+                )
+                .unwrap()
+                & a,
+            a
+        );
+
+        let (main_xy, main_xy_click) = legacy_actions(&f, |actions| {
+            (actions.main_xy.as_raw(), actions.main_xy_click.as_raw())
+        });
+        let get_pressed = |x, y| {
+            fakexr::set_action_state(main_xy, fakexr::ActionState::Vector2(x, y), LeftHand);
+            fakexr::set_action_state(main_xy_click, fakexr::ActionState::Bool(true), LeftHand);
+            f.input.frame_start_update();
+            get_state(&f, LeftHand).ulButtonPressed & (a | menu | axis0)
+        };
+
+        assert_eq!(get_pressed(0.0, 0.8), menu, "top");
+        assert_eq!(get_pressed(0.1, -0.8), a, "bottom");
+        assert_eq!(get_pressed(-0.8, 0.1), axis0, "left");
+        assert_eq!(get_pressed(0.8, 0.0), axis0, "right");
+
+        fakexr::set_action_state(main_xy_click, fakexr::ActionState::Bool(false), LeftHand);
+        f.input.frame_start_update();
+        assert_eq!(
+            get_state(&f, LeftHand).ulButtonPressed & (a | menu | axis0),
+            0
+        );
+    }
+
+    #[test]
+    fn untracked_controller_state_with_pose() {
+        let f = legacy_fixture(&ViveWands);
+
+        // No grip pose has been set, so the controller can't be located.
+        let mut state = vr::VRControllerState_t::default();
+        let mut pose = vr::TrackedDevicePose_t {
+            bPoseIsValid: true,
+            bDeviceIsConnected: true,
+            ..Default::default()
+        };
+        assert!(f.input.get_legacy_controller_state_with_pose(
+            1,
+            &mut state,
+            std::mem::size_of_val(&state) as u32,
+            vr::ETrackingUniverseOrigin::Seated,
+            &mut pose,
+        ));
+        assert!(!pose.bPoseIsValid);
+        assert!(!pose.bDeviceIsConnected);
+    }
+
+    #[test]
+    fn wmr_trackpad_and_thumbstick_clicks() {
+        use fakexr::UserPath::*;
+        let f = legacy_fixture(&HolographicController);
+
+        let (main_xy_click, secondary_xy, secondary_xy_click) = legacy_actions(&f, |actions| {
+            (
+                actions.main_xy_click.as_raw(),
+                actions.secondary_xy.as_raw(),
+                actions.secondary_xy_click.as_raw(),
+            )
+        });
+        let axis0 = super::button_mask_from_id(vr::EVRButtonId::Axis0);
+        let axis2 = super::button_mask_from_id(vr::EVRButtonId::Axis2);
+
+        fakexr::set_action_state(main_xy_click, fakexr::ActionState::Bool(true), LeftHand);
+        f.input.frame_start_update();
+        let state = get_state(&f, LeftHand);
+        assert_eq!(state.ulButtonPressed & (axis0 | axis2), axis0);
+
+        fakexr::set_action_state(main_xy_click, fakexr::ActionState::Bool(false), LeftHand);
+        fakexr::set_action_state(
+            secondary_xy_click,
+            fakexr::ActionState::Bool(true),
+            LeftHand,
+        );
+        fakexr::set_action_state(
+            secondary_xy,
+            fakexr::ActionState::Vector2(0.0, 1.0),
+            LeftHand,
+        );
+        f.input.frame_start_update();
+        let state = get_state(&f, LeftHand);
+        assert_eq!(state.ulButtonPressed & (axis0 | axis2), axis2);
+        // Braces to copy out of the packed struct
+        let axis = { state.rAxis }[2];
+        assert_eq!((axis.x, axis.y), (0.0, 1.0));
+    }
+
+    #[test]
+    fn fixed_input_rate() {
+        let f = legacy_fixture(&SimpleController);
+
+        const RATE: f32 = 20.0;
+        f.input.set_input_rate(Some(RATE));
+        let packet_num = || {
+            f.input
+                .legacy_state
+                .packet_num
+                .load(std::sync::atomic::Ordering::Relaxed)
+        };
+        let start_packet = packet_num();
+        let start = std::time::Instant::now();
+        let mut frames = 0;
+        while start.elapsed() < std::time::Duration::from_millis(500) {
+            f.input.frame_start_update();
+            frames += 1;
+            std::thread::sleep(std::time::Duration::from_millis(1));
+        }
+
+        let syncs = packet_num() - start_packet;
+        let expected = (start.elapsed().as_secs_f32() * RATE).ceil() as u32;
+        assert!(
+            syncs <= expected + 1 && syncs + 2 >= expected,
+            "expected about {expected} syncs, got {syncs} ({frames} frames)"
+        );
+        assert!(frames > syncs * 2, "{frames} frames, {syncs} syncs");
+    }
 
     #[test]
     fn no_legacy_input_with_manifest() {
@@ -819,4 +1473,62 @@ mod tests {
             fakexr::UserPath::RightHand
         ));
     }
+
+    #[test]
+    fn default_manifest_alongside_legacy_input() {
+        use fakexr::UserPath::*;
+        let mut f = Fixture::new();
+        f.input
+            .default_manifest_path
+            .set(Some(
+                concat!(
+                    env!("CARGO_MANIFEST_DIR"),
+                    "/src/input/default_actions/actions.json"
+                )
+                .into(),
+            ))
+            .unwrap();
+        f.input.openxr.restart_session();
+
+        f.set_interaction_profile(&Knuckles, LeftHand);
+        f.input.frame_start_update(); // load the default manifest and legacy actions
+        f.input.openxr.poll_events();
+
+        let legacy_trigger = f
+            .input
+            .openxr
+            .session_data
+            .get()
+            .input_data
+            .get_legacy_actions()
+            .expect("Legacy actions should be loaded with the default manifest")
+            .actions
+            .trigger_click
+            .as_raw();
+        let set = f.get_action_set_handle(c"/actions/default");
+        let trigger_click = f.get_action_handle(c"/actions/default/in/trigger_click");
+        fakexr::set_action_state(legacy_trigger, fakexr::ActionState::Bool(true), LeftHand);
+        fakexr::set_action_state(
+            f.get_action::<bool>(trigger_click),
+            fakexr::ActionState::Bool(true),
+            LeftHand,
+        );
+
+        f.sync(vr::VRActiveActionSet_t {
+            ulActionSet: set,
+            ..Default::default()
+        });
+        let state = f.get_bool_state(trigger_click).unwrap();
+        assert!(state.bActive);
+        assert!(state.bState);
+
+        let mut state = vr::VRControllerState_t::default();
+        assert!(f.input.get_legacy_controller_state(
+            1,
+            &mut state,
+            std::mem::size_of_val(&state) as u32
+        ));
+        let trigger = super::button_mask_from_id(vr::EVRButtonId::SteamVR_Trigger);
+        assert_eq!(state.ulButtonPressed & trigger, trigger);
+    }
 }
@@ -0,0 +1,82 @@
+use log::{error, info};
+use openvr as vr;
+use openxr as xr;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// Writes device poses to a CSV file each frame, for debugging tracking/calibration issues.
+/// Enabled by setting XRIZER_POSE_LOG to the path of the file to write.
+pub(super) struct PoseLog {
+    writer: BufWriter<File>,
+}
+
+impl PoseLog {
+    pub const HEADER: &str = "frame,device,valid,connected,tracking_result,\
+        pos_x,pos_y,pos_z,rot_x,rot_y,rot_z,rot_w,\
+        vel_x,vel_y,vel_z,ang_vel_x,ang_vel_y,ang_vel_z";
+
+    pub fn from_env() -> Option<Self> {
+        let path = std::env::var_os("XRIZER_POSE_LOG")?;
+        match Self::new(&path) {
+            Ok(log) => {
+                info!("Logging device poses to {path:?}");
+                Some(log)
+            }
+            Err(e) => {
+                error!("Failed to create pose log at {path:?}: {e}");
+                None
+            }
+        }
+    }
+
+    pub fn new(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writeln!(writer, "{}", Self::HEADER)?;
+        Ok(Self { writer })
+    }
+
+    pub fn log_poses<'a>(
+        &mut self,
+        frame: u64,
+        poses: impl IntoIterator<Item = (vr::TrackedDeviceIndex_t, &'a vr::TrackedDevicePose_t)>,
+    ) {
+        let result = poses
+            .into_iter()
+            .try_for_each(|(index, pose)| self.write_row(frame, index, pose))
+            .and_then(|_| self.writer.flush());
+
+        if let Err(e) = result {
+            crate::warn_once!("Failed to write to pose log: {}", e);
+        }
+    }
+
+    fn write_row(
+        &mut self,
+        frame: u64,
+        index: vr::TrackedDeviceIndex_t,
+        pose: &vr::TrackedDevicePose_t,
+    ) -> std::io::Result<()> {
+        let xr::Posef {
+            position: pos,
+            orientation: rot,
+        } = pose.mDeviceToAbsoluteTracking.into();
+        let [vx, vy, vz] = pose.vVelocity.v;
+        let [ax, ay, az] = pose.vAngularVelocity.v;
+
+        writeln!(
+            self.writer,
+            "{frame},{index},{},{},{:?},{},{},{},{},{},{},{},{vx},{vy},{vz},{ax},{ay},{az}",
+            pose.bPoseIsValid,
+            pose.bDeviceIsConnected,
+            pose.eTrackingResult,
+            pos.x,
+            pos.y,
+            pos.z,
+            rot.x,
+            rot.y,
+            rot.z,
+            rot.w,
+        )
+    }
+}
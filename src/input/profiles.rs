@@ -1,5 +1,7 @@
+pub mod holographic_controller;
 pub mod knuckles;
 pub mod oculus_touch;
+pub mod oculus_touch_plus;
 pub mod simple_controller;
 pub mod vive_controller;
 
@@ -8,8 +10,11 @@ use super::{
 };
 use crate::openxr_data::Hand;
 use glam::Mat4;
+use holographic_controller::HolographicController;
 use knuckles::Knuckles;
 use oculus_touch::Touch;
+use oculus_touch_plus::TouchPlus;
+use openvr as vr;
 use openxr as xr;
 use simple_controller::SimpleController;
 use std::ffi::CStr;
@@ -26,6 +31,10 @@ pub trait InteractionProfile: Sync + Send {
     /// Can be extracted from SteamVR rendermodel files, it is the inverse of the "grip" or "openxr_grip" value
     fn offset_grip_pose(&self, _: Hand) -> Mat4;
     fn skeletal_input_bindings(&self, string_to_path: &dyn StringToPath) -> SkeletalInputBindings;
+    /// Profiles added by an extension can only be used if it's enabled.
+    fn is_supported(&self, _: &xr::ExtensionSet) -> bool {
+        true
+    }
 }
 
 pub enum Property<T> {
@@ -45,11 +54,22 @@ impl<T> Property<T> {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum MainAxisType {
     Thumbstick,
     Trackpad,
 }
 
+impl MainAxisType {
+    /// The axis type reported to games for the main axis (Prop_Axis0Type_Int32).
+    pub fn axis_type(self) -> vr::EVRControllerAxisType {
+        match self {
+            Self::Thumbstick => vr::EVRControllerAxisType::Joystick,
+            Self::Trackpad => vr::EVRControllerAxisType::TrackPad,
+        }
+    }
+}
+
 pub struct ProfileProperties {
     /// Corresponds to Prop_ModelNumber_String
     /// Can be pulled from a SteamVR System Report
@@ -61,6 +81,8 @@ pub struct ProfileProperties {
     /// Can be found in SteamVR under resources/rendermodels (some are in driver subdirs)
     pub render_model_name: Property<&'static CStr>,
     pub main_axis: MainAxisType,
+    /// A second axis reported as Axis2, for controllers with both a trackpad and a thumbstick
+    pub secondary_axis: Option<MainAxisType>,
     /// Corresponds to Prop_RegisteredDeviceType_String
     pub registered_device_type: Property<&'static CStr>,
     /// Corresponds to Prop_SerialNumber_String
@@ -72,6 +94,49 @@ pub struct ProfileProperties {
     /// Corresponds to Prop_SupportedButtons_Uint64
     /// Can be pulled from a SteamVR System Report
     pub legacy_buttons_mask: u64,
+    /// Corresponds to the Prop_NamedIconPathDevice*_String properties
+    pub icons: Property<IconPaths>,
+}
+
+/// Can be found in SteamVR under a driver's resources/icons directory
+pub struct IconPaths {
+    pub off: &'static CStr,
+    pub searching: &'static CStr,
+    pub searching_alert: &'static CStr,
+    pub ready: &'static CStr,
+    pub ready_alert: &'static CStr,
+    pub not_ready: &'static CStr,
+    pub standby: &'static CStr,
+    pub alert_low: &'static CStr,
+}
+
+impl IconPaths {
+    /// The Vive wand's icons, used for controllers that don't have any of their own.
+    pub const VIVE: Self = Self {
+        off: c"{htc}/icons/controller_status_off.png",
+        searching: c"{htc}/icons/controller_status_searching.gif",
+        searching_alert: c"{htc}/icons/controller_status_searching_alert.gif",
+        ready: c"{htc}/icons/controller_status_ready.png",
+        ready_alert: c"{htc}/icons/controller_status_ready_alert.png",
+        not_ready: c"{htc}/icons/controller_status_error.png",
+        standby: c"{htc}/icons/controller_status_standby.png",
+        alert_low: c"{htc}/icons/controller_status_ready_low.png",
+    };
+
+    pub fn get(&self, property: vr::ETrackedDeviceProperty) -> Option<&'static CStr> {
+        use vr::ETrackedDeviceProperty::*;
+        match property {
+            NamedIconPathDeviceOff_String => Some(self.off),
+            NamedIconPathDeviceSearching_String => Some(self.searching),
+            NamedIconPathDeviceSearchingAlert_String => Some(self.searching_alert),
+            NamedIconPathDeviceReady_String => Some(self.ready),
+            NamedIconPathDeviceReadyAlert_String => Some(self.ready_alert),
+            NamedIconPathDeviceNotReady_String => Some(self.not_ready),
+            NamedIconPathDeviceStandby_String => Some(self.standby),
+            NamedIconPathDeviceAlertLow_String => Some(self.alert_low),
+            _ => None,
+        }
+    }
 }
 
 pub(super) struct PathTranslation {
@@ -112,6 +177,8 @@ impl Profiles {
                 (ControllerType::ViveController, &ViveWands),
                 (ControllerType::Knuckles, &Knuckles),
                 (ControllerType::OculusTouch, &Touch),
+                (ControllerType::OculusTouch, &TouchPlus),
+                (ControllerType::ViveController, &HolographicController),
                 (ControllerType::ViveController, &SimpleController),
             ],
         };
@@ -0,0 +1,190 @@
+use super::{
+    IconPaths, InteractionProfile, MainAxisType, PathTranslation, ProfileProperties, Property,
+    SkeletalInputBindings, StringToPath,
+};
+use crate::button_mask_from_ids;
+use crate::input::legacy::{self, button_mask_from_id, LegacyBindings};
+use crate::openxr_data::Hand;
+use glam::Mat4;
+use openvr::EVRButtonId::{ApplicationMenu, Axis0, Axis1, Axis2, Grip, System};
+
+pub struct HolographicController;
+
+impl InteractionProfile for HolographicController {
+    fn properties(&self) -> &'static ProfileProperties {
+        static DEVICE_PROPERTIES: ProfileProperties = ProfileProperties {
+            model: Property::BothHands(c"WindowsMR: 0x045E/0x065B/0/2"),
+            openvr_controller_type: c"holographic_controller",
+            render_model_name: Property::PerHand {
+                left: c"{holographic}/rendermodels/holographic_controller_left",
+                right: c"{holographic}/rendermodels/holographic_controller_right",
+            },
+            main_axis: MainAxisType::Trackpad,
+            secondary_axis: Some(MainAxisType::Thumbstick),
+            registered_device_type: Property::PerHand {
+                left: c"holographic/MRSOURCE0",
+                right: c"holographic/MRSOURCE1",
+            },
+            serial_number: Property::PerHand {
+                left: c"MRSOURCE0",
+                right: c"MRSOURCE1",
+            },
+            tracking_system_name: c"holographic",
+            manufacturer_name: c"WindowsMR",
+            // Matches SteamVR's WMR driver: the trackpad is Axis0, the thumbstick is Axis2.
+            legacy_buttons_mask: button_mask_from_ids!(
+                System,
+                ApplicationMenu,
+                Grip,
+                Axis0,
+                Axis1,
+                Axis2
+            ),
+            icons: Property::BothHands(IconPaths::VIVE),
+        };
+        &DEVICE_PROPERTIES
+    }
+    fn profile_path(&self) -> &'static str {
+        "/interaction_profiles/microsoft/motion_controller"
+    }
+    fn translate_map(&self) -> &'static [PathTranslation] {
+        &[
+            PathTranslation {
+                from: "grip",
+                to: "squeeze",
+                stop: true,
+            },
+            PathTranslation {
+                from: "trigger/pull",
+                to: "trigger/value",
+                stop: true,
+            },
+            PathTranslation {
+                from: "trigger/click",
+                to: "trigger/value",
+                stop: true,
+            },
+            PathTranslation {
+                from: "application_menu",
+                to: "menu",
+                stop: true,
+            },
+        ]
+    }
+
+    fn legal_paths(&self) -> Box<[String]> {
+        [
+            "input/menu/click",
+            "input/squeeze/click",
+            "input/trigger/value",
+            "input/thumbstick",
+            "input/thumbstick/x",
+            "input/thumbstick/y",
+            "input/thumbstick/click",
+            "input/trackpad",
+            "input/trackpad/x",
+            "input/trackpad/y",
+            "input/trackpad/click",
+            "input/trackpad/touch",
+            "input/grip/pose",
+            "input/aim/pose",
+            "output/haptic",
+        ]
+        .iter()
+        .flat_map(|s| {
+            [
+                format!("/user/hand/left/{s}"),
+                format!("/user/hand/right/{s}"),
+            ]
+        })
+        .collect()
+    }
+
+    fn legacy_bindings(&self, stp: &dyn StringToPath) -> LegacyBindings {
+        LegacyBindings {
+            extra: legacy::Bindings {
+                grip_pose: stp.leftright("input/grip/pose"),
+                aim_pose: stp.leftright("input/aim/pose"),
+            },
+            trigger: stp.leftright("input/trigger/value"),
+            // No trigger click - it's pressed past a threshold of the trigger value.
+            trigger_click: Vec::new(),
+            app_menu: stp.leftright("input/menu/click"),
+            a: vec![],
+            squeeze: stp.leftright("input/squeeze/click"),
+            squeeze_force: Vec::new(),
+            squeeze_click: stp.leftright("input/squeeze/click"),
+            main_xy: stp.leftright("input/trackpad"),
+            main_xy_click: stp.leftright("input/trackpad/click"),
+            main_xy_touch: stp.leftright("input/trackpad/touch"),
+            trigger_touch: Vec::new(),
+            app_menu_touch: Vec::new(),
+            a_touch: Vec::new(),
+            secondary_xy: stp.leftright("input/thumbstick"),
+            secondary_xy_click: stp.leftright("input/thumbstick/click"),
+            haptic: stp.leftright("output/haptic"),
+        }
+    }
+
+    fn skeletal_input_bindings(&self, stp: &dyn StringToPath) -> SkeletalInputBindings {
+        SkeletalInputBindings {
+            thumb_touch: stp
+                .leftright("input/trackpad/click")
+                .into_iter()
+                .chain(stp.leftright("input/trackpad/touch"))
+                .chain(stp.leftright("input/thumbstick/click"))
+                .collect(),
+            index_touch: stp.leftright("input/trigger/value"),
+            index_curl: stp.leftright("input/trigger/value"),
+            rest_curl: stp.leftright("input/squeeze/click"),
+        }
+    }
+
+    fn offset_grip_pose(&self, _: Hand) -> Mat4 {
+        Mat4::IDENTITY
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HolographicController, InteractionProfile};
+    use crate::input::tests::Fixture;
+    use openxr as xr;
+
+    #[test]
+    fn verify_bindings() {
+        let f = Fixture::new();
+        let path = HolographicController.profile_path();
+        f.load_actions(c"actions.json");
+        f.verify_bindings::<bool>(
+            path,
+            c"/actions/set1/in/boolact",
+            [
+                "/user/hand/left/input/squeeze/click".into(),
+                "/user/hand/right/input/squeeze/click".into(),
+                "/user/hand/left/input/menu/click".into(),
+                "/user/hand/right/input/menu/click".into(),
+                "/user/hand/left/input/trackpad/click".into(),
+                "/user/hand/left/input/trackpad/touch".into(),
+            ],
+        );
+
+        f.verify_bindings::<xr::Vector2f>(
+            path,
+            c"/actions/set1/in/vec2act",
+            [
+                "/user/hand/left/input/trackpad".into(),
+                "/user/hand/right/input/trackpad".into(),
+            ],
+        );
+
+        f.verify_bindings::<xr::Haptic>(
+            path,
+            c"/actions/set1/in/vib",
+            [
+                "/user/hand/left/output/haptic".into(),
+                "/user/hand/right/output/haptic".into(),
+            ],
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use super::{
-    InteractionProfile, MainAxisType, PathTranslation, ProfileProperties, Property,
+    IconPaths, InteractionProfile, MainAxisType, PathTranslation, ProfileProperties, Property,
     SkeletalInputBindings, StringToPath,
 };
 use crate::button_mask_from_ids;
@@ -27,6 +27,7 @@ impl InteractionProfile for Knuckles {
                 right: c"{indexcontroller}valve_controller_knu_1_0_right",
             },
             main_axis: MainAxisType::Thumbstick,
+            secondary_axis: None,
             registered_device_type: Property::PerHand {
                 left: c"valve/index_controllerLHR-FFFFFFF1",
                 right: c"valve/index_controllerLHR-FFFFFFF2",
@@ -46,6 +47,30 @@ impl InteractionProfile for Knuckles {
                 EVRButtonId::Axis1,
                 EVRButtonId::Axis2
             ),
+            icons: Property::PerHand {
+                left: IconPaths {
+                    off: c"{indexcontroller}/icons/left_controller_status_off.png",
+                    searching: c"{indexcontroller}/icons/left_controller_status_searching.gif",
+                    searching_alert:
+                        c"{indexcontroller}/icons/left_controller_status_searching_alert.gif",
+                    ready: c"{indexcontroller}/icons/left_controller_status_ready.png",
+                    ready_alert: c"{indexcontroller}/icons/left_controller_status_ready_alert.png",
+                    not_ready: c"{indexcontroller}/icons/left_controller_status_error.png",
+                    standby: c"{indexcontroller}/icons/left_controller_status_standby.png",
+                    alert_low: c"{indexcontroller}/icons/left_controller_status_ready_low.png",
+                },
+                right: IconPaths {
+                    off: c"{indexcontroller}/icons/right_controller_status_off.png",
+                    searching: c"{indexcontroller}/icons/right_controller_status_searching.gif",
+                    searching_alert:
+                        c"{indexcontroller}/icons/right_controller_status_searching_alert.gif",
+                    ready: c"{indexcontroller}/icons/right_controller_status_ready.png",
+                    ready_alert: c"{indexcontroller}/icons/right_controller_status_ready_alert.png",
+                    not_ready: c"{indexcontroller}/icons/right_controller_status_error.png",
+                    standby: c"{indexcontroller}/icons/right_controller_status_standby.png",
+                    alert_low: c"{indexcontroller}/icons/right_controller_status_ready_low.png",
+                },
+            },
         };
         &DEVICE_PROPERTIES
     }
@@ -121,16 +146,23 @@ impl InteractionProfile for Knuckles {
         LegacyBindings {
             extra: legacy::Bindings {
                 grip_pose: stp.leftright("input/grip/pose"),
+                aim_pose: stp.leftright("input/aim/pose"),
             },
             app_menu: stp.leftright("input/b/click"),
             a: stp.leftright("input/a/click"),
             trigger: stp.leftright("input/trigger/value"),
             trigger_click: stp.leftright("input/trigger/click"),
             squeeze: stp.leftright("input/squeeze/value"),
+            squeeze_force: stp.leftright("input/squeeze/force"),
             squeeze_click: stp.leftright("input/squeeze/value"),
             main_xy: stp.leftright("input/thumbstick"),
             main_xy_click: stp.leftright("input/thumbstick/click"),
             main_xy_touch: stp.leftright("input/thumbstick/touch"),
+            trigger_touch: stp.leftright("input/trigger/touch"),
+            app_menu_touch: stp.leftright("input/b/touch"),
+            a_touch: stp.leftright("input/a/touch"),
+            secondary_xy: Vec::new(),
+            secondary_xy_click: Vec::new(),
             haptic: stp.leftright("output/haptic"),
         }
     }
@@ -178,8 +210,25 @@ impl InteractionProfile for Knuckles {
 mod tests {
     use super::{InteractionProfile, Knuckles};
     use crate::input::{tests::Fixture, ActionData};
+    use crate::openxr_data::Hand;
+    use glam::{Mat4, Vec3};
     use openxr as xr;
 
+    #[test]
+    fn grip_offsets_are_mirrored() {
+        let left = Knuckles.offset_grip_pose(Hand::Left);
+        let right = Knuckles.offset_grip_pose(Hand::Right);
+        assert!(!left.abs_diff_eq(Mat4::IDENTITY, 0.001));
+
+        // Reflect the right hand offset across the YZ plane.
+        let mirror = Mat4::from_scale(Vec3::new(-1.0, 1.0, 1.0));
+        let mirrored = mirror * right * mirror;
+        assert!(
+            left.abs_diff_eq(mirrored, 0.0001),
+            "left: {left:?}\nmirrored right: {mirrored:?}"
+        );
+    }
+
     #[test]
     fn verify_bindings() {
         let f = Fixture::new();
@@ -262,4 +311,22 @@ mod tests {
             ],
         );
     }
+
+    #[test]
+    fn separate_trackpad_and_thumbstick_actions() {
+        let f = Fixture::new();
+        f.load_actions(c"actions_trackpad_thumbstick.json");
+
+        let path = Knuckles.profile_path();
+        f.verify_bindings::<xr::Vector2f>(
+            path,
+            c"/actions/set1/in/move",
+            ["/user/hand/left/input/trackpad".into()],
+        );
+        f.verify_bindings::<xr::Vector2f>(
+            path,
+            c"/actions/set1/in/turn",
+            ["/user/hand/left/input/thumbstick".into()],
+        );
+    }
 }
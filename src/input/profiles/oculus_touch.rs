@@ -1,5 +1,5 @@
 use super::{
-    InteractionProfile, MainAxisType, PathTranslation, ProfileProperties, Property,
+    IconPaths, InteractionProfile, MainAxisType, PathTranslation, ProfileProperties, Property,
     SkeletalInputBindings, StringToPath,
 };
 use crate::button_mask_from_ids;
@@ -33,6 +33,7 @@ impl InteractionProfile for Touch {
             tracking_system_name: c"oculus",
             manufacturer_name: c"Oculus",
             main_axis: MainAxisType::Thumbstick,
+            secondary_axis: None,
             legacy_buttons_mask: button_mask_from_ids!(
                 System,
                 ApplicationMenu,
@@ -42,6 +43,7 @@ impl InteractionProfile for Touch {
                 Axis1,
                 Axis2
             ),
+            icons: Property::BothHands(IconPaths::VIVE),
         };
         &DEVICE_PROPERTIES
     }
@@ -92,10 +94,13 @@ impl InteractionProfile for Touch {
         LegacyBindings {
             extra: legacy::Bindings {
                 grip_pose: stp.leftright("input/grip/pose"),
+                aim_pose: stp.leftright("input/aim/pose"),
             },
             trigger: stp.leftright("input/trigger/value"),
-            trigger_click: stp.leftright("input/trigger/value"),
+            // No trigger click - it's pressed past a threshold of the trigger value.
+            trigger_click: Vec::new(),
             app_menu: vec![
+                stp("/user/hand/left/input/menu/click"),
                 stp("/user/hand/left/input/y/click"),
                 stp("/user/hand/right/input/b/click"),
             ],
@@ -103,11 +108,24 @@ impl InteractionProfile for Touch {
                 stp("/user/hand/left/input/x/click"),
                 stp("/user/hand/right/input/a/click"),
             ],
-            squeeze_click: stp.leftright("input/squeeze/value"),
+            // No grip button - the legacy grip is pressed past a threshold of the squeeze value.
+            squeeze_click: Vec::new(),
             squeeze: stp.leftright("input/squeeze/value"),
+            squeeze_force: Vec::new(),
             main_xy: stp.leftright("input/thumbstick"),
             main_xy_click: stp.leftright("input/thumbstick/click"),
             main_xy_touch: stp.leftright("input/thumbstick/touch"),
+            trigger_touch: stp.leftright("input/trigger/touch"),
+            app_menu_touch: vec![
+                stp("/user/hand/left/input/y/touch"),
+                stp("/user/hand/right/input/b/touch"),
+            ],
+            a_touch: vec![
+                stp("/user/hand/left/input/x/touch"),
+                stp("/user/hand/right/input/a/touch"),
+            ],
+            secondary_xy: Vec::new(),
+            secondary_xy_click: Vec::new(),
             haptic: stp.leftright("output/haptic"),
         }
     }
@@ -0,0 +1,165 @@
+use super::{
+    oculus_touch::Touch, IconPaths, InteractionProfile, MainAxisType, PathTranslation,
+    ProfileProperties, Property, SkeletalInputBindings, StringToPath,
+};
+use crate::button_mask_from_ids;
+use crate::input::legacy::{button_mask_from_id, LegacyBindings};
+use crate::openxr_data::Hand;
+use glam::Mat4;
+use openvr::EVRButtonId::{ApplicationMenu, Axis0, Axis1, Axis2, Grip, System, A};
+use openxr as xr;
+
+/// Quest 3 controllers. These lack the tracking ring, but are otherwise laid out like the Touch
+/// controllers, so the bindings are shared.
+pub struct TouchPlus;
+
+impl InteractionProfile for TouchPlus {
+    fn properties(&self) -> &'static ProfileProperties {
+        static DEVICE_PROPERTIES: ProfileProperties = ProfileProperties {
+            model: Property::PerHand {
+                left: c"Meta Quest 3 (Left Controller)",
+                right: c"Meta Quest 3 (Right Controller)",
+            },
+            openvr_controller_type: c"oculus_touch",
+            render_model_name: Property::PerHand {
+                left: c"oculus_quest_plus_controller_left",
+                right: c"oculus_quest_plus_controller_right",
+            },
+            registered_device_type: Property::PerHand {
+                left: c"oculus/2G0YXC3Q2N_Controller_Left",
+                right: c"oculus/2G0YXC3Q2N_Controller_Right",
+            },
+            serial_number: Property::PerHand {
+                left: c"2G0YXC3Q2N_Controller_Left",
+                right: c"2G0YXC3Q2N_Controller_Right",
+            },
+            tracking_system_name: c"oculus",
+            manufacturer_name: c"Oculus",
+            main_axis: MainAxisType::Thumbstick,
+            secondary_axis: None,
+            legacy_buttons_mask: button_mask_from_ids!(
+                System,
+                ApplicationMenu,
+                Grip,
+                A,
+                Axis0,
+                Axis1,
+                Axis2
+            ),
+            icons: Property::BothHands(IconPaths::VIVE),
+        };
+        &DEVICE_PROPERTIES
+    }
+    fn profile_path(&self) -> &'static str {
+        "/interaction_profiles/meta/touch_controller_plus"
+    }
+    fn translate_map(&self) -> &'static [PathTranslation] {
+        Touch.translate_map()
+    }
+
+    fn legacy_bindings(&self, stp: &dyn StringToPath) -> LegacyBindings {
+        Touch.legacy_bindings(stp)
+    }
+
+    fn skeletal_input_bindings(&self, stp: &dyn StringToPath) -> SkeletalInputBindings {
+        Touch.skeletal_input_bindings(stp)
+    }
+
+    fn legal_paths(&self) -> Box<[String]> {
+        let plus_only = [
+            "input/trigger/proximity",
+            "input/trigger_curl/value",
+            "input/trigger_slide/value",
+            "input/trigger/force",
+            "input/thumb_resting_surfaces/proximity",
+        ]
+        .iter()
+        .flat_map(|p| {
+            [
+                format!("/user/hand/left/{p}"),
+                format!("/user/hand/right/{p}"),
+            ]
+        });
+
+        Touch
+            .legal_paths()
+            .into_vec()
+            .into_iter()
+            .chain(plus_only)
+            .collect()
+    }
+
+    fn offset_grip_pose(&self, hand: Hand) -> Mat4 {
+        Touch.offset_grip_pose(hand)
+    }
+
+    fn is_supported(&self, extensions: &xr::ExtensionSet) -> bool {
+        extensions.meta_touch_controller_plus
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InteractionProfile, TouchPlus};
+    use crate::input::tests::Fixture;
+    use openxr as xr;
+
+    #[test]
+    fn verify_bindings() {
+        let f = Fixture::new();
+        f.load_actions(c"actions.json");
+
+        let path = TouchPlus.profile_path();
+        f.verify_bindings::<bool>(
+            path,
+            c"/actions/set1/in/boolact",
+            [
+                "/user/hand/left/input/x/click".into(),
+                "/user/hand/left/input/y/click".into(),
+                "/user/hand/right/input/a/click".into(),
+                "/user/hand/right/input/b/click".into(),
+                "/user/hand/right/input/thumbstick/click".into(),
+                "/user/hand/right/input/thumbstick/touch".into(),
+                "/user/hand/left/input/menu/click".into(),
+            ],
+        );
+
+        f.verify_bindings::<f32>(
+            path,
+            c"/actions/set1/boolact_asfloat",
+            [
+                "/user/hand/left/input/squeeze/value".into(),
+                "/user/hand/right/input/squeeze/value".into(),
+                "/user/hand/left/input/trigger/value".into(),
+                "/user/hand/right/input/trigger/value".into(),
+            ],
+        );
+
+        f.verify_bindings::<f32>(
+            path,
+            c"/actions/set1/in/vec1act",
+            [
+                "/user/hand/left/input/trigger/value".into(),
+                "/user/hand/right/input/trigger/value".into(),
+            ],
+        );
+
+        f.verify_bindings::<xr::Vector2f>(
+            path,
+            c"/actions/set1/in/vec2act",
+            [
+                "/user/hand/left/input/thumbstick".into(),
+                "/user/hand/right/input/thumbstick".into(),
+            ],
+        );
+
+        f.verify_bindings::<xr::Haptic>(
+            path,
+            c"/actions/set1/in/vib",
+            [
+                "/user/hand/left/output/haptic".into(),
+                "/user/hand/right/output/haptic".into(),
+            ],
+        );
+    }
+}
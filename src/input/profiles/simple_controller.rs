@@ -1,5 +1,5 @@
 use super::{
-    InteractionProfile, MainAxisType, PathTranslation, ProfileProperties, Property,
+    IconPaths, InteractionProfile, MainAxisType, PathTranslation, ProfileProperties, Property,
     SkeletalInputBindings, StringToPath,
 };
 use crate::button_mask_from_ids;
@@ -17,6 +17,7 @@ impl InteractionProfile for SimpleController {
             openvr_controller_type: c"<unknown>",
             render_model_name: Property::BothHands(c"generic_controller"),
             main_axis: MainAxisType::Thumbstick,
+            secondary_axis: None,
             // TODO: These are just from the vive_controller. I'm not certain whether that's correct here
             registered_device_type: Property::PerHand {
                 left: c"htc/vive_controllerLHR-00000001",
@@ -29,6 +30,7 @@ impl InteractionProfile for SimpleController {
             tracking_system_name: c"lighthouse",
             manufacturer_name: c"HTC",
             legacy_buttons_mask: button_mask_from_ids!(System, ApplicationMenu, Grip, Axis0, Axis1),
+            icons: Property::BothHands(IconPaths::VIVE),
         };
         &DEVICE_PROPERTIES
     }
@@ -54,16 +56,23 @@ impl InteractionProfile for SimpleController {
         LegacyBindings {
             extra: Bindings {
                 grip_pose: stp.leftright("input/grip/pose"),
+                aim_pose: stp.leftright("input/aim/pose"),
             },
             trigger: stp.leftright("input/select/click"),
             trigger_click: stp.leftright("input/select/click"),
             app_menu: stp.leftright("input/menu/click"),
             a: vec![],
             squeeze: stp.leftright("input/menu/click"),
+            squeeze_force: Vec::new(),
             squeeze_click: stp.leftright("input/menu/click"),
             main_xy: vec![],
             main_xy_click: vec![],
             main_xy_touch: vec![],
+            trigger_touch: Vec::new(),
+            app_menu_touch: Vec::new(),
+            a_touch: Vec::new(),
+            secondary_xy: Vec::new(),
+            secondary_xy_click: Vec::new(),
             haptic: stp.leftright("output/haptic"),
         }
     }
@@ -99,3 +108,36 @@ impl InteractionProfile for SimpleController {
         Mat4::IDENTITY
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{InteractionProfile, SimpleController};
+    use crate::input::tests::Fixture;
+    use openxr as xr;
+
+    #[test]
+    fn verify_bindings() {
+        let f = Fixture::new();
+        let path = SimpleController.profile_path();
+        f.load_actions(c"actions.json");
+        f.verify_bindings::<bool>(
+            path,
+            c"/actions/set1/in/boolact",
+            [
+                "/user/hand/left/input/menu/click".into(),
+                "/user/hand/right/input/menu/click".into(),
+                "/user/hand/left/input/select/click".into(),
+                "/user/hand/right/input/select/click".into(),
+            ],
+        );
+
+        f.verify_bindings::<xr::Haptic>(
+            path,
+            c"/actions/set1/in/vib",
+            [
+                "/user/hand/left/output/haptic".into(),
+                "/user/hand/right/output/haptic".into(),
+            ],
+        );
+    }
+}
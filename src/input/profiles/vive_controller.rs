@@ -1,11 +1,11 @@
 use super::{
-    InteractionProfile, MainAxisType, PathTranslation, ProfileProperties, Property,
+    IconPaths, InteractionProfile, MainAxisType, PathTranslation, ProfileProperties, Property,
     SkeletalInputBindings, StringToPath,
 };
 use crate::button_mask_from_ids;
 use crate::input::legacy::{self, button_mask_from_id, LegacyBindings};
 use crate::openxr_data::Hand;
-use glam::Mat4;
+use glam::{EulerRot, Mat4, Quat, Vec3};
 use openvr::EVRButtonId::{ApplicationMenu, Axis0, Axis1, Grip, System};
 
 pub struct ViveWands;
@@ -17,6 +17,7 @@ impl InteractionProfile for ViveWands {
             openvr_controller_type: c"vive_controller",
             render_model_name: Property::BothHands(c"vr_controller_vive_1_5"),
             main_axis: MainAxisType::Trackpad,
+            secondary_axis: None,
             registered_device_type: Property::PerHand {
                 left: c"htc/vive_controllerLHR-00000001",
                 right: c"htc/vive_controllerLHR-00000002",
@@ -28,6 +29,7 @@ impl InteractionProfile for ViveWands {
             tracking_system_name: c"lighthouse",
             manufacturer_name: c"HTC",
             legacy_buttons_mask: button_mask_from_ids!(System, ApplicationMenu, Grip, Axis0, Axis1),
+            icons: Property::BothHands(IconPaths::VIVE),
         };
         &DEVICE_PROPERTIES
     }
@@ -88,16 +90,23 @@ impl InteractionProfile for ViveWands {
         LegacyBindings {
             extra: legacy::Bindings {
                 grip_pose: stp.leftright("input/grip/pose"),
+                aim_pose: stp.leftright("input/aim/pose"),
             },
             trigger: stp.leftright("input/trigger/value"),
             trigger_click: stp.leftright("input/trigger/click"),
             app_menu: stp.leftright("input/menu/click"),
             a: vec![],
             squeeze: stp.leftright("input/squeeze/click"),
+            squeeze_force: Vec::new(),
             squeeze_click: stp.leftright("input/squeeze/click"),
             main_xy: stp.leftright("input/trackpad"),
             main_xy_click: stp.leftright("input/trackpad/click"),
             main_xy_touch: stp.leftright("input/trackpad/touch"),
+            trigger_touch: Vec::new(),
+            app_menu_touch: Vec::new(),
+            a_touch: Vec::new(),
+            secondary_xy: Vec::new(),
+            secondary_xy_click: Vec::new(),
             haptic: stp.leftright("output/haptic"),
         }
     }
@@ -116,7 +125,17 @@ impl InteractionProfile for ViveWands {
     }
 
     fn offset_grip_pose(&self, _: Hand) -> Mat4 {
-        Mat4::IDENTITY
+        // The wands are symmetrical, so both hands share the same grip.
+        Mat4::from_rotation_translation(
+            Quat::from_euler(
+                EulerRot::XYZ,
+                5.037_f32.to_radians(),
+                0.0_f32.to_radians(),
+                0.0_f32.to_radians(),
+            ),
+            Vec3::new(0.0, -0.015, 0.097),
+        )
+        .inverse()
     }
 }
 
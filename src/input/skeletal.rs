@@ -14,11 +14,12 @@ use std::time::Instant;
 use HandSkeletonBone::*;
 
 impl<C: openxr_data::Compositor> Input<C> {
-    /// Returns false if hand tracking data couldn't be generated for some reason.
+    /// Falls back to the estimated skeleton if hand tracking data isn't available.
     pub(super) fn get_bones_from_hand_tracking(
         &self,
         session_data: &SessionData,
         space: vr::EVRSkeletalTransformSpace,
+        motion_range: vr::EVRSkeletalMotionRange,
         hand_tracker: &xr::HandTracker,
         hand: Hand,
         transforms: &mut [vr::VRBoneTransform_t],
@@ -30,7 +31,7 @@ impl<C: openxr_data::Compositor> Input<C> {
         let devices = self.devices.read().unwrap();
 
         let Some(controller) = devices.get_controller(hand) else {
-            self.get_estimated_bones(session_data, space, hand, transforms);
+            self.get_estimated_bones(session_data, space, motion_range, hand, transforms);
             return;
         };
 
@@ -39,12 +40,12 @@ impl<C: openxr_data::Compositor> Input<C> {
             Hand::Right => &pose_data.right_space,
         }
         .try_get_or_init_raw(&controller.interaction_profile, session_data, pose_data) else {
-            self.get_estimated_bones(session_data, space, hand, transforms);
+            self.get_estimated_bones(session_data, space, motion_range, hand, transforms);
             return;
         };
 
         let Some(joints) = raw.locate_hand_joints(hand_tracker, display_time).unwrap() else {
-            self.get_estimated_bones(session_data, space, hand, transforms);
+            self.get_estimated_bones(session_data, space, motion_range, hand, transforms);
             return;
         };
 
@@ -169,43 +170,12 @@ impl<C: openxr_data::Compositor> Input<C> {
         &self,
         session_data: &SessionData,
         space: vr::EVRSkeletalTransformSpace,
+        motion_range: vr::EVRSkeletalMotionRange,
         hand: Hand,
         transforms: &mut [vr::VRBoneTransform_t],
     ) {
         let finger_state = self.get_finger_state(session_data, hand);
-        let (open, fist) = match hand {
-            Hand::Left => (&gen::left_hand::OPENHAND, &gen::left_hand::FIST),
-            Hand::Right => (&gen::right_hand::OPENHAND, &gen::right_hand::FIST),
-        };
-
-        const fn constrain<'a, F, G>(f: F) -> F
-        where
-            F: Fn(&'a [vr::VRBoneTransform_t], f32) -> G,
-            G: Fn(usize) -> (Vec3, Quat) + 'a,
-        {
-            f
-        }
-        let bone_transform_map = constrain(|start_data: &[vr::VRBoneTransform_t], state| {
-            move |idx| {
-                let (start_pos, start_rot) = bone_transform_to_glam(start_data[idx]);
-                let (closed_pos, closed_rot) = bone_transform_to_glam(fist[idx]);
-
-                let pos = start_pos.lerp(closed_pos, state);
-                let rot = start_rot.slerp(closed_rot, state);
-
-                (pos, rot)
-            }
-        });
-
-        let bone_it = (0..HandSkeletonBone::Count as usize).map(|idx| {
-            let bone = unsafe { std::mem::transmute::<usize, HandSkeletonBone>(idx) };
-            let curl_state = finger_state.get_bone_state(bone);
-
-            let map_fn = bone_transform_map(open, curl_state);
-            map_fn(idx)
-        });
-
-        finalize_transforms(bone_it, space, transforms);
+        estimate_bones(&finger_state, hand, space, motion_range, transforms);
         *self.skeletal_tracking_level.write().unwrap() = vr::EVRSkeletalTrackingLevel::Estimated;
     }
 
@@ -306,6 +276,47 @@ impl<C: openxr_data::Compositor> Input<C> {
     }
 }
 
+/// Blends each finger from the open hand towards its closed pose by its curl. With the controller,
+/// fingers close around it (the grip limit), without it they close into a full fist.
+pub(super) fn estimate_bones(
+    finger_state: &FingerState,
+    hand: Hand,
+    space: vr::EVRSkeletalTransformSpace,
+    motion_range: vr::EVRSkeletalMotionRange,
+    transforms: &mut [vr::VRBoneTransform_t],
+) {
+    let (open, closed) = match hand {
+        Hand::Left => (
+            &gen::left_hand::OPENHAND,
+            match motion_range {
+                vr::EVRSkeletalMotionRange::WithController => &gen::left_hand::GRIPLIMIT,
+                vr::EVRSkeletalMotionRange::WithoutController => &gen::left_hand::FIST,
+            },
+        ),
+        Hand::Right => (
+            &gen::right_hand::OPENHAND,
+            match motion_range {
+                vr::EVRSkeletalMotionRange::WithController => &gen::right_hand::GRIPLIMIT,
+                vr::EVRSkeletalMotionRange::WithoutController => &gen::right_hand::FIST,
+            },
+        ),
+    };
+
+    let bone_it = HandSkeletonBone::ALL.into_iter().map(|bone| {
+        let curl_state = finger_state.get_bone_state(bone);
+
+        let (open_pos, open_rot) = bone_transform_to_glam(open[bone as usize]);
+        let (closed_pos, closed_rot) = bone_transform_to_glam(closed[bone as usize]);
+
+        (
+            open_pos.lerp(closed_pos, curl_state),
+            open_rot.slerp(closed_rot, curl_state),
+        )
+    });
+
+    finalize_transforms(bone_it, space, transforms);
+}
+
 /// trait alias
 trait PoseIterator: Iterator<Item = (Vec3, Quat)> {}
 impl<T: Iterator<Item = (Vec3, Quat)>> PoseIterator for T {}
@@ -452,6 +463,19 @@ impl FingerState {
         }
     }
 
+    /// Every finger curled by the same amount.
+    #[cfg(test)]
+    pub fn curled(curl: f32) -> FingerState {
+        FingerState {
+            index: curl,
+            middle: curl,
+            ring: curl,
+            pinky: curl,
+            thumb: curl,
+            time: Instant::now(),
+        }
+    }
+
     fn lerp(&self, target: &Self, amount: f32) -> Self {
         Self {
             index: self.index + (target.index - self.index) * amount,
@@ -536,6 +560,43 @@ pub(super) enum HandSkeletonBone {
     Count,
 }
 
+impl HandSkeletonBone {
+    /// Every bone, in skeleton order.
+    pub(super) const ALL: [HandSkeletonBone; HandSkeletonBone::Count as usize] = [
+        Root,
+        Wrist,
+        Thumb0,
+        Thumb1,
+        Thumb2,
+        Thumb3,
+        IndexFinger0,
+        IndexFinger1,
+        IndexFinger2,
+        IndexFinger3,
+        IndexFinger4,
+        MiddleFinger0,
+        MiddleFinger1,
+        MiddleFinger2,
+        MiddleFinger3,
+        MiddleFinger4,
+        RingFinger0,
+        RingFinger1,
+        RingFinger2,
+        RingFinger3,
+        RingFinger4,
+        PinkyFinger0,
+        PinkyFinger1,
+        PinkyFinger2,
+        PinkyFinger3,
+        PinkyFinger4,
+        AuxThumb,
+        AuxIndexFinger,
+        AuxMiddleFinger,
+        AuxRingFinger,
+        AuxPinkyFinger,
+    ];
+}
+
 macro_rules! skeletal_input_actions {
     ($($field:ident: $ty:ty),+$(,)?) => {
         pub struct SkeletalInputActions {
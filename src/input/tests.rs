@@ -1,9 +1,10 @@
 use super::{
+    pose_log::PoseLog,
     profiles::{
-        knuckles::Knuckles, oculus_touch::Touch, simple_controller::SimpleController,
-        vive_controller::ViveWands,
+        knuckles::Knuckles, oculus_touch::Touch, oculus_touch_plus::TouchPlus,
+        simple_controller::SimpleController, vive_controller::ViveWands, MainAxisType,
     },
-    ActionData, Input, InteractionProfile,
+    ActionData, Input, InteractionProfile, EYE_GAZE_POSE_PATH, EYE_GAZE_PROFILE_PATH,
 };
 use crate::{
     input::ActionKey,
@@ -11,13 +12,13 @@ use crate::{
     vr::{self, IVRInput010_Interface},
 };
 use fakexr::UserPath::*;
-use glam::{Mat4, Quat};
+use glam::{Mat4, Quat, Vec3};
 use openxr as xr;
 use slotmap::KeyData;
 use std::collections::HashSet;
 use std::f32::consts::FRAC_PI_4;
 use std::ffi::CStr;
-use std::sync::{Arc, Barrier};
+use std::sync::{Arc, Barrier, Mutex};
 
 static ACTIONS_JSONS_DIR: &CStr = unsafe {
     CStr::from_bytes_with_nul_unchecked(
@@ -453,6 +454,76 @@ fn input_state_flow() {
     assert!(state.bChanged);
 }
 
+#[test]
+fn haptics_respect_active_action_set() {
+    let mut f = Fixture::new();
+
+    let set1 = f.get_action_set_handle(c"/actions/set1");
+    let set2 = f.get_action_set_handle(c"/actions/set2");
+    let vib = f.get_action_handle(c"/actions/set1/in/vib");
+    let left = f.get_input_source_handle(c"/user/hand/left");
+
+    f.load_actions(c"actions.json");
+    let trigger = |f: &Fixture| {
+        assert_eq!(
+            f.input
+                .TriggerHapticVibrationAction(vib, 0.0, 0.1, 160.0, 1.0, left),
+            vr::EVRInputError::None
+        );
+        fakexr::is_haptic_activated(f.get_action::<xr::Haptic>(vib), LeftHand)
+    };
+
+    f.sync(vr::VRActiveActionSet_t {
+        ulActionSet: set2,
+        ..Default::default()
+    });
+    assert!(!trigger(&f));
+
+    f.sync(vr::VRActiveActionSet_t {
+        ulActionSet: set1,
+        ..Default::default()
+    });
+    assert!(trigger(&f));
+}
+
+#[test]
+fn haptic_parameters_reach_runtime() {
+    let mut f = Fixture::new();
+
+    let set1 = f.get_action_set_handle(c"/actions/set1");
+    let vib = f.get_action_handle(c"/actions/set1/in/vib");
+    let left = f.get_input_source_handle(c"/user/hand/left");
+
+    f.load_actions(c"actions.json");
+    f.sync(vr::VRActiveActionSet_t {
+        ulActionSet: set1,
+        ..Default::default()
+    });
+
+    let trigger = |frequency, amplitude| {
+        assert_eq!(
+            f.input
+                .TriggerHapticVibrationAction(vib, 0.0, 0.5, frequency, amplitude, left),
+            vr::EVRInputError::None
+        );
+        fakexr::last_haptic_vibration(f.raw_session()).unwrap()
+    };
+
+    let vibration = trigger(160.0, 0.25);
+    assert_eq!(vibration.frequency, 160.0);
+    assert_eq!(vibration.amplitude, 0.25);
+    assert_eq!(vibration.duration, xr::Duration::from_nanos(500_000_000));
+
+    // 0 means the default frequency
+    let vibration = trigger(0.0, 2.0);
+    assert_eq!(vibration.frequency, xr::FREQUENCY_UNSPECIFIED);
+    assert_eq!(vibration.amplitude, 1.0);
+
+    let vibration = trigger(100_000.0, -1.0);
+    assert_eq!(vibration.frequency, super::MAX_HAPTIC_FREQUENCY);
+    assert_eq!(vibration.amplitude, 0.0);
+}
+
 #[test]
 fn reload_manifest_on_session_restart() {
     let mut f = Fixture::new();
@@ -586,6 +657,283 @@ fn raw_pose_waitgetposes_and_skeletal_pose_identical() {
     );
 }
 
+#[test]
+fn controller_pose_mode() {
+    let mut f = Fixture::new();
+    f.load_actions(c"actions.json");
+    f.set_interaction_profile(&Knuckles, LeftHand);
+
+    let frame = || {
+        f.input.openxr.poll_events();
+        f.input.frame_start_update();
+    };
+    frame();
+    frame();
+
+    let aim = xr::Posef {
+        position: xr::Vector3f {
+            x: 0.1,
+            y: 0.2,
+            z: 0.3,
+        },
+        orientation: xr::Quaternionf::IDENTITY,
+    };
+    fakexr::set_grip(f.raw_session(), LeftHand, xr::Posef::IDENTITY);
+    fakexr::set_aim(f.raw_session(), LeftHand, aim);
+
+    let get_pose = |mode| -> xr::Posef {
+        f.input.set_controller_pose_mode(mode);
+        frame();
+        f.input
+            .get_controller_pose(Hand::Left, Some(vr::ETrackingUniverseOrigin::Seated))
+            .unwrap()
+            .mDeviceToAbsoluteTracking
+            .into()
+    };
+
+    // The default is offset to SteamVR's raw pose.
+    let offset = get_pose(super::ControllerPoseMode::OffsetGrip);
+    let expected = Knuckles.offset_grip_pose(Hand::Left).w_axis.truncate();
+    let actual = glam::Vec3::new(offset.position.x, offset.position.y, offset.position.z);
+    assert!(actual.abs_diff_eq(expected, 1e-5), "{actual} != {expected}");
+
+    compare_pose(
+        xr::Posef::IDENTITY,
+        get_pose(super::ControllerPoseMode::Grip),
+    );
+    compare_pose(aim, get_pose(super::ControllerPoseMode::Aim));
+}
+
+#[test]
+fn controller_offset() {
+    let mut f = Fixture::new();
+    f.load_actions(c"actions.json");
+    f.set_interaction_profile(&Knuckles, LeftHand);
+
+    let frame = || {
+        f.input.openxr.poll_events();
+        f.input.frame_start_update();
+    };
+    frame();
+    frame();
+
+    let grip_position = Vec3::new(0.1, 0.2, 0.3);
+    let grip_rotation = Quat::from_rotation_x(FRAC_PI_4);
+    fakexr::set_grip(
+        f.raw_session(),
+        LeftHand,
+        xr::Posef {
+            position: xr::Vector3f {
+                x: grip_position.x,
+                y: grip_position.y,
+                z: grip_position.z,
+            },
+            orientation: xr::Quaternionf {
+                x: grip_rotation.x,
+                y: grip_rotation.y,
+                z: grip_rotation.z,
+                w: grip_rotation.w,
+            },
+        },
+    );
+    f.input
+        .set_controller_pose_mode(super::ControllerPoseMode::Grip);
+
+    let offset_translation = Vec3::new(0.0, 0.0, -0.1);
+    let offset_rotation = Quat::from_rotation_y(FRAC_PI_4);
+    f.input.set_controller_offset(
+        Hand::Left,
+        Mat4::from_rotation_translation(offset_rotation, offset_translation),
+    );
+    frame();
+
+    let pose: xr::Posef = f
+        .input
+        .get_controller_pose(Hand::Left, Some(vr::ETrackingUniverseOrigin::Seated))
+        .unwrap()
+        .mDeviceToAbsoluteTracking
+        .into();
+    let position = Vec3::new(pose.position.x, pose.position.y, pose.position.z);
+    let rotation = Quat::from_xyzw(
+        pose.orientation.x,
+        pose.orientation.y,
+        pose.orientation.z,
+        pose.orientation.w,
+    );
+
+    // The offset is in the controller's space.
+    let expected_position = grip_position + grip_rotation * offset_translation;
+    let expected_rotation = grip_rotation * offset_rotation;
+    assert!(
+        position.abs_diff_eq(expected_position, 1e-5),
+        "{position} != {expected_position}"
+    );
+    assert!(
+        rotation.abs_diff_eq(expected_rotation, 1e-5)
+            || rotation.abs_diff_eq(-expected_rotation, 1e-5),
+        "{rotation} != {expected_rotation}"
+    );
+}
+
+#[test]
+fn partially_tracked_pose() {
+    let pose = |location_flags| {
+        vr::space_relation_to_openvr_pose(
+            xr::SpaceLocation {
+                location_flags,
+                pose: xr::Posef::IDENTITY,
+            },
+            xr::SpaceVelocity::default(),
+        )
+    };
+
+    let full =
+        pose(xr::SpaceLocationFlags::ORIENTATION_VALID | xr::SpaceLocationFlags::POSITION_VALID);
+    assert!(full.bPoseIsValid && full.bDeviceIsConnected);
+    assert_eq!(full.eTrackingResult, vr::ETrackingResult::Running_OK);
+
+    // Orientation only - still there, but out of range.
+    let partial = pose(
+        xr::SpaceLocationFlags::ORIENTATION_VALID | xr::SpaceLocationFlags::ORIENTATION_TRACKED,
+    );
+    assert!(!partial.bPoseIsValid);
+    assert!(partial.bDeviceIsConnected);
+    assert_eq!(
+        partial.eTrackingResult,
+        vr::ETrackingResult::Running_OutOfRange
+    );
+
+    let none = pose(xr::SpaceLocationFlags::EMPTY);
+    assert!(!none.bPoseIsValid && !none.bDeviceIsConnected);
+    assert_eq!(
+        none.eTrackingResult,
+        vr::ETrackingResult::Running_OutOfRange
+    );
+}
+
+#[test]
+fn predicted_pose_uses_time_offset() {
+    let mut f = Fixture::new();
+    f.load_actions(c"actions.json");
+    f.set_interaction_profile(&Knuckles, LeftHand);
+
+    let frame = || {
+        f.input.openxr.poll_events();
+        f.input.frame_start_update();
+    };
+    frame();
+    frame();
+
+    fakexr::set_grip(f.raw_session(), LeftHand, xr::Posef::IDENTITY);
+    fakexr::set_linear_velocity(
+        f.raw_session(),
+        LeftHand,
+        xr::Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: -1.0,
+        },
+    );
+
+    let get_pose = |predicted_time| {
+        let session_data = f.input.openxr.session_data.get();
+        let devices = f.input.devices.read().unwrap();
+        devices
+            .get_controller(Hand::Left)
+            .unwrap()
+            .get_pose(
+                &f.input.openxr,
+                &session_data,
+                vr::ETrackingUniverseOrigin::Seated,
+                predicted_time,
+            )
+            .unwrap()
+    };
+
+    let display_time = f.input.openxr.display_time.get();
+    let current = get_pose(None);
+    let predicted = get_pose(Some(xr::Time::from_nanos(
+        display_time.as_nanos() + xr::Duration::from_nanos(20_000_000).as_nanos(),
+    )));
+
+    let current_z = current.mDeviceToAbsoluteTracking.m[2][3];
+    let predicted_z = predicted.mDeviceToAbsoluteTracking.m[2][3];
+    assert!(
+        predicted_z < current_z,
+        "predicted pose should be further along: {predicted_z} vs {current_z}"
+    );
+}
+
+#[test]
+fn controller_velocity_in_origin_space() {
+    let mut f = Fixture::new();
+    f.load_actions(c"actions.json");
+    f.set_interaction_profile(&Knuckles, LeftHand);
+
+    let frame = || {
+        f.input.openxr.poll_events();
+        f.input.frame_start_update();
+    };
+    frame();
+    frame();
+
+    fakexr::set_grip(f.raw_session(), LeftHand, xr::Posef::IDENTITY);
+    fakexr::set_linear_velocity(
+        f.raw_session(),
+        LeftHand,
+        xr::Vector3f {
+            x: 1.0,
+            y: 0.0,
+            z: 0.0,
+        },
+    );
+    fakexr::set_angular_velocity(
+        f.raw_session(),
+        LeftHand,
+        xr::Vector3f {
+            x: 0.0,
+            y: 0.0,
+            z: 2.0,
+        },
+    );
+
+    // Turn the seated origin a quarter turn to the left, so local +x is along its +z axis.
+    let rotation = Quat::from_rotation_y(std::f32::consts::FRAC_PI_2);
+    f.input.openxr.set_seated_zero_pose(xr::Posef {
+        orientation: xr::Quaternionf {
+            x: rotation.x,
+            y: rotation.y,
+            z: rotation.z,
+            w: rotation.w,
+        },
+        position: xr::Vector3f::default(),
+    });
+
+    let session_data = f.input.openxr.session_data.get();
+    let devices = f.input.devices.read().unwrap();
+    let pose = devices
+        .get_controller(Hand::Left)
+        .unwrap()
+        .get_pose(
+            &f.input.openxr,
+            &session_data,
+            vr::ETrackingUniverseOrigin::Seated,
+            Some(f.input.openxr.display_time.get()),
+        )
+        .unwrap();
+
+    let linear = Vec3::from(pose.vVelocity.v);
+    let angular = Vec3::from(pose.vAngularVelocity.v);
+    assert!(
+        linear.abs_diff_eq(Vec3::new(0.0, 0.0, 1.0), 0.0001),
+        "unexpected linear velocity: {linear:?}"
+    );
+    assert!(
+        angular.abs_diff_eq(Vec3::new(-2.0, 0.0, 0.0), 0.0001),
+        "unexpected angular velocity: {angular:?}"
+    );
+}
+
 #[test]
 fn actions_with_bad_paths() {
     let mut f = Fixture::new();
@@ -728,6 +1076,42 @@ fn pose_action_no_restrict() {
     }
 }
 
+#[test]
+fn pose_action_bound_to_eye_gaze() {
+    let f = Fixture::new();
+    assert!(f.input.openxr.enabled_extensions.ext_eye_gaze_interaction);
+
+    let gaze = f.get_action_handle(c"/actions/set1/in/gaze");
+    f.load_actions(c"actions_eye_gaze.json");
+
+    let data = f.input.openxr.session_data.get();
+    let gaze_action = data
+        .input_data
+        .pose_data
+        .get()
+        .unwrap()
+        .gaze
+        .as_ref()
+        .unwrap();
+    let instance = &f.input.openxr.instance;
+    let eye_gaze_profile = instance.string_to_path(EYE_GAZE_PROFILE_PATH).unwrap();
+    assert_eq!(
+        fakexr::get_suggested_bindings(gaze_action.as_raw(), eye_gaze_profile),
+        vec![EYE_GAZE_POSE_PATH.to_string()]
+    );
+
+    let knuckles = instance.string_to_path(Knuckles.profile_path()).unwrap();
+    let bound = data
+        .input_data
+        .get_loaded_actions()
+        .unwrap()
+        .try_get_pose(gaze, knuckles)
+        .unwrap();
+    assert!(bound.gaze);
+    assert!(bound.left.is_none());
+    assert!(bound.right.is_none());
+}
+
 #[test]
 fn raw_pose_switch_profile() {
     let mut f = Fixture::new();
@@ -867,6 +1251,70 @@ fn cased_actions() {
     assert!(pose.pose.bPoseIsValid);
 }
 
+#[test]
+fn action_set_priority() {
+    let mut f = Fixture::new();
+    f.load_actions(c"actions_priority.json");
+    f.set_interaction_profile(&Knuckles, RightHand);
+
+    let low = f.get_action_set_handle(c"/actions/low");
+    let high = f.get_action_set_handle(c"/actions/high");
+    let fire = f.get_action_handle(c"/actions/low/in/fire");
+    let jump = f.get_action_handle(c"/actions/high/in/jump");
+    f.sync(vr::VRActiveActionSet_t {
+        ulActionSet: high,
+        ..Default::default()
+    });
+
+    fakexr::set_action_state(
+        f.get_action::<bool>(fire),
+        fakexr::ActionState::Bool(true),
+        RightHand,
+    );
+    fakexr::set_action_state(
+        f.get_action::<bool>(jump),
+        fakexr::ActionState::Bool(true),
+        RightHand,
+    );
+
+    let sync_both = |low_priority, high_priority| {
+        let mut sets = [
+            vr::VRActiveActionSet_t {
+                ulActionSet: low,
+                nPriority: low_priority,
+                ..Default::default()
+            },
+            vr::VRActiveActionSet_t {
+                ulActionSet: high,
+                nPriority: high_priority,
+                ..Default::default()
+            },
+        ];
+        assert_eq!(
+            f.input.UpdateActionState(
+                sets.as_mut_ptr(),
+                std::mem::size_of::<vr::VRActiveActionSet_t>() as u32,
+                sets.len() as u32
+            ),
+            vr::EVRInputError::None
+        );
+    };
+
+    // Both are bound to the A button, so only the higher priority action should be active.
+    sync_both(0, 1);
+    let state = f.get_bool_state(jump).unwrap();
+    assert!(state.bActive);
+    assert!(state.bState);
+    let state = f.get_bool_state(fire).unwrap();
+    assert!(!state.bActive);
+    assert!(!state.bState);
+
+    // Equal priorities don't mask anything.
+    sync_both(0, 0);
+    assert!(f.get_bool_state(jump).unwrap().bState);
+    assert!(f.get_bool_state(fire).unwrap().bState);
+}
+
 #[test]
 fn digital_action_initalize_on_failure() {
     let f = Fixture::new();
@@ -1011,6 +1459,74 @@ fn detect_controller_after_manifest_load() {
     assert!(index.is_some_and(|i| f.input.is_device_connected(i)));
 }
 
+#[test]
+fn no_connect_event_for_uninitialized_device() {
+    let f = Fixture::new();
+    f.load_actions(c"actions.json");
+    f.input.openxr.poll_events();
+    f.input.frame_start_update();
+
+    // A controller that was enumerated, but never got a profile (and thus a space).
+    let mut device = super::TrackedDevice::new(
+        super::TrackedDeviceType::Controller { hand: Hand::Left },
+        None,
+        None,
+    );
+    device.connected = true;
+    let index = f
+        .input
+        .devices
+        .write()
+        .unwrap()
+        .push_device(device)
+        .unwrap();
+
+    for _ in 0..3 {
+        let mut event = std::mem::MaybeUninit::<vr::VREvent_t>::zeroed();
+        while f.input.get_next_event(
+            std::mem::size_of::<vr::VREvent_t>() as u32,
+            event.as_mut_ptr(),
+        ) {
+            let event = unsafe { event.assume_init_ref() };
+            assert!(
+                !(event.eventType == vr::EVREventType::TrackedDeviceActivated as u32
+                    && event.trackedDeviceIndex == index),
+                "got connected event for uninitialized device"
+            );
+        }
+        f.input.frame_start_update();
+    }
+}
+
+#[test]
+fn force_connected_controllers() {
+    let f = Fixture::new();
+    f.load_actions(c"actions.json");
+    f.input.force_connect_controllers();
+    f.input.openxr.poll_events();
+    f.input.frame_start_update();
+
+    // No interaction profile has been reported for either hand.
+    let left = f.input.get_controller_device_index(Hand::Left).unwrap();
+    let right = f.input.get_controller_device_index(Hand::Right).unwrap();
+    assert!(f.input.is_device_connected(left));
+    assert!(f.input.is_device_connected(right));
+
+    let mut activated = HashSet::new();
+    let mut event = std::mem::MaybeUninit::<vr::VREvent_t>::zeroed();
+    while f.input.get_next_event(
+        std::mem::size_of::<vr::VREvent_t>() as u32,
+        event.as_mut_ptr(),
+    ) {
+        let event = unsafe { event.assume_init_ref() };
+        if event.eventType == vr::EVREventType::TrackedDeviceActivated as u32 {
+            activated.insert(event.trackedDeviceIndex);
+        }
+    }
+    assert!(activated.contains(&left), "{activated:?}");
+    assert!(activated.contains(&right), "{activated:?}");
+}
+
 #[test]
 fn empty_manifest() {
     let f = Fixture::new();
@@ -1022,24 +1538,82 @@ fn empty_manifest() {
 }
 
 #[test]
-fn load_actions_race() {
-    let mut f = Fixture::new();
+fn default_manifest_without_game_manifest() {
+    let f = Fixture::new();
+    f.input
+        .default_manifest_path
+        .set(Some(
+            concat!(
+                env!("CARGO_MANIFEST_DIR"),
+                "/src/input/default_actions/actions.json"
+            )
+            .into(),
+        ))
+        .unwrap();
+
     f.input.openxr.restart_session(); // get to real session
+    f.input.frame_start_update();
 
-    f.set_interaction_profile(&Touch, LeftHand);
-    f.set_interaction_profile(&Touch, RightHand);
+    let data = f.input.openxr.session_data.get();
+    assert!(data.input_data.get_legacy_actions().is_some());
+    assert!(data.input_data.get_loaded_actions().is_some());
+    drop(data);
 
-    let mut f = Arc::new(f);
-    f.input.frame_start_update(); // load legacy
-    f.input.openxr.poll_events();
-    let got_input = f.input.get_legacy_controller_state(
-        1,
-        &mut vr::VRControllerState_t::default(),
-        std::mem::size_of::<vr::VRControllerState_t>() as _,
+    f.verify_bindings::<f32>(
+        Knuckles.profile_path(),
+        c"/actions/default/in/trigger",
+        [
+            "/user/hand/left/input/trigger/value".into(),
+            "/user/hand/right/input/trigger/value".into(),
+        ],
     );
-    assert!(got_input);
 
-    std::thread::scope(|scope| {
+    // The game should still be able to load its own manifest afterwards.
+    f.load_actions(c"actions.json");
+    f.get_action_handle(c"/actions/set1/in/boolact");
+}
+
+#[test]
+fn reload_action_manifest() {
+    let f = Fixture::new();
+    f.load_actions(c"actions.json");
+    let boolact = f.get_action_handle(c"/actions/set1/in/boolact");
+    {
+        let data = f.input.openxr.session_data.get();
+        let loaded = data.input_data.get_loaded_actions().unwrap();
+        assert!(loaded.try_get_action(boolact).is_ok());
+    }
+
+    f.load_actions(c"actions_trackpad_thumbstick.json");
+    let move_act = f.get_action_handle(c"/actions/set1/in/move");
+    let data = f.input.openxr.session_data.get();
+    let loaded = data.input_data.get_loaded_actions().unwrap();
+    assert!(loaded.try_get_action(move_act).is_ok());
+    assert_eq!(
+        loaded.try_get_action(boolact).err(),
+        Some(vr::EVRInputError::InvalidHandle)
+    );
+}
+
+#[test]
+fn load_actions_race() {
+    let mut f = Fixture::new();
+    f.input.openxr.restart_session(); // get to real session
+
+    f.set_interaction_profile(&Touch, LeftHand);
+    f.set_interaction_profile(&Touch, RightHand);
+
+    let mut f = Arc::new(f);
+    f.input.frame_start_update(); // load legacy
+    f.input.openxr.poll_events();
+    let got_input = f.input.get_legacy_controller_state(
+        1,
+        &mut vr::VRControllerState_t::default(),
+        std::mem::size_of::<vr::VRControllerState_t>() as _,
+    );
+    assert!(got_input);
+
+    std::thread::scope(|scope| {
         let barrier = Arc::new(Barrier::new(2));
         {
             let input = f.input.clone();
@@ -1077,3 +1651,726 @@ fn load_actions_race() {
     let res = f.get_bool_state(boolact);
     assert!(res.is_ok(), "{res:?}");
 }
+
+#[test]
+fn left_and_right_hand_skeletons_are_mirrored() {
+    use super::skeletal::HandSkeletonBone::{self, *};
+
+    let f = Fixture::new();
+
+    let bones = |hand, pose| {
+        let mut transforms = [vr::VRBoneTransform_t::default(); HandSkeletonBone::Count as usize];
+        f.input.get_reference_transforms(
+            hand,
+            vr::EVRSkeletalTransformSpace::Parent,
+            pose,
+            &mut transforms,
+        );
+        transforms.map(|t| {
+            let q = t.orientation;
+            (
+                Vec3::from_slice(&t.position.v[..3]),
+                Quat::from_xyzw(q.x, q.y, q.z, q.w),
+            )
+        })
+    };
+
+    // Reflects a transform across the YZ plane.
+    let mirror = |(pos, rot): (Vec3, Quat)| {
+        (
+            Vec3::new(-pos.x, pos.y, pos.z),
+            Quat::from_xyzw(rot.x, -rot.y, -rot.z, rot.w),
+        )
+    };
+    // Metacarpals are mirrored, but their local axes are also turned around X so the rest of the
+    // finger can be expressed along -X.
+    let mirror_turned = |transform| {
+        let (pos, rot): (Vec3, Quat) = mirror(transform);
+        (pos, rot * Quat::from_rotation_x(std::f32::consts::PI))
+    };
+
+    for pose in [
+        vr::EVRSkeletalReferencePose::BindPose,
+        vr::EVRSkeletalReferencePose::OpenHand,
+        vr::EVRSkeletalReferencePose::Fist,
+        vr::EVRSkeletalReferencePose::GripLimit,
+    ] {
+        let left = bones(Hand::Left, pose);
+        let right = bones(Hand::Right, pose);
+
+        for bone in HandSkeletonBone::ALL {
+            let (right_pos, right_rot) = right[bone as usize];
+            let expected = match bone {
+                Root => (right_pos, right_rot),
+                Thumb0 | IndexFinger0 | MiddleFinger0 | RingFinger0 | PinkyFinger0 => {
+                    mirror_turned((right_pos, right_rot))
+                }
+                // The bind pose aux bones follow the metacarpal convention.
+                AuxThumb | AuxIndexFinger | AuxMiddleFinger | AuxRingFinger | AuxPinkyFinger
+                    if pose == vr::EVRSkeletalReferencePose::BindPose =>
+                {
+                    mirror_turned((right_pos, right_rot))
+                }
+                Wrist | AuxThumb | AuxIndexFinger | AuxMiddleFinger | AuxRingFinger
+                | AuxPinkyFinger => mirror((right_pos, right_rot)),
+                // Everything else points down the opposite X axis.
+                _ => (-right_pos, right_rot),
+            };
+
+            // The dumped poses are a few millimeters and degrees off from a perfect mirror.
+            let (left_pos, left_rot) = left[bone as usize];
+            let (expected_pos, expected_rot) = expected;
+            assert!(
+                left_pos.abs_diff_eq(expected_pos, 0.0025),
+                "{pose:?} bone {}: left position {left_pos} is not a mirror of right {right_pos}",
+                bone as usize
+            );
+            assert!(
+                left_rot.dot(expected_rot).abs() > 0.999,
+                "{pose:?} bone {}: left rotation {left_rot} is not a mirror of right {right_rot}",
+                bone as usize
+            );
+        }
+    }
+}
+
+#[test]
+fn skeletal_motion_range() {
+    use super::skeletal::{estimate_bones, FingerState, HandSkeletonBone};
+
+    let f = Fixture::new();
+    let bones = |transforms: &[vr::VRBoneTransform_t]| {
+        [HandSkeletonBone::Thumb3, HandSkeletonBone::IndexFinger4]
+            .map(|bone| Vec3::from(transforms[bone as usize].position.v))
+    };
+    let estimated = |curl, range| {
+        let mut transforms = [vr::VRBoneTransform_t::default(); HandSkeletonBone::Count as usize];
+        estimate_bones(
+            &FingerState::curled(curl),
+            Hand::Right,
+            vr::EVRSkeletalTransformSpace::Model,
+            range,
+            &mut transforms,
+        );
+        bones(&transforms)
+    };
+    let reference = |pose| {
+        let mut transforms = [vr::VRBoneTransform_t::default(); HandSkeletonBone::Count as usize];
+        f.input.get_reference_transforms(
+            Hand::Right,
+            vr::EVRSkeletalTransformSpace::Model,
+            pose,
+            &mut transforms,
+        );
+        bones(&transforms)
+    };
+    let assert_near = |actual: [Vec3; 2], expected: [Vec3; 2], msg: &str| {
+        assert!(
+            actual
+                .iter()
+                .zip(expected)
+                .all(|(a, e)| a.abs_diff_eq(e, 0.0001)),
+            "{msg}: {actual:?} != {expected:?}"
+        );
+    };
+
+    // Both ranges start from an open hand...
+    let open = reference(vr::EVRSkeletalReferencePose::OpenHand);
+    for range in [
+        vr::EVRSkeletalMotionRange::WithController,
+        vr::EVRSkeletalMotionRange::WithoutController,
+    ] {
+        assert_near(estimated(0.0, range), open, &format!("{range:?} open"));
+    }
+
+    // ...but only close into a fist without the controller in the way.
+    let with = estimated(1.0, vr::EVRSkeletalMotionRange::WithController);
+    let without = estimated(1.0, vr::EVRSkeletalMotionRange::WithoutController);
+    assert_near(
+        with,
+        reference(vr::EVRSkeletalReferencePose::GripLimit),
+        "with controller closed",
+    );
+    assert_near(
+        without,
+        reference(vr::EVRSkeletalReferencePose::Fist),
+        "without controller closed",
+    );
+    // The grip limit rests the thumb on the controller instead of folding it over the fingers.
+    assert!(
+        !with[0].abs_diff_eq(without[0], 0.01),
+        "thumb should differ between motion ranges: {with:?} vs {without:?}"
+    );
+}
+
+#[test]
+fn pose_log_writes_rows() {
+    let f = Fixture::new();
+    let path = std::env::temp_dir().join(format!("xrizer_pose_log_{}.csv", std::process::id()));
+    f.input
+        .pose_log
+        .set(Mutex::new(PoseLog::new(&path).unwrap()))
+        .unwrap_or_else(|_| panic!("pose log already set"));
+
+    f.input.openxr.poll_events();
+    f.input.frame_start_update();
+
+    let mut poses = [vr::TrackedDevicePose_t::default(); 1];
+    f.input.get_poses(&mut poses, None, None);
+
+    let log = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let mut lines = log.lines();
+    assert_eq!(lines.next(), Some(PoseLog::HEADER));
+    let row = lines.next().expect("pose log should have a row");
+    assert!(row.starts_with("0,0,"), "unexpected row: {row}");
+    assert_eq!(
+        row.split(',').count(),
+        PoseLog::HEADER.split(',').count(),
+        "unexpected row: {row}"
+    );
+}
+
+#[test]
+fn rapid_profile_changes_settle_on_last_profile() {
+    let mut f = Fixture::new();
+    f.load_actions(c"actions.json");
+    let set1 = f.get_action_set_handle(c"/actions/set1");
+
+    // Queue up several profile changes before any of them are handled.
+    for profile in [&Knuckles as &dyn InteractionProfile, &Touch, &ViveWands] {
+        f.set_interaction_profile(profile, LeftHand);
+        let mut active = vr::VRActiveActionSet_t {
+            ulActionSet: set1,
+            ..Default::default()
+        };
+        assert_eq!(
+            f.input.UpdateActionState(
+                &mut active,
+                std::mem::size_of::<vr::VRActiveActionSet_t>() as u32,
+                1
+            ),
+            vr::EVRInputError::None
+        );
+    }
+
+    // we need to wait two frames for the controllers to be connected.
+    for _ in 0..2 {
+        f.input.openxr.poll_events();
+        f.input.frame_start_update();
+    }
+
+    {
+        let devices = f.input.devices.read().unwrap();
+        let controller = devices.get_controller(Hand::Left).unwrap();
+        assert!(controller.connected);
+        assert_eq!(
+            controller.interaction_profile.unwrap().profile_path(),
+            ViveWands.profile_path()
+        );
+        assert_eq!(
+            controller.profile_path,
+            f.input
+                .openxr
+                .instance
+                .string_to_path(ViveWands.profile_path())
+                .unwrap()
+        );
+    }
+    assert_eq!(
+        f.input.get_controller_string_tracked_property(
+            Hand::Left,
+            vr::ETrackedDeviceProperty::ControllerType_String
+        ),
+        Some(c"vive_controller")
+    );
+    assert_eq!(
+        f.input.get_controller_main_axis(Hand::Left),
+        Some(MainAxisType::Trackpad)
+    );
+}
+
+#[test]
+fn disabled_profile_bindings_not_suggested() {
+    let f = Fixture::new();
+    f.input.disable_profile(Touch.profile_path());
+    f.load_actions(c"actions.json");
+
+    let boolact = f.get_action::<bool>(f.get_action_handle(c"/actions/set1/in/boolact"));
+    let suggested = |profile: &dyn InteractionProfile| {
+        let path = f
+            .input
+            .openxr
+            .instance
+            .string_to_path(profile.profile_path())
+            .unwrap();
+        fakexr::get_suggested_bindings(boolact, path)
+    };
+
+    assert!(suggested(&Touch).is_empty());
+    // Other profiles, even ones sharing the same bindings file, are unaffected.
+    assert!(!suggested(&TouchPlus).is_empty());
+    assert!(!suggested(&Knuckles).is_empty());
+}
+
+#[test]
+fn untranslated_path_suggested_verbatim() {
+    let suggested = |f: &Fixture| {
+        let boolact = f.get_action::<bool>(f.get_action_handle(c"/actions/set1/in/boolact"));
+        let path = f
+            .input
+            .openxr
+            .instance
+            .string_to_path(ViveWands.profile_path())
+            .unwrap();
+        fakexr::get_suggested_bindings(boolact, path)
+    };
+    let raw_path = "/user/hand/right/input/trigger/click".to_string();
+
+    // By default, the trigger click is translated to the trigger value.
+    let f = Fixture::new();
+    f.load_actions(c"actions.json");
+    assert!(!suggested(&f).contains(&raw_path));
+
+    let f = Fixture::new();
+    f.input
+        .add_untranslated_path("/user/hand/right/input/trigger");
+    f.load_actions(c"actions.json");
+    let bindings = suggested(&f);
+    assert!(bindings.contains(&raw_path), "{bindings:#?}");
+    // Paths outside of the prefix are still translated.
+    assert!(!bindings.contains(&"/user/hand/left/input/trigger/click".to_string()));
+}
+
+#[test]
+fn recenter_action_resets_seated_space() {
+    let mut f = Fixture::new();
+    let set1 = f.get_action_set_handle(c"/actions/set1");
+    let binding = "/user/hand/left/input/menu/click";
+    f.input.set_recenter_binding(binding);
+    f.load_actions(c"actions.json");
+    f.set_interaction_profile(&Touch, LeftHand);
+
+    let recenter = f
+        .input
+        .openxr
+        .session_data
+        .get()
+        .input_data
+        .pose_data
+        .get()
+        .unwrap()
+        .recenter
+        .as_raw();
+    let touch = f
+        .input
+        .openxr
+        .instance
+        .string_to_path(Touch.profile_path())
+        .unwrap();
+    assert_eq!(
+        fakexr::get_suggested_bindings(recenter, touch),
+        vec![binding.to_string()]
+    );
+
+    let seated_space = |f: &Fixture| {
+        f.input
+            .openxr
+            .session_data
+            .get()
+            .get_space_for_origin(vr::ETrackingUniverseOrigin::Seated)
+            .as_raw()
+    };
+    let sync = |f: &mut Fixture| {
+        f.sync(vr::VRActiveActionSet_t {
+            ulActionSet: set1,
+            ..Default::default()
+        });
+        f.input.frame_start_update();
+    };
+
+    let original = seated_space(&f);
+    sync(&mut f);
+    assert_eq!(seated_space(&f), original);
+
+    fakexr::set_action_state(recenter, fakexr::ActionState::Bool(true), LeftHand);
+    sync(&mut f);
+    let recentered = seated_space(&f);
+    assert_ne!(recentered, original);
+
+    // Holding the button doesn't keep recentering.
+    sync(&mut f);
+    assert_eq!(seated_space(&f), recentered);
+}
+
+#[test]
+fn knuckles_icon_paths() {
+    let mut f = Fixture::new();
+    f.load_actions(c"actions.json");
+    f.set_interaction_profile(&Knuckles, LeftHand);
+
+    // we need to wait two frames for the controllers to be connected.
+    for _ in 0..2 {
+        f.input.openxr.poll_events();
+        f.input.frame_start_update();
+    }
+
+    let ready = f
+        .input
+        .get_controller_string_tracked_property(
+            Hand::Left,
+            vr::ETrackedDeviceProperty::NamedIconPathDeviceReady_String,
+        )
+        .unwrap();
+    assert!(!ready.is_empty());
+    assert!(ready.to_str().unwrap().starts_with("{indexcontroller}"));
+}
+
+#[test]
+fn active_interaction_profile() {
+    let mut f = Fixture::new();
+    f.load_actions(c"actions.json");
+
+    let active = |hand| {
+        f.input
+            .active_interaction_profile(&f.input.openxr.session_data.get(), hand)
+            .map(|p| p.profile_path())
+    };
+    assert_eq!(active(Hand::Left), None);
+
+    f.set_interaction_profile(&Knuckles, LeftHand);
+    for _ in 0..2 {
+        f.input.openxr.poll_events();
+        f.input.frame_start_update();
+    }
+
+    assert_eq!(active(Hand::Left), Some(Knuckles.profile_path()));
+    assert_eq!(active(Hand::Right), None);
+}
+
+#[test]
+fn serial_numbers_are_unique() {
+    // Whatever controllers are connected to each hand, their serials shouldn't collide with each
+    // other or with the HMD's.
+    let properties = || {
+        super::profiles::Profiles::get()
+            .list
+            .iter()
+            .map(|(_, profile)| profile.properties())
+    };
+    let left: HashSet<&CStr> = properties()
+        .map(|p| *p.serial_number.get(Hand::Left))
+        .collect();
+    let right: HashSet<&CStr> = properties()
+        .map(|p| *p.serial_number.get(Hand::Right))
+        .collect();
+
+    assert!(left.is_disjoint(&right), "{left:?} {right:?}");
+    assert!(!left.contains(c"<unknown>") && !right.contains(c"<unknown>"));
+}
+
+#[test]
+fn main_axis_type_matches_profile() {
+    let mut f = Fixture::new();
+    f.load_actions(c"actions.json");
+    f.set_interaction_profile(&ViveWands, LeftHand);
+    f.set_interaction_profile(&Touch, RightHand);
+
+    // we need to wait two frames for the controllers to be connected.
+    for _ in 0..2 {
+        f.input.openxr.poll_events();
+        f.input.frame_start_update();
+    }
+
+    assert_eq!(
+        f.input.get_controller_main_axis(Hand::Left),
+        Some(MainAxisType::Trackpad)
+    );
+    assert_eq!(
+        f.input.get_controller_int_tracked_property(
+            Hand::Left,
+            vr::ETrackedDeviceProperty::Axis0Type_Int32
+        ),
+        Some(vr::EVRControllerAxisType::TrackPad as i32)
+    );
+
+    assert_eq!(
+        f.input.get_controller_main_axis(Hand::Right),
+        Some(MainAxisType::Thumbstick)
+    );
+    assert_eq!(
+        f.input.get_controller_int_tracked_property(
+            Hand::Right,
+            vr::ETrackedDeviceProperty::Axis0Type_Int32
+        ),
+        Some(vr::EVRControllerAxisType::Joystick as i32)
+    );
+}
+
+#[test]
+fn primary_hand_alias() {
+    let f = Fixture::new();
+    f.load_actions(c"actions_primary_hand.json");
+    f.verify_bindings::<bool>(
+        Knuckles.profile_path(),
+        c"/actions/set1/in/primary",
+        ["/user/hand/right/input/a/click".into()],
+    );
+    f.verify_bindings::<bool>(
+        Knuckles.profile_path(),
+        c"/actions/set1/in/secondary",
+        ["/user/hand/left/input/a/click".into()],
+    );
+
+    let f = Fixture::new();
+    assert_eq!(
+        f.input
+            .SetDominantHand(vr::ETrackedControllerRole::LeftHand),
+        vr::EVRInputError::None
+    );
+    f.load_actions(c"actions_primary_hand.json");
+    f.verify_bindings::<bool>(
+        Knuckles.profile_path(),
+        c"/actions/set1/in/primary",
+        ["/user/hand/left/input/a/click".into()],
+    );
+    f.verify_bindings::<bool>(
+        Knuckles.profile_path(),
+        c"/actions/set1/in/secondary",
+        ["/user/hand/right/input/a/click".into()],
+    );
+}
+
+#[test]
+fn dominant_hand_selection_priority() {
+    let mut f = Fixture::new();
+    f.load_actions(c"actions.json");
+    f.set_interaction_profile(&Knuckles, LeftHand);
+    f.set_interaction_profile(&Knuckles, RightHand);
+
+    for _ in 0..2 {
+        f.input.openxr.poll_events();
+        f.input.frame_start_update();
+    }
+
+    let priority = |hand| {
+        f.input
+            .get_controller_int_tracked_property(
+                hand,
+                vr::ETrackedDeviceProperty::ControllerHandSelectionPriority_Int32,
+            )
+            .unwrap()
+    };
+    assert!(priority(Hand::Right) > priority(Hand::Left));
+
+    assert_eq!(
+        f.input
+            .SetDominantHand(vr::ETrackedControllerRole::LeftHand),
+        vr::EVRInputError::None
+    );
+    let mut role = vr::ETrackedControllerRole::Invalid;
+    assert_eq!(f.input.GetDominantHand(&mut role), vr::EVRInputError::None);
+    assert_eq!(role, vr::ETrackedControllerRole::LeftHand);
+    assert!(priority(Hand::Left) > priority(Hand::Right));
+
+    assert_eq!(
+        f.input
+            .SetDominantHand(vr::ETrackedControllerRole::Treadmill),
+        vr::EVRInputError::InvalidParam
+    );
+}
+
+#[test]
+fn thumbstick_deadzone() {
+    let f = Fixture::new();
+    let apply = |x, y| {
+        let xr::Vector2f { x, y } = f.input.apply_thumbstick_deadzone(xr::Vector2f { x, y });
+        (x, y)
+    };
+
+    assert_eq!(apply(0.1, 0.05), (0.1, 0.05));
+
+    f.input.set_thumbstick_deadzone(0.2);
+    assert_eq!(apply(0.1, 0.05), (0.0, 0.0));
+    assert_eq!(apply(0.15, -0.15), (0.15, -0.15));
+    assert_eq!(apply(0.0, 0.9), (0.0, 0.9));
+}
+
+#[test]
+fn controller_can_power_off_property() {
+    let mut f = Fixture::new();
+    f.load_actions(c"actions.json");
+    f.set_interaction_profile(&Knuckles, LeftHand);
+
+    let prop = vr::ETrackedDeviceProperty::DeviceCanPowerOff_Bool;
+
+    // we need to wait two frames for the controller to be connected.
+    for _ in 0..2 {
+        f.input.openxr.poll_events();
+        f.input.frame_start_update();
+    }
+
+    assert_eq!(
+        f.input
+            .get_controller_bool_tracked_property(Hand::Left, prop),
+        Some(false)
+    );
+}
+
+#[test]
+fn swap_hands() {
+    let mut f = Fixture::new();
+    f.load_actions(c"actions.json");
+    f.set_interaction_profile(&Knuckles, LeftHand);
+    f.set_interaction_profile(&Knuckles, RightHand);
+
+    let pose = |x| xr::Posef {
+        position: xr::Vector3f { x, y: 0.0, z: 0.0 },
+        orientation: xr::Quaternionf::IDENTITY,
+    };
+    fakexr::set_grip(f.raw_session(), LeftHand, pose(-1.0));
+    fakexr::set_aim(f.raw_session(), LeftHand, pose(-1.0));
+    fakexr::set_grip(f.raw_session(), RightHand, pose(1.0));
+    fakexr::set_aim(f.raw_session(), RightHand, pose(1.0));
+
+    // we need to wait two frames for the controllers to be connected.
+    for _ in 0..2 {
+        f.input.openxr.poll_events();
+        f.input.frame_start_update();
+    }
+
+    let left = f.input.get_controller_device_index(Hand::Left).unwrap();
+    let right = f.input.get_controller_device_index(Hand::Right).unwrap();
+    assert_eq!(
+        f.input.get_controller_device_index_for_role(Hand::Left),
+        Some(left)
+    );
+    assert_eq!(f.input.device_index_to_role(left), Some(Hand::Left));
+
+    f.input.set_swap_hands(true);
+    let left_role_index = f
+        .input
+        .get_controller_device_index_for_role(Hand::Left)
+        .unwrap();
+    assert_eq!(left_role_index, right);
+    assert_eq!(f.input.device_index_to_role(right), Some(Hand::Left));
+    assert_eq!(f.input.device_index_to_role(left), Some(Hand::Right));
+
+    // The device reported for the left role should be the right hand's controller.
+    let left_role_pose = f
+        .input
+        .get_device_pose(left_role_index, None)
+        .expect("left role should have a pose");
+    let right_pose = f.input.get_controller_pose(Hand::Right, None).unwrap();
+    assert_eq!(
+        left_role_pose.mDeviceToAbsoluteTracking.m,
+        right_pose.mDeviceToAbsoluteTracking.m
+    );
+    assert_eq!(
+        f.input.device_index_to_hand(left_role_index),
+        Some(Hand::Right)
+    );
+}
+
+#[test]
+fn activity_level_follows_idle_timer() {
+    let mut f = Fixture::new();
+    f.load_actions(c"actions.json");
+    f.set_interaction_profile(&Knuckles, LeftHand);
+    f.set_interaction_profile(&Knuckles, RightHand);
+
+    // we need to wait two frames for the controllers to be connected.
+    for _ in 0..2 {
+        f.input.openxr.poll_events();
+        f.input.frame_start_update();
+    }
+
+    let left = f.input.get_controller_device_index(Hand::Left).unwrap();
+    let right = f.input.get_controller_device_index(Hand::Right).unwrap();
+    let mut poses = [vr::TrackedDevicePose_t::default(); 3];
+    f.input.get_poses(&mut poses, None, None);
+    assert_eq!(
+        f.input.get_device_activity_level(left),
+        Some(vr::EDeviceActivityLevel::UserInteraction)
+    );
+
+    {
+        let devices = f.input.devices.read().unwrap();
+        for index in [left, right] {
+            devices.get_device(index).unwrap().backdate_activity(
+                super::devices::IDLE_TIMEOUT + std::time::Duration::from_secs(1),
+            );
+        }
+    }
+
+    // Only the right controller moves.
+    fakexr::set_grip(
+        f.raw_session(),
+        RightHand,
+        xr::Posef {
+            position: xr::Vector3f {
+                x: 0.5,
+                y: 0.0,
+                z: 0.0,
+            },
+            orientation: xr::Quaternionf::IDENTITY,
+        },
+    );
+    f.input.frame_start_update();
+    f.input.get_poses(&mut poses, None, None);
+
+    assert_eq!(
+        f.input.get_device_activity_level(left),
+        Some(vr::EDeviceActivityLevel::Idle_Timeout)
+    );
+    assert_eq!(
+        f.input.get_device_activity_level(right),
+        Some(vr::EDeviceActivityLevel::UserInteraction)
+    );
+}
+
+#[test]
+fn pose_smoothing_lags_controller_pose() {
+    let mut f = Fixture::new();
+    f.load_actions(c"actions.json");
+    f.set_interaction_profile(&Knuckles, LeftHand);
+    f.input.set_pose_smoothing(0.5);
+
+    let set_x = |x| {
+        fakexr::set_grip(
+            f.raw_session(),
+            LeftHand,
+            xr::Posef {
+                position: xr::Vector3f { x, y: 0.0, z: 0.0 },
+                orientation: xr::Quaternionf::IDENTITY,
+            },
+        );
+    };
+    let get_x = || {
+        f.input.frame_start_update();
+        let left = f.input.get_controller_device_index(Hand::Left).unwrap();
+        let mut poses = [vr::TrackedDevicePose_t::default(); 2];
+        f.input.get_poses(&mut poses, None, None);
+        poses[left as usize].mDeviceToAbsoluteTracking.m[0][3]
+    };
+
+    set_x(0.0);
+    // we need to wait two frames for the controller to be connected.
+    f.input.openxr.poll_events();
+    f.input.frame_start_update();
+    f.input.openxr.poll_events();
+    let start = get_x();
+
+    // The controller only moves halfway towards its new position each frame.
+    set_x(1.0);
+    let x = get_x();
+    assert!((x - start - 0.5).abs() < 1e-5, "{x} (started at {start})");
+    let x = get_x();
+    assert!((x - start - 0.75).abs() < 1e-5, "{x} (started at {start})");
+
+    // Without smoothing it jumps straight there.
+    f.input.set_pose_smoothing(0.0);
+    let x = get_x();
+    assert!((x - start - 1.0).abs() < 1e-5, "{x} (started at {start})");
+}
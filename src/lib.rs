@@ -10,6 +10,7 @@ mod misc_unknown;
 mod openxr_data;
 mod overlay;
 mod overlayview;
+mod perf_hud;
 mod rendermodels;
 mod screenshots;
 mod settings;
@@ -21,18 +22,51 @@ mod error_dialog;
 use clientcore::ClientCore;
 use openvr as vr;
 use std::ffi::{c_char, c_void, CStr};
-use std::sync::OnceLock;
 use std::sync::{
     atomic::{AtomicU32, AtomicU64, Ordering},
     Arc,
 };
+use std::sync::{LazyLock, OnceLock};
 
 macro_rules! warn_unimplemented {
-    ($function:literal) => {
+    ($function:literal) => {{
+        if crate::strict_mode() {
+            panic!(
+                "{} unimplemented ({}:{}) - panicking because XRIZER_STRICT is set",
+                $function,
+                file!(),
+                line!()
+            );
+        }
         crate::warn_once!("{} unimplemented ({}:{})", $function, file!(), line!());
-    };
+    }};
 }
 use warn_unimplemented;
+
+/// With XRIZER_STRICT=1, unimplemented functions panic instead of returning a default, so it's
+/// obvious what a game is missing.
+fn strict_mode() -> bool {
+    static STRICT_MODE: LazyLock<bool> =
+        LazyLock::new(|| std::env::var("XRIZER_STRICT").is_ok_and(|strict| strict == "1"));
+
+    #[cfg(test)]
+    if let Some(strict) = STRICT_MODE_OVERRIDE.get() {
+        return strict;
+    }
+    *STRICT_MODE
+}
+
+// Per thread, so tests running in parallel don't affect each other.
+#[cfg(test)]
+thread_local! {
+    static STRICT_MODE_OVERRIDE: std::cell::Cell<Option<bool>> = const { std::cell::Cell::new(None) };
+}
+
+#[cfg(test)]
+fn set_strict_mode(strict: bool) {
+    STRICT_MODE_OVERRIDE.set(Some(strict));
+}
+
 macro_rules! warn_once {
     ($literal:literal $(,$($tt:tt)*)?) => {{
         static ONCE: std::sync::Once = std::sync::Once::new();
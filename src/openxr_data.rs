@@ -1,17 +1,24 @@
+#[cfg(test)]
+use crate::graphics_backends::{GlData, TextureFormat};
 use crate::{
     clientcore::{Injected, Injector},
     graphics_backends::{supported_apis_enum, GraphicsBackend, VulkanData},
+    AtomicF64,
 };
+use ash::vk::Handle;
 use derive_more::Deref;
 use glam::f32::{Quat, Vec3};
-use log::{info, warn};
+use log::{debug, info, warn};
 use openvr as vr;
 use openxr as xr;
+use std::collections::VecDeque;
+use std::ffi::c_void;
 use std::mem::ManuallyDrop;
 use std::sync::{
-    atomic::{AtomicI64, Ordering},
-    RwLock,
+    atomic::{AtomicBool, AtomicI64, AtomicPtr, AtomicU64, Ordering},
+    Mutex, RwLock,
 };
+use std::time::Instant;
 
 pub trait Compositor: vr::InterfaceImpl {
     fn post_session_restart(
@@ -37,7 +44,18 @@ pub struct OpenXrData<C: Compositor> {
     pub system_id: xr::SystemId,
     pub session_data: SessionReadGuard,
     pub display_time: AtomicXrTime,
+    /// Shared frame counter, so that all of the timing APIs agree on the current frame.
+    pub frame_metrics: FrameMetrics,
     pub enabled_extensions: xr::ExtensionSet,
+    /// The Vulkan instance the current session was created with, or null if it isn't a Vulkan session.
+    vulkan_instance: AtomicPtr<c_void>,
+    /// Set when the runtime ends the session on its own - see take_quit_request
+    quit_requested: AtomicBool,
+    /// OpenVR events for reference spaces moved by the runtime - see take_space_change_event
+    space_change_events: Mutex<VecDeque<vr::EVREventType>>,
+    /// Whether the user is wearing the headset, according to XR_EXT_user_presence.
+    /// Always true if the extension isn't available.
+    user_present: AtomicBool,
 
     /// should only be externally accessed for testing
     pub(crate) input: Injected<crate::input::Input<C>>,
@@ -122,6 +140,10 @@ impl<C: Compositor> OpenXrData<C> {
         exts.khr_composition_layer_equirect2 = supported_exts.khr_composition_layer_equirect2;
         exts.khr_composition_layer_color_scale_bias =
             supported_exts.khr_composition_layer_color_scale_bias;
+        exts.khr_composition_layer_depth = supported_exts.khr_composition_layer_depth;
+        exts.ext_user_presence = supported_exts.ext_user_presence;
+        exts.meta_touch_controller_plus = supported_exts.meta_touch_controller_plus;
+        exts.ext_eye_gaze_interaction = supported_exts.ext_eye_gaze_interaction;
 
         let instance = entry
             .create_instance(
@@ -141,15 +163,16 @@ impl<C: Compositor> OpenXrData<C> {
             .system(xr::FormFactor::HEAD_MOUNTED_DISPLAY)
             .map_err(InitError::SystemCreationFailed)?;
 
-        let session_data = SessionReadGuard(RwLock::new(ManuallyDrop::new(
-            SessionData::new(
-                &instance,
-                system_id,
-                vr::ETrackingUniverseOrigin::Standing,
-                None,
-            )?
-            .0,
-        )));
+        let mut session = SessionData::new(
+            &instance,
+            system_id,
+            vr::ETrackingUniverseOrigin::Standing,
+            None,
+        )?
+        .0;
+        session.force_seated = std::env::var("XRIZER_FORCE_SEATED").is_ok_and(|s| s == "1");
+        let vulkan_instance = AtomicPtr::new(session.vulkan_instance(None));
+        let session_data = SessionReadGuard(RwLock::new(ManuallyDrop::new(session)));
 
         Ok(Self {
             _entry: entry,
@@ -157,7 +180,12 @@ impl<C: Compositor> OpenXrData<C> {
             system_id,
             session_data,
             display_time: AtomicXrTime(1.into()),
+            frame_metrics: FrameMetrics::new(),
             enabled_extensions: exts,
+            vulkan_instance,
+            quit_requested: AtomicBool::new(false),
+            space_change_events: Mutex::default(),
+            user_present: AtomicBool::new(true),
             input: injector.inject(),
             compositor: injector.inject(),
         })
@@ -165,24 +193,67 @@ impl<C: Compositor> OpenXrData<C> {
 
     pub fn poll_events(&self) {
         let data = self.session_data.get();
-        if let Some(state) = self.poll_events_impl(&data) {
+        let PolledEvents {
+            state,
+            changed_spaces,
+        } = self.poll_events_impl(&data);
+        if state.is_some() || !changed_spaces.is_empty() {
             drop(data);
-            self.session_data.0.write().unwrap().state = state;
+            let mut data = self.session_data.0.write().unwrap();
+            if let Some(state) = state {
+                data.state = state;
+            }
+            for ty in changed_spaces {
+                data.clear_space_offset(ty);
+            }
         }
     }
 
-    fn poll_events_impl(&self, session_data: &SessionData) -> Option<xr::SessionState> {
+    fn poll_events_impl(&self, session_data: &SessionData) -> PolledEvents {
         let mut buf = xr::EventDataBuffer::new();
         let mut state = None;
+        let mut changed_spaces = Vec::new();
+        let mut profile_changed = false;
         while let Some(event) = self.instance.poll_event(&mut buf).unwrap() {
             match event {
                 xr::Event::SessionStateChanged(event) => {
                     state = Some(event.state());
                     info!("OpenXR session state changed: {:?}", event.state());
+                    // If we didn't ask for the session to end, the runtime wants the app gone.
+                    if matches!(
+                        event.state(),
+                        xr::SessionState::STOPPING | xr::SessionState::EXITING
+                    ) && !session_data.exit_requested.swap(true, Ordering::Relaxed)
+                    {
+                        info!("Runtime is ending the session, asking app to quit");
+                        self.quit_requested.store(true, Ordering::Relaxed);
+                    }
                 }
+                // Runtimes can send a burst of these (e.g. while controllers are pairing), so we
+                // only check the current profiles once all of them have been drained.
                 xr::Event::InteractionProfileChanged(_) => {
-                    if let Some(input) = self.input.get() {
-                        input.interaction_profile_changed(session_data);
+                    profile_changed = true;
+                }
+                xr::Event::ReferenceSpaceChangePending(event) => {
+                    let ty = event.reference_space_type();
+                    info!("Runtime is changing reference space {ty:?}");
+                    let vr_event = match ty {
+                        xr::ReferenceSpaceType::STAGE => {
+                            vr::EVREventType::ChaperoneUniverseHasChanged
+                        }
+                        xr::ReferenceSpaceType::LOCAL => vr::EVREventType::SeatedZeroPoseReset,
+                        _ => continue,
+                    };
+                    changed_spaces.push(ty);
+                    self.space_change_events.lock().unwrap().push_back(vr_event);
+                }
+                xr::Event::UserPresenceChangedEXT(event) => {
+                    let present = event.is_user_present();
+                    info!("User presence changed: {present}");
+                    if self.user_present.swap(present, Ordering::Relaxed) != present {
+                        if let Some(input) = self.input.get() {
+                            input.user_presence_changed(present);
+                        }
                     }
                 }
                 _ => {
@@ -191,7 +262,16 @@ impl<C: Compositor> OpenXrData<C> {
             }
         }
 
-        state
+        if profile_changed {
+            if let Some(input) = self.input.get() {
+                input.interaction_profile_changed(session_data);
+            }
+        }
+
+        PolledEvents {
+            state,
+            changed_spaces,
+        }
     }
 
     pub fn restart_session(&self) {
@@ -199,6 +279,7 @@ impl<C: Compositor> OpenXrData<C> {
         self.end_session(&mut session_guard);
 
         let origin = session_guard.current_origin;
+        let force_seated = session_guard.force_seated;
         let comp = self
             .compositor
             .get()
@@ -209,9 +290,12 @@ impl<C: Compositor> OpenXrData<C> {
         // We need to destroy the old session before creating the new one.
         let _ = unsafe { ManuallyDrop::take(&mut *session_guard) };
 
-        let (session, waiter, stream) =
+        let (mut session, waiter, stream) =
             SessionData::new(&self.instance, self.system_id, origin, Some(&info))
                 .expect("Failed to initalize new session");
+        session.force_seated = force_seated;
+        self.vulkan_instance
+            .store(session.vulkan_instance(Some(&info)), Ordering::Relaxed);
 
         comp.post_session_restart(&session, waiter, stream);
 
@@ -222,10 +306,20 @@ impl<C: Compositor> OpenXrData<C> {
         *session_guard = ManuallyDrop::new(session);
     }
 
+    pub fn vulkan_instance(&self) -> Option<xr::sys::platform::VkInstance> {
+        let instance = self.vulkan_instance.load(Ordering::Relaxed);
+        (!instance.is_null()).then_some(instance as _)
+    }
+
     pub fn set_tracking_space(&self, space: vr::ETrackingUniverseOrigin) {
         self.session_data.0.write().unwrap().current_origin = space;
     }
 
+    #[cfg(test)]
+    pub fn set_force_seated(&self, enabled: bool) {
+        self.session_data.0.write().unwrap().force_seated = enabled;
+    }
+
     pub fn get_tracking_space(&self) -> vr::ETrackingUniverseOrigin {
         self.session_data.get().current_origin
     }
@@ -292,11 +386,63 @@ impl<C: Compositor> OpenXrData<C> {
         };
     }
 
+    /// Offsets the seated origin directly, as reset_tracking_space would for a rotated headset.
+    #[cfg(test)]
+    pub fn set_seated_zero_pose(&self, pose: xr::Posef) {
+        let mut guard = self.session_data.0.write().unwrap();
+        guard.local_space_adjusted = guard
+            .session
+            .create_reference_space(xr::ReferenceSpaceType::LOCAL, pose)
+            .unwrap();
+    }
+
+    /// Asks the runtime to gracefully end the current session, e.g. because the app is quitting.
+    /// Calling this multiple times is fine.
+    pub fn request_exit(&self) {
+        let session_data = self.session_data.get();
+        if !session_data.is_running() {
+            info!(
+                "No running session to exit (session state: {:?})",
+                session_data.state
+            );
+            return;
+        }
+
+        if session_data.exit_requested.swap(true, Ordering::Relaxed) {
+            debug!("Session exit already requested");
+            return;
+        }
+
+        info!("Requesting session exit");
+        if let Err(e) = session_data.session.request_exit() {
+            warn!("Failed to request session exit: {e}");
+        }
+    }
+
+    /// Returns true once after the runtime has ended the session without us asking, meaning the
+    /// app should be sent VREvent_Quit.
+    pub fn take_quit_request(&self) -> bool {
+        self.quit_requested.swap(false, Ordering::Relaxed)
+    }
+
+    /// Returns the next OpenVR event for a reference space the runtime has moved, if any.
+    pub fn take_space_change_event(&self) -> Option<vr::EVREventType> {
+        self.space_change_events.lock().unwrap().pop_front()
+    }
+
+    /// Whether the user is currently wearing the headset.
+    #[inline]
+    pub fn user_present(&self) -> bool {
+        self.user_present.load(Ordering::Relaxed)
+    }
+
     fn end_session(&self, session_data: &mut SessionData) {
-        session_data.session.request_exit().unwrap();
+        if !session_data.exit_requested.swap(true, Ordering::Relaxed) && session_data.is_running() {
+            session_data.session.request_exit().unwrap();
+        }
         let mut state = session_data.state;
         while state != xr::SessionState::STOPPING {
-            if let Some(s) = self.poll_events_impl(session_data) {
+            if let Some(s) = self.poll_events_impl(session_data).state {
                 state = s;
             }
         }
@@ -306,13 +452,19 @@ impl<C: Compositor> OpenXrData<C> {
         }
         session_data.session.end().unwrap();
         while state != xr::SessionState::EXITING {
-            if let Some(s) = self.poll_events_impl(session_data) {
+            if let Some(s) = self.poll_events_impl(session_data).state {
                 state = s;
             }
         }
     }
 }
 
+struct PolledEvents {
+    state: Option<xr::SessionState>,
+    /// Reference spaces the runtime has moved
+    changed_spaces: Vec<xr::ReferenceSpaceType>,
+}
+
 pub struct AtomicXrTime(AtomicI64);
 
 impl AtomicXrTime {
@@ -327,6 +479,60 @@ impl AtomicXrTime {
     }
 }
 
+pub struct FrameMetrics {
+    system_start: Instant,
+    index: AtomicU64,
+    time: AtomicF64,
+    /// Predicted display period of the last waited frame, in nanoseconds.
+    display_period: AtomicI64,
+}
+
+impl FrameMetrics {
+    fn new() -> Self {
+        Self {
+            system_start: Instant::now(),
+            index: 0.into(),
+            time: 0.0.into(),
+            display_period: 0.into(),
+        }
+    }
+
+    /// Should be called once per presented frame.
+    pub fn advance_frame(&self) {
+        self.index.fetch_add(1, Ordering::Relaxed);
+        self.time.store(self.system_start.elapsed().as_secs_f64());
+    }
+
+    #[inline]
+    pub fn frame_index(&self) -> u64 {
+        self.index.load(Ordering::Relaxed)
+    }
+
+    /// Seconds since startup at which the last frame was presented.
+    #[inline]
+    pub fn last_frame_time(&self) -> f64 {
+        self.time.load()
+    }
+
+    #[inline]
+    pub fn time_since_last_frame(&self) -> f64 {
+        self.system_start.elapsed().as_secs_f64() - self.last_frame_time()
+    }
+
+    #[inline]
+    pub fn set_display_period(&self, period: xr::Duration) {
+        self.display_period
+            .store(period.as_nanos(), Ordering::Relaxed);
+    }
+
+    /// The runtime's predicted display period in seconds, if a frame has been waited on yet.
+    #[inline]
+    pub fn display_period(&self) -> Option<f64> {
+        let period = self.display_period.load(Ordering::Relaxed);
+        (period > 0).then(|| period as f64 / 1_000_000_000.0)
+    }
+}
+
 pub struct SessionReadGuard(RwLock<ManuallyDrop<SessionData>>);
 impl SessionReadGuard {
     pub fn get(&self) -> std::sync::RwLockReadGuard<'_, ManuallyDrop<SessionData>> {
@@ -370,6 +576,8 @@ pub struct SessionData {
     pub session: xr::Session<xr::AnyGraphics>,
     session_graphics: GraphicalSession,
     pub state: xr::SessionState,
+    /// Set once xrRequestExitSession has been called for this session
+    exit_requested: AtomicBool,
     pub view_space: xr::Space,
     // The "reference" space is always equivalent to the reference space with an identity offset.
     // The "adjusted" space may have an offset, set by reset_tracking_space.
@@ -380,6 +588,8 @@ pub struct SessionData {
     stage_space_reference: xr::Space,
     stage_space_adjusted: xr::Space,
     pub current_origin: vr::ETrackingUniverseOrigin,
+    /// Treat standing poses as seated, for seated players in standing only games (XRIZER_FORCE_SEATED)
+    force_seated: bool,
 
     pub input_data: crate::input::InputSessionData,
     pub comp_data: crate::compositor::CompositorSessionData,
@@ -406,6 +616,17 @@ pub enum SessionCreationError {
 }
 
 impl SessionData {
+    /// Whether the session has been begun and not yet asked to stop.
+    fn is_running(&self) -> bool {
+        matches!(
+            self.state,
+            xr::SessionState::READY
+                | xr::SessionState::SYNCHRONIZED
+                | xr::SessionState::VISIBLE
+                | xr::SessionState::FOCUSED
+        )
+    }
+
     fn new(
         instance: &xr::Instance,
         system_id: xr::SystemId,
@@ -512,6 +733,7 @@ impl SessionData {
                 session,
                 session_graphics,
                 state: xr::SessionState::READY,
+                exit_requested: AtomicBool::new(false),
                 view_space,
                 local_space_reference,
                 local_space_adjusted,
@@ -521,6 +743,7 @@ impl SessionData {
                 comp_data: Default::default(),
                 overlay_data: Default::default(),
                 current_origin,
+                force_seated: false,
             },
             waiter,
             stream,
@@ -573,13 +796,59 @@ impl SessionData {
         }
     }
 
+    /// The first of the runtime's swapchain formats (in its order of preference) that OpenVR
+    /// textures can be submitted in. Returns None if none of them have an OpenVR equivalent.
+    #[cfg(test)]
+    pub fn recommended_texture_format(&self) -> Option<TextureFormat> {
+        fn first_supported<G: GraphicsBackend>(session: &Session<G::Api>) -> Option<TextureFormat> {
+            session
+                .session
+                .enumerate_swapchain_formats()
+                .inspect_err(|e| warn!("Couldn't enumerate swapchain formats: {e}"))
+                .ok()?
+                .into_iter()
+                .find_map(G::texture_format)
+        }
+
+        match &self.session_graphics {
+            GraphicalSession::Vulkan(s) => first_supported::<VulkanData>(s),
+            GraphicalSession::OpenGL(s) => first_supported::<GlData>(s),
+            #[cfg(test)]
+            GraphicalSession::Fake(s) => first_supported::<crate::compositor::FakeGraphicsData>(s),
+        }
+    }
+
     pub fn tracking_space(&self) -> &xr::Space {
         self.get_space_for_origin(self.current_origin)
     }
 
+    /// Drops any offset set by reset_tracking_space, because the runtime has moved the space.
+    fn clear_space_offset(&mut self, ty: xr::ReferenceSpaceType) {
+        let adjusted = match ty {
+            xr::ReferenceSpaceType::LOCAL => &mut self.local_space_adjusted,
+            xr::ReferenceSpaceType::STAGE => &mut self.stage_space_adjusted,
+            _ => return,
+        };
+        *adjusted = self
+            .session
+            .create_reference_space(ty, xr::Posef::IDENTITY)
+            .unwrap();
+    }
+
+    /// The origin poses are actually located in, accounting for XRIZER_FORCE_SEATED.
     #[inline]
-    pub fn get_space_for_origin(&self, origin: vr::ETrackingUniverseOrigin) -> &xr::Space {
+    fn effective_origin(&self, origin: vr::ETrackingUniverseOrigin) -> vr::ETrackingUniverseOrigin {
         match origin {
+            vr::ETrackingUniverseOrigin::Standing if self.force_seated => {
+                vr::ETrackingUniverseOrigin::Seated
+            }
+            other => other,
+        }
+    }
+
+    #[inline]
+    pub fn get_space_for_origin(&self, origin: vr::ETrackingUniverseOrigin) -> &xr::Space {
+        match self.effective_origin(origin) {
             vr::ETrackingUniverseOrigin::Seated => &self.local_space_adjusted,
             vr::ETrackingUniverseOrigin::Standing => &self.stage_space_adjusted,
             vr::ETrackingUniverseOrigin::RawAndUncalibrated => unreachable!(),
@@ -591,20 +860,48 @@ impl SessionData {
         match ty {
             xr::ReferenceSpaceType::VIEW => &self.view_space,
             xr::ReferenceSpaceType::LOCAL => &self.local_space_adjusted,
+            xr::ReferenceSpaceType::STAGE if self.force_seated => &self.local_space_adjusted,
             xr::ReferenceSpaceType::STAGE => &self.stage_space_adjusted,
             other => panic!("Unsupported reference space type: {other:?}"),
         }
     }
 
+    /// The width and depth of the play area in meters, from the STAGE bounds. Defaults to 2x2m
+    /// if the runtime doesn't know the bounds.
+    pub fn play_area_size(&self) -> (f32, f32) {
+        const DEFAULT_SIZE: (f32, f32) = (2.0, 2.0);
+        match self
+            .session
+            .reference_space_bounds_rect(xr::ReferenceSpaceType::STAGE)
+        {
+            Ok(Some(xr::Extent2Df { width, height })) if width > 0.0 && height > 0.0 => {
+                (width, height)
+            }
+            Ok(_) => DEFAULT_SIZE,
+            Err(e) => {
+                warn!("Failed to get play area bounds: {e}");
+                DEFAULT_SIZE
+            }
+        }
+    }
+
     #[inline]
     pub fn current_origin_as_reference_space(&self) -> xr::ReferenceSpaceType {
-        match self.current_origin {
+        match self.effective_origin(self.current_origin) {
             vr::ETrackingUniverseOrigin::Seated => xr::ReferenceSpaceType::LOCAL,
             vr::ETrackingUniverseOrigin::Standing => xr::ReferenceSpaceType::STAGE,
             vr::ETrackingUniverseOrigin::RawAndUncalibrated => unreachable!(),
         }
     }
 
+    fn vulkan_instance(&self, create_info: Option<&SessionCreateInfo>) -> *mut c_void {
+        match (create_info, &self.temp_vulkan) {
+            (Some(SessionCreateInfo::Vulkan(info)), _) => info.instance as _,
+            (_, Some(vk)) => vk.instance.handle().as_raw() as _,
+            _ => std::ptr::null_mut(),
+        }
+    }
+
     /// Returns true if this session is not using a temporary graphics setup.
     #[inline]
     pub fn is_real_session(&self) -> bool {
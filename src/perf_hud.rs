@@ -0,0 +1,220 @@
+use crate::openxr_data::FrameMetrics;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Wide enough for the longest line, "FPS 144.0".
+pub const HUD_WIDTH: u32 = MARGIN * 2 + MAX_LINE_GLYPHS * GLYPH_ADVANCE - GLYPH_SCALE;
+pub const HUD_HEIGHT: u32 = 96;
+
+/// Number of frames the stats are averaged over.
+const FRAME_WINDOW: usize = 90;
+/// The HUD is only redrawn this often, so we're not uploading a texture every frame.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(500);
+/// A frame that takes longer than this many display periods is assumed to have been reprojected.
+const REPROJECTION_THRESHOLD: f64 = 1.5;
+
+const GLYPH_WIDTH: u32 = 3;
+const GLYPH_HEIGHT: u32 = 5;
+const GLYPH_SCALE: u32 = 4;
+const MARGIN: u32 = 8;
+/// Horizontal distance between the start of two glyphs, including a one pixel (unscaled) gap.
+const GLYPH_ADVANCE: u32 = (GLYPH_WIDTH + 1) * GLYPH_SCALE;
+const MAX_LINE_GLYPHS: u32 = 9;
+const BACKGROUND: [u8; 4] = [0, 0, 0, 160];
+const FOREGROUND: [u8; 4] = [255, 255, 255, 255];
+
+/// An optional in-headset HUD showing the app's framerate, frame time, and how often it's missing
+/// the runtime's frame deadline. Enabled with XRIZER_PERF_HUD=1.
+pub struct PerfHud {
+    frame_intervals: VecDeque<f64>,
+    last_frame: Option<Instant>,
+    last_redraw: Option<Instant>,
+    revision: u64,
+    pixels: Vec<u8>,
+}
+
+#[derive(Debug, Default, Copy, Clone, PartialEq)]
+pub struct PerfStats {
+    pub fps: f64,
+    pub frame_time_ms: f64,
+    /// Fraction of frames in the window that took longer than the display period.
+    pub reprojection_ratio: f64,
+}
+
+impl PerfHud {
+    pub fn new() -> Self {
+        let mut hud = Self {
+            frame_intervals: VecDeque::with_capacity(FRAME_WINDOW),
+            last_frame: None,
+            last_redraw: None,
+            revision: 0,
+            pixels: vec![0; (HUD_WIDTH * HUD_HEIGHT * 4) as usize],
+        };
+        hud.redraw(PerfStats::default());
+        hud
+    }
+
+    /// Should be called once per presented frame.
+    pub fn record_frame(&mut self, metrics: &FrameMetrics) {
+        let now = Instant::now();
+        if let Some(last) = self.last_frame.replace(now) {
+            if self.frame_intervals.len() == FRAME_WINDOW {
+                self.frame_intervals.pop_front();
+            }
+            self.frame_intervals
+                .push_back(now.duration_since(last).as_secs_f64());
+        }
+
+        if self
+            .last_redraw
+            .is_none_or(|last| now.duration_since(last) >= REDRAW_INTERVAL)
+        {
+            self.last_redraw = Some(now);
+            self.redraw(self.stats(metrics.display_period()));
+        }
+    }
+
+    pub fn stats(&self, display_period: Option<f64>) -> PerfStats {
+        if self.frame_intervals.is_empty() {
+            return PerfStats::default();
+        }
+
+        let count = self.frame_intervals.len() as f64;
+        let frame_time = self.frame_intervals.iter().sum::<f64>() / count;
+        let reprojected = display_period.map_or(0, |period| {
+            self.frame_intervals
+                .iter()
+                .filter(|interval| **interval > period * REPROJECTION_THRESHOLD)
+                .count()
+        });
+
+        PerfStats {
+            fps: if frame_time > 0.0 {
+                1.0 / frame_time
+            } else {
+                0.0
+            },
+            frame_time_ms: frame_time * 1000.0,
+            reprojection_ratio: reprojected as f64 / count,
+        }
+    }
+
+    /// Incremented every time the HUD image changes.
+    #[inline]
+    pub fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Tightly packed RGBA8 pixels, HUD_WIDTH x HUD_HEIGHT.
+    #[inline]
+    pub fn pixels(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    fn redraw(&mut self, stats: PerfStats) {
+        for pixel in self.pixels.chunks_exact_mut(4) {
+            pixel.copy_from_slice(&BACKGROUND);
+        }
+
+        let lines = [
+            format!("FPS {:.1}", stats.fps),
+            format!("MS {:.1}", stats.frame_time_ms),
+            format!("RP {:.0}%", stats.reprojection_ratio * 100.0),
+        ];
+        for (row, line) in lines.iter().enumerate() {
+            let y = MARGIN + row as u32 * (GLYPH_HEIGHT + 2) * GLYPH_SCALE;
+            for (col, c) in line.chars().enumerate() {
+                let x = MARGIN + col as u32 * GLYPH_ADVANCE;
+                self.draw_glyph(x, y, c);
+            }
+        }
+
+        self.revision += 1;
+    }
+
+    fn draw_glyph(&mut self, x: u32, y: u32, c: char) {
+        let glyph = glyph(c);
+        for (glyph_y, row) in glyph.iter().enumerate() {
+            for glyph_x in 0..GLYPH_WIDTH {
+                if row & (1 << (GLYPH_WIDTH - 1 - glyph_x)) == 0 {
+                    continue;
+                }
+
+                for py in 0..GLYPH_SCALE {
+                    for px in 0..GLYPH_SCALE {
+                        let pixel_x = x + glyph_x * GLYPH_SCALE + px;
+                        let pixel_y = y + glyph_y as u32 * GLYPH_SCALE + py;
+                        if pixel_x >= HUD_WIDTH || pixel_y >= HUD_HEIGHT {
+                            continue;
+                        }
+                        let idx = ((pixel_y * HUD_WIDTH + pixel_x) * 4) as usize;
+                        self.pixels[idx..idx + 4].copy_from_slice(&FOREGROUND);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// A minimal 3x5 font, only containing the characters the HUD needs.
+fn glyph(c: char) -> [u8; GLYPH_HEIGHT as usize] {
+    match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' | 'S' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        '%' => [0b101, 0b001, 0b010, 0b100, 0b101],
+        'F' => [0b111, 0b100, 0b111, 0b100, 0b100],
+        'P' => [0b111, 0b101, 0b111, 0b100, 0b100],
+        'M' => [0b101, 0b111, 0b111, 0b101, 0b101],
+        'R' => [0b110, 0b101, 0b110, 0b101, 0b101],
+        _ => [0; GLYPH_HEIGHT as usize],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn is_foreground(hud: &PerfHud, x: u32, y: u32) -> bool {
+        let idx = ((y * HUD_WIDTH + x) * 4) as usize;
+        hud.pixels()[idx..idx + 4] == FOREGROUND
+    }
+
+    #[test]
+    fn high_refresh_rate_fps_fits() {
+        let mut hud = PerfHud::new();
+        hud.redraw(PerfStats {
+            fps: 144.0,
+            ..Default::default()
+        });
+
+        // "FPS 144.0" - the top row of the final '0' is fully lit.
+        let last_glyph_x = MARGIN + 8 * GLYPH_ADVANCE;
+        let last_pixel_x = last_glyph_x + GLYPH_WIDTH * GLYPH_SCALE - 1;
+        assert!(last_pixel_x < HUD_WIDTH);
+        for x in last_glyph_x..=last_pixel_x {
+            assert!(
+                is_foreground(&hud, x, MARGIN),
+                "pixel {x} of the last glyph is clipped"
+            );
+        }
+
+        // Nothing is drawn into the right margin.
+        for y in 0..HUD_HEIGHT {
+            for x in HUD_WIDTH - MARGIN..HUD_WIDTH {
+                assert!(
+                    !is_foreground(&hud, x, y),
+                    "glyph drawn in margin at ({x}, {y})"
+                );
+            }
+        }
+    }
+}
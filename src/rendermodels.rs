@@ -1,7 +1,88 @@
+use std::f32::consts::PI;
 use std::ffi::CStr;
 
+use glam::{Quat, Vec3};
 use log::debug;
 use openvr as vr;
+use openxr as xr;
+
+const IDENTITY: vr::HmdMatrix34_t = vr::HmdMatrix34_t {
+    m: [
+        [1.0, 0.0, 0.0, 0.0],
+        [0.0, 1.0, 0.0, 0.0],
+        [0.0, 0.0, 1.0, 0.0],
+    ],
+};
+
+/// How far the trigger rotates about its pivot when fully pulled (20 degrees).
+const TRIGGER_MAX_ANGLE: f32 = PI / 9.0;
+/// Distance from the center of the trackpad to its edge, in meters.
+const TRACKPAD_RADIUS: f32 = 0.02;
+
+fn component_transform(orientation: Quat, position: Vec3) -> vr::HmdMatrix34_t {
+    xr::Posef {
+        orientation: xr::Quaternionf {
+            x: orientation.x,
+            y: orientation.y,
+            z: orientation.z,
+            w: orientation.w,
+        },
+        position: xr::Vector3f {
+            x: position.x,
+            y: position.y,
+            z: position.z,
+        },
+    }
+    .into()
+}
+
+/// Derives the state of a standard controller component from a legacy controller state block.
+/// The trigger rotates back about its pivot as it's pulled, and the trackpad's local transform
+/// follows the touch position on its surface, so apps can animate their own models with them.
+fn component_state_from_controller(
+    component: &CStr,
+    state: &vr::VRControllerState_t,
+) -> Option<vr::RenderModel_ComponentState_t> {
+    let (button, axis) = match component.to_bytes() {
+        b"trigger" => (vr::EVRButtonId::SteamVR_Trigger, 1),
+        b"trackpad" => (vr::EVRButtonId::Axis0, 0),
+        b"grip" => (vr::EVRButtonId::Grip, 2),
+        _ => return None,
+    };
+    let mask = 1_u64 << (button as u32);
+    let vr::VRControllerAxis_t { x, y } = state.rAxis[axis];
+    let axis_moved = x != 0.0 || y != 0.0;
+
+    let mut properties = vr::EVRComponentProperty::IsVisible;
+    if state.ulButtonTouched & mask != 0 || axis_moved {
+        properties |= vr::EVRComponentProperty::IsTouched;
+    }
+    if state.ulButtonPressed & mask != 0 {
+        properties |= vr::EVRComponentProperty::IsPressed;
+    }
+
+    let (render_model, local) = match button {
+        vr::EVRButtonId::SteamVR_Trigger => {
+            let pulled = component_transform(
+                Quat::from_rotation_x(-x.clamp(0.0, 1.0) * TRIGGER_MAX_ANGLE),
+                Vec3::ZERO,
+            );
+            (pulled, pulled)
+        }
+        // The pad itself doesn't move, only the touch point on it.
+        vr::EVRButtonId::Axis0 => (
+            IDENTITY,
+            component_transform(Quat::IDENTITY, Vec3::new(x, 0.0, -y) * TRACKPAD_RADIUS),
+        ),
+        _ => (IDENTITY, IDENTITY),
+    };
+
+    Some(vr::RenderModel_ComponentState_t {
+        mTrackingToComponentRenderModel: render_model,
+        mTrackingToComponentLocal: local,
+        uProperties: properties.0 as _,
+    })
+}
 
 #[derive(Default, macros::InterfaceImpl)]
 #[interface = "IVRRenderModels"]
@@ -46,13 +127,25 @@ impl vr::IVRRenderModels006_Interface for RenderModels {
     fn GetComponentState(
         &self,
         _: *const std::ffi::c_char,
-        _: *const std::ffi::c_char,
-        _: *const vr::VRControllerState_t,
+        component_name: *const std::ffi::c_char,
+        controller_state: *const vr::VRControllerState_t,
         _: *const vr::RenderModel_ControllerMode_State_t,
-        _: *mut vr::RenderModel_ComponentState_t,
+        component_state: *mut vr::RenderModel_ComponentState_t,
     ) -> bool {
-        crate::warn_unimplemented!("GetComponentState");
-        false
+        if component_name.is_null() || component_state.is_null() {
+            return false;
+        }
+        let Some(controller_state) = (unsafe { controller_state.as_ref() }) else {
+            return false;
+        };
+
+        let name = unsafe { CStr::from_ptr(component_name) };
+        let Some(state) = component_state_from_controller(name, controller_state) else {
+            debug!("no state for component {name:?}");
+            return false;
+        };
+        unsafe { component_state.write(state) };
+        true
     }
     fn GetComponentStateForDevicePath(
         &self,
@@ -169,3 +262,71 @@ impl vr::IVRRenderModels006_Interface for RenderModels {
         vr::EVRRenderModelError::NotSupported
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trigger_state_tracks_input() {
+        let has = |state: &vr::RenderModel_ComponentState_t, prop: vr::EVRComponentProperty| {
+            state.uProperties & prop.0 as u32 != 0
+        };
+        let trigger_mask = 1_u64 << (vr::EVRButtonId::SteamVR_Trigger as u32);
+        let mut controller = vr::VRControllerState_t::default();
+
+        let state = component_state_from_controller(c"trigger", &controller).unwrap();
+        assert!(has(&state, vr::EVRComponentProperty::IsVisible));
+        assert!(!has(&state, vr::EVRComponentProperty::IsTouched));
+        assert!(!has(&state, vr::EVRComponentProperty::IsPressed));
+
+        controller.rAxis[1].x = 0.5;
+        let state = component_state_from_controller(c"trigger", &controller).unwrap();
+        assert!(has(&state, vr::EVRComponentProperty::IsTouched));
+        assert!(!has(&state, vr::EVRComponentProperty::IsPressed));
+
+        controller.rAxis[1].x = 1.0;
+        controller.ulButtonPressed |= trigger_mask;
+        let state = component_state_from_controller(c"trigger", &controller).unwrap();
+        assert!(has(&state, vr::EVRComponentProperty::IsTouched));
+        assert!(has(&state, vr::EVRComponentProperty::IsPressed));
+
+        // The trigger rotates further the more it's pulled.
+        let mut angle = |value| {
+            controller.rAxis[1].x = value;
+            let state = component_state_from_controller(c"trigger", &controller).unwrap();
+            let pose: xr::Posef = state.mTrackingToComponentRenderModel.into();
+            assert_eq!(
+                state.mTrackingToComponentLocal.m,
+                state.mTrackingToComponentRenderModel.m
+            );
+            let xr::Quaternionf { x, y, z, w } = pose.orientation;
+            Quat::from_xyzw(x, y, z, w).angle_between(Quat::IDENTITY)
+        };
+        assert!(angle(0.0) < 1e-3);
+        let half = angle(0.5);
+        let full = angle(1.0);
+        assert!((half - TRIGGER_MAX_ANGLE / 2.0).abs() < 1e-4, "{half}");
+        assert!((full - TRIGGER_MAX_ANGLE).abs() < 1e-4, "{full}");
+
+        // Other components don't pick up the trigger.
+        let state = component_state_from_controller(c"trackpad", &controller).unwrap();
+        assert!(!has(&state, vr::EVRComponentProperty::IsTouched));
+        assert!(!has(&state, vr::EVRComponentProperty::IsPressed));
+        assert!(component_state_from_controller(c"unknown", &controller).is_none());
+    }
+
+    #[test]
+    fn trackpad_local_transform_follows_touch() {
+        let mut controller = vr::VRControllerState_t::default();
+        controller.rAxis[0] = vr::VRControllerAxis_t { x: 1.0, y: 0.5 };
+
+        let state = component_state_from_controller(c"trackpad", &controller).unwrap();
+        assert_eq!(state.mTrackingToComponentRenderModel.m, IDENTITY.m);
+        let xr::Posef { position, .. } = state.mTrackingToComponentLocal.into();
+        assert_eq!(
+            (position.x, position.y, position.z),
+            (TRACKPAD_RADIUS, 0.0, -0.5 * TRACKPAD_RADIUS)
+        );
+    }
+}
@@ -130,7 +130,14 @@ impl vr::IVRSettings003_Interface for Settings {
             *error = EVRSettingsError::None;
         }
         debug!("Getting float on {section}/{key}");
-        0.0
+        match (section.as_ref(), key.as_ref()) {
+            // Brightness tools scale these, so a zero gain would leave the display black.
+            (
+                "steamvr",
+                "hmdDisplayColorGainR" | "hmdDisplayColorGainG" | "hmdDisplayColorGainB",
+            ) => 1.0,
+            _ => 0.0,
+        }
     }
 
     fn GetString(
@@ -185,3 +192,24 @@ impl vr::IVRSettings002On003 for Settings {
         false
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vr::IVRSettings003_Interface;
+
+    #[test]
+    fn display_color_gains_are_neutral() {
+        let settings = Settings::default();
+        for key in [
+            c"hmdDisplayColorGainR",
+            c"hmdDisplayColorGainG",
+            c"hmdDisplayColorGainB",
+        ] {
+            let mut err = EVRSettingsError::ReadFailed;
+            let value = settings.GetFloat(c"steamvr".as_ptr(), key.as_ptr(), &mut err);
+            assert_eq!(err, EVRSettingsError::None, "{key:?}");
+            assert_eq!(value, 1.0, "{key:?}");
+        }
+    }
+}
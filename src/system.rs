@@ -3,14 +3,16 @@ use crate::{
     input::{Input, TrackedDeviceType},
     openxr_data::{Hand, RealOpenXrData, SessionData},
     overlay::OverlayMan,
-    tracy_span,
+    tracy_span, AtomicF32,
 };
 use glam::{Mat3, Quat, Vec3};
-use log::{debug, error, trace, warn};
+use log::{debug, error, info, trace, warn};
 use openvr as vr;
 use openxr as xr;
-use std::ffi::CStr;
-use std::sync::{Arc, Mutex};
+use std::ffi::{CStr, CString};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
 #[derive(Copy, Clone)]
 pub struct ViewData {
@@ -24,14 +26,53 @@ struct ViewDataViewSpace {
     original_orientations: [Quat; 2],
 }
 
+/// Timings of xrLocateViews calls, for diagnosing slow runtimes without a Tracy build.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct FrameStats {
+    pub last: Duration,
+    pub min: Duration,
+    pub max: Duration,
+    pub count: u64,
+    total: Duration,
+}
+
+impl FrameStats {
+    fn record(&mut self, duration: Duration) {
+        self.min = if self.count == 0 {
+            duration
+        } else {
+            self.min.min(duration)
+        };
+        self.max = self.max.max(duration);
+        self.last = duration;
+        self.total += duration;
+        self.count += 1;
+    }
+
+    pub fn avg(&self) -> Duration {
+        if self.count == 0 {
+            Duration::ZERO
+        } else {
+            self.total.div_f64(self.count as f64)
+        }
+    }
+}
+
 #[derive(Default)]
 struct ViewCache {
     view: Option<ViewDataViewSpace>,
     local: Option<ViewData>,
     stage: Option<ViewData>,
+    stats: FrameStats,
 }
 
 impl ViewCache {
+    fn clear(&mut self) {
+        self.view = None;
+        self.local = None;
+        self.stage = None;
+    }
+
     fn get_views(
         &mut self,
         session: &SessionData,
@@ -41,7 +82,9 @@ impl ViewCache {
         match ty {
             xr::ReferenceSpaceType::VIEW => {
                 self.view
-                    .get_or_insert_with(|| Self::get_views_view_space(session, display_time))
+                    .get_or_insert_with(|| {
+                        Self::get_views_view_space(&mut self.stats, session, display_time)
+                    })
                     .data
             }
             xr::ReferenceSpaceType::LOCAL | xr::ReferenceSpaceType::STAGE => {
@@ -52,27 +95,52 @@ impl ViewCache {
                 };
 
                 *view.get_or_insert_with(|| {
-                    let view_rots = self
-                        .view
-                        .get_or_insert_with(|| Self::get_views_view_space(session, display_time))
-                        .original_orientations;
-
-                    Self::get_views_other_space(session, display_time, ty, view_rots)
+                    let view_space = self.view.get_or_insert_with(|| {
+                        Self::get_views_view_space(&mut self.stats, session, display_time)
+                    });
+                    let view_rots = view_space.original_orientations;
+                    let view_fovs = view_space.data.views.map(|v| v.fov);
+
+                    Self::get_views_other_space(
+                        &mut self.stats,
+                        session,
+                        display_time,
+                        ty,
+                        view_rots,
+                        view_fovs,
+                    )
                 })
             }
             other => panic!("unexpected reference space type: {other:?}"),
         }
     }
 
-    fn get_views_view_space(session: &SessionData, display_time: xr::Time) -> ViewDataViewSpace {
-        let (flags, mut views) = session
+    fn locate_views(
+        stats: &mut FrameStats,
+        session: &SessionData,
+        display_time: xr::Time,
+        ty: xr::ReferenceSpaceType,
+    ) -> (xr::ViewStateFlags, Vec<xr::View>) {
+        let start = Instant::now();
+        let ret = session
             .session
             .locate_views(
                 xr::ViewConfigurationType::PRIMARY_STEREO,
                 display_time,
-                session.get_space_from_type(xr::ReferenceSpaceType::VIEW),
+                session.get_space_from_type(ty),
             )
             .expect("Couldn't locate views");
+        stats.record(start.elapsed());
+        ret
+    }
+
+    fn get_views_view_space(
+        stats: &mut FrameStats,
+        session: &SessionData,
+        display_time: xr::Time,
+    ) -> ViewDataViewSpace {
+        let (flags, mut views) =
+            Self::locate_views(stats, session, display_time, xr::ReferenceSpaceType::VIEW);
 
         let original_orientations = views
             .iter_mut()
@@ -102,30 +170,33 @@ impl ViewCache {
     }
 
     fn get_views_other_space(
+        stats: &mut FrameStats,
         session: &SessionData,
         display_time: xr::Time,
         ty: xr::ReferenceSpaceType,
         view_data_orientations_inverse: [Quat; 2],
+        view_fovs: [xr::Fovf; 2],
     ) -> ViewData {
-        let (flags, mut views) = session
-            .session
-            .locate_views(
-                xr::ViewConfigurationType::PRIMARY_STEREO,
-                display_time,
-                session.get_space_from_type(ty),
-            )
-            .expect("Couldn't locate views");
+        let (flags, mut views) = Self::locate_views(stats, session, display_time, ty);
 
         for (
             xr::View {
                 pose: xr::Posef {
                     orientation: rot, ..
                 },
-                ..
+                fov,
             },
-            view_rot,
-        ) in views.iter_mut().zip(view_data_orientations_inverse)
+            (view_rot, view_fov),
+        ) in views
+            .iter_mut()
+            .zip(view_data_orientations_inverse.into_iter().zip(view_fovs))
         {
+            // The FOV is relative to the view itself, so it shouldn't depend on the space the
+            // views were located in - but some runtimes report slightly different FOVs per space.
+            // Always use the VIEW space FOV, so the projection games get from GetProjectionRaw
+            // matches what we submit, regardless of the tracking origin.
+            *fov = view_fov;
+
             let quat = Quat::from_xyzw(rot.x, rot.y, rot.z, rot.w);
             // rotate the inverse of the view space view rotation by this space's
             // view orientation to remove the canting from the displays in this space
@@ -147,6 +218,70 @@ impl ViewCache {
     }
 }
 
+/// Triangles covering the corners of an eye's image outside of a circle around the view center,
+/// given the tangents of its projection (as returned by GetProjectionRaw). The circle reaches the
+/// farthest image edge, and each triangle lies beyond the line tangent to it facing the corner, so
+/// only pixels that would be cut off by a round lens are hidden.
+fn hidden_corner_triangles(left: f32, right: f32, top: f32, bottom: f32) -> Vec<vr::HmdVector2_t> {
+    let radius = [left, right, top, bottom]
+        .into_iter()
+        .map(f32::abs)
+        .fold(0.0, f32::max);
+    // Scaled to the image the same way the runtime's visibility mask is.
+    let to_uv = |x: f32, y: f32| vr::HmdVector2_t {
+        v: [(x - left) / (right - left), (y - top) / (bottom - top)],
+    };
+
+    let mut vertices = Vec::new();
+    for (x, y) in [(left, top), (right, top), (left, bottom), (right, bottom)] {
+        let distance = x.hypot(y);
+        if distance <= radius {
+            continue;
+        }
+        let (nx, ny) = (x / distance, y / distance);
+        // Where the tangent line crosses the horizontal and vertical edges meeting at this corner.
+        vertices.extend([
+            to_uv(x, y),
+            to_uv((radius - y * ny) / nx, y),
+            to_uv(x, (radius - x * nx) / ny),
+        ]);
+    }
+    vertices
+}
+
+/// Answers the "what is the runtime doing" queries consistently from our actual state.
+struct SystemCapabilities<'a> {
+    openxr: &'a RealOpenXrData,
+}
+
+impl SystemCapabilities<'_> {
+    /// OpenXR runtimes always drive the headset directly, never as an extended desktop monitor.
+    fn display_on_desktop(&self) -> bool {
+        false
+    }
+
+    /// We never render controller models on the app's behalf.
+    fn drawing_controllers(&self) -> bool {
+        false
+    }
+
+    /// OpenXR has no explicit hint for this, but a session that's visible without focus means
+    /// something else (e.g. the runtime's dashboard) is being drawn over the app.
+    fn reduce_rendering_work(&self) -> bool {
+        self.openxr.session_data.get().state == xr::SessionState::VISIBLE
+    }
+
+    /// The runtime only gives input to the focused session, so while the session is running without
+    /// focus (e.g. the dashboard is up) the app shouldn't act on it. Other states are left alone, so
+    /// apps aren't told input is gone before the session has even started.
+    fn input_available(&self) -> bool {
+        !matches!(
+            self.openxr.session_data.get().state,
+            xr::SessionState::SYNCHRONIZED | xr::SessionState::VISIBLE
+        )
+    }
+}
+
 #[derive(macros::InterfaceImpl)]
 #[interface = "IVRSystem"]
 #[versions(023, 022, 021, 020, 019, 017, 016, 015, 014, 012, 009)]
@@ -156,6 +291,65 @@ pub struct System {
     overlay: Injected<OverlayMan>,
     vtables: Vtables,
     views: Mutex<ViewCache>,
+    /// Multiplier for the recommended render target size (XRIZER_RENDER_SCALE)
+    render_scale: AtomicF32,
+    /// Report the same symmetric FOV for both eyes (XRIZER_SYMMETRIC_FOV)
+    symmetric_fov: AtomicBool,
+    /// The SteamVR version reported to the game (XRIZER_RUNTIME_VERSION)
+    runtime_version: Mutex<CString>,
+    /// The headset model reported to the game, if overridden (XRIZER_HMD_MODEL)
+    hmd_model: Mutex<Option<HmdModel>>,
+    /// Millimeters added to the runtime's IPD, split evenly between the eyes (XRIZER_IPD_OFFSET)
+    ipd_offset: AtomicF32,
+    /// Generate a hidden area mesh if the runtime has no visibility mask (XRIZER_FALLBACK_HIDDEN_AREA)
+    fallback_hidden_area: AtomicBool,
+    /// Per eye fallback hidden area meshes, once generated
+    fallback_hidden_area_meshes: [OnceLock<Box<[vr::HmdVector2_t]>>; 2],
+}
+
+/// The largest IPD adjustment allowed, in millimeters.
+const MAX_IPD_OFFSET: f32 = 10.0;
+
+/// The SteamVR version reported by default.
+const DEFAULT_RUNTIME_VERSION: &CStr = c"2.5.1";
+
+/// Reported as the tracking universe ID. SteamVR gives each room setup its own ID, but the runtime
+/// owns the play space here and never changes universes, so any fixed non-zero value will do.
+const UNIVERSE_ID: u64 = 1;
+
+/// A SteamVR headset the HMD can be reported as, for games that check for specific models.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum HmdModel {
+    Vive,
+    Index,
+}
+
+impl HmdModel {
+    fn from_env() -> Option<Self> {
+        match std::env::var("XRIZER_HMD_MODEL").as_deref() {
+            Err(_) => None,
+            Ok("vive") => Some(Self::Vive),
+            Ok("index") => Some(Self::Index),
+            Ok(other) => {
+                warn!("Invalid XRIZER_HMD_MODEL ({other})");
+                None
+            }
+        }
+    }
+
+    fn model_number(self) -> &'static CStr {
+        match self {
+            Self::Vive => c"Vive MV",
+            Self::Index => c"Index",
+        }
+    }
+
+    fn manufacturer_name(self) -> &'static CStr {
+        match self {
+            Self::Vive => c"HTC",
+            Self::Index => c"Valve",
+        }
+    }
 }
 
 mod log_tags {
@@ -170,11 +364,150 @@ impl System {
             overlay: injector.inject(),
             vtables: Default::default(),
             views: Mutex::default(),
+            render_scale: std::env::var("XRIZER_RENDER_SCALE")
+                .ok()
+                .and_then(|scale| {
+                    scale
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|scale| scale.is_finite() && *scale > 0.0)
+                        .or_else(|| {
+                            warn!("Invalid XRIZER_RENDER_SCALE: {scale}");
+                            None
+                        })
+                })
+                .unwrap_or(1.0)
+                .into(),
+            symmetric_fov: std::env::var("XRIZER_SYMMETRIC_FOV")
+                .is_ok_and(|s| s == "1")
+                .into(),
+            runtime_version: std::env::var("XRIZER_RUNTIME_VERSION")
+                .ok()
+                .and_then(|version| {
+                    CString::new(version)
+                        .inspect_err(|e| warn!("Invalid XRIZER_RUNTIME_VERSION ({e})"))
+                        .ok()
+                })
+                .unwrap_or_else(|| DEFAULT_RUNTIME_VERSION.into())
+                .into(),
+            hmd_model: HmdModel::from_env().into(),
+            ipd_offset: std::env::var("XRIZER_IPD_OFFSET")
+                .ok()
+                .and_then(|offset| {
+                    offset
+                        .parse::<f32>()
+                        .ok()
+                        .filter(|offset| offset.is_finite())
+                        .or_else(|| {
+                            warn!("Invalid XRIZER_IPD_OFFSET: {offset}");
+                            None
+                        })
+                })
+                .map(|offset| offset.clamp(-MAX_IPD_OFFSET, MAX_IPD_OFFSET))
+                .unwrap_or(0.0)
+                .into(),
+            fallback_hidden_area: std::env::var("XRIZER_FALLBACK_HIDDEN_AREA")
+                .is_ok_and(|s| s == "1")
+                .into(),
+            fallback_hidden_area_meshes: Default::default(),
+        }
+    }
+
+    #[inline]
+    pub fn render_scale(&self) -> f32 {
+        self.render_scale.load()
+    }
+
+    #[cfg(test)]
+    pub fn set_render_scale(&self, scale: f32) {
+        self.render_scale.store(scale);
+    }
+
+    #[cfg(test)]
+    pub fn set_symmetric_fov(&self, enabled: bool) {
+        self.symmetric_fov.store(enabled, Ordering::Relaxed);
+    }
+
+    #[cfg(test)]
+    pub fn set_fallback_hidden_area(&self, enabled: bool) {
+        self.fallback_hidden_area.store(enabled, Ordering::Relaxed);
+    }
+
+    #[cfg(test)]
+    pub fn set_ipd_offset(&self, millimeters: f32) {
+        self.ipd_offset
+            .store(millimeters.clamp(-MAX_IPD_OFFSET, MAX_IPD_OFFSET));
+    }
+
+    #[cfg(test)]
+    pub fn set_hmd_model(&self, model: Option<HmdModel>) {
+        *self.hmd_model.lock().unwrap() = model;
+    }
+
+    /// Invalidates any pointer previously returned by GetRuntimeVersion.
+    #[cfg(test)]
+    pub fn set_runtime_version(&self, version: &CStr) {
+        *self.runtime_version.lock().unwrap() = version.into();
+    }
+
+    /// The FOV the game should render the given eye with.
+    pub fn projection_fov(&self, eye: vr::EVREye) -> xr::Fovf {
+        // Views located in other spaces use the VIEW space FOV (see get_views_other_space)
+        let views = self.get_views(xr::ReferenceSpaceType::VIEW).views;
+        if !self.symmetric_fov.load(Ordering::Relaxed) {
+            return views[eye as usize].fov;
+        }
+
+        // Some engines break with canted displays, so pad both eyes to the widest extent on each axis.
+        let max_extent = |angles: fn(&xr::Fovf) -> [f32; 2]| {
+            views
+                .iter()
+                .flat_map(|view| angles(&view.fov))
+                .map(f32::abs)
+                .fold(0.0, f32::max)
+        };
+        let horizontal = max_extent(|fov| [fov.angle_left, fov.angle_right]);
+        let vertical = max_extent(|fov| [fov.angle_up, fov.angle_down]);
+        xr::Fovf {
+            angle_left: -horizontal,
+            angle_right: horizontal,
+            angle_up: vertical,
+            angle_down: -vertical,
+        }
+    }
+
+    /// A hidden area mesh for runtimes without XR_KHR_visibility_mask, so games don't render the
+    /// corners of the image that can't be seen through the lenses anyway.
+    fn fallback_hidden_area_mesh(&self, eye: vr::EVREye) -> vr::HiddenAreaMesh_t {
+        let cache = &self.fallback_hidden_area_meshes[eye as usize];
+        let vertices = match cache.get() {
+            Some(vertices) => vertices,
+            None => {
+                let [mut left, mut right, mut top, mut bottom] = [0.0; 4];
+                self.GetProjectionRaw(eye, &mut left, &mut right, &mut top, &mut bottom);
+                let vertices = hidden_corner_triangles(left, right, top, bottom);
+                // Don't cache nothing if the views haven't been located yet.
+                if vertices.is_empty() {
+                    return Default::default();
+                }
+                cache.get_or_init(|| vertices.into())
+            }
+        };
+
+        vr::HiddenAreaMesh_t {
+            pVertexData: vertices.as_ptr(),
+            unTriangleCount: (vertices.len() / 3) as u32,
+        }
+    }
+
+    fn capabilities(&self) -> SystemCapabilities<'_> {
+        SystemCapabilities {
+            openxr: &self.openxr,
         }
     }
 
     pub fn reset_views(&self) {
-        std::mem::take(&mut *self.views.lock().unwrap());
+        self.views.lock().unwrap().clear();
         let session = self.openxr.session_data.get();
         let display_time = self.openxr.display_time.get();
         let mut views = self.views.lock().unwrap();
@@ -190,8 +523,116 @@ impl System {
         tracy_span!();
         let session = self.openxr.session_data.get();
         let mut views = self.views.lock().unwrap();
-        views.get_views(&session, self.openxr.display_time.get(), ty)
+        let mut data = views.get_views(&session, self.openxr.display_time.get(), ty);
+
+        // Move each eye outward (or inward) along its own right axis by half the offset.
+        let half_offset = self.ipd_offset.load() / 2000.0;
+        if half_offset != 0.0 {
+            for (view, sign) in data.views.iter_mut().zip([-1.0, 1.0]) {
+                let o = view.pose.orientation;
+                let right = Quat::from_xyzw(o.x, o.y, o.z, o.w) * Vec3::X;
+                let p = &mut view.pose.position;
+                p.x += right.x * half_offset * sign;
+                p.y += right.y * half_offset * sign;
+                p.z += right.z * half_offset * sign;
+            }
+        }
+        data
+    }
+
+    /// Timings of the xrLocateViews calls made so far.
+    pub fn frame_stats(&self) -> FrameStats {
+        self.views.lock().unwrap().stats
+    }
+
+    /// The runtime's predicted display time for the current frame, in seconds. This is the time
+    /// poses are predicted for, and what GetDeviceToAbsoluteTrackingPose offsets are relative to.
+    pub fn predicted_display_time(&self) -> f64 {
+        self.openxr.display_time.get().as_nanos() as f64 / 1_000_000_000.0
+    }
+}
+
+impl Drop for System {
+    fn drop(&mut self) {
+        let stats = self.frame_stats();
+        if stats.count > 0 {
+            info!(
+                "xrLocateViews timings over {} calls: last {:?}, min {:?}, avg {:?}, max {:?}",
+                stats.count,
+                stats.last,
+                stats.min,
+                stats.avg(),
+                stats.max
+            );
+        }
+    }
+}
+
+fn write_space_change_event(
+    ty: vr::EVREventType,
+    event: *mut vr::VREvent_t,
+    size: u32,
+    pose: *mut vr::TrackedDevicePose_t,
+) -> bool {
+    const MIN_SPACE_EVENT_SIZE: usize =
+        std::mem::offset_of!(vr::VREvent_t, data) + std::mem::size_of::<vr::VREvent_Chaperone_t>();
+    if event.is_null() || (size as usize) < MIN_SPACE_EVENT_SIZE {
+        warn!("Couldn't send {ty:?} event - invalid event struct (size: {size})");
+        return false;
+    }
+
+    debug!("sending {ty:?} event");
+    // See Input::get_next_event for why we're using raw pointers here.
+    unsafe {
+        (&raw mut (*event).eventType).write(ty as u32);
+        (&raw mut (*event).trackedDeviceIndex).write(vr::k_unTrackedDeviceIndexInvalid);
+        (&raw mut (*event).eventAgeSeconds).write(0.0);
+        if ty == vr::EVREventType::SeatedZeroPoseReset {
+            (&raw mut (*event).data.seatedZeroPoseReset).write(vr::VREvent_SeatedZeroPoseReset_t {
+                bResetBySystemMenu: false,
+            });
+        } else {
+            (&raw mut (*event).data.chaperone).write(vr::VREvent_Chaperone_t {
+                m_nPreviousUniverse: UNIVERSE_ID,
+                m_nCurrentUniverse: UNIVERSE_ID,
+            });
+        }
+        if !pose.is_null() {
+            pose.write(Default::default());
+        }
+    }
+    true
+}
+
+fn write_quit_event(
+    event: *mut vr::VREvent_t,
+    size: u32,
+    pose: *mut vr::TrackedDevicePose_t,
+) -> bool {
+    const MIN_PROCESS_EVENT_SIZE: usize =
+        std::mem::offset_of!(vr::VREvent_t, data) + std::mem::size_of::<vr::VREvent_Process_t>();
+    if event.is_null() || (size as usize) < MIN_PROCESS_EVENT_SIZE {
+        warn!("Couldn't send quit event - invalid event struct (size: {size})");
+        return false;
+    }
+
+    debug!("sending quit event");
+    // See Input::get_next_event for why we're using raw pointers here.
+    unsafe {
+        (&raw mut (*event).eventType).write(vr::EVREventType::Quit as u32);
+        (&raw mut (*event).trackedDeviceIndex).write(vr::k_unTrackedDeviceIndexInvalid);
+        (&raw mut (*event).eventAgeSeconds).write(0.0);
+        (&raw mut (*event).data.process).write(vr::VREvent_Process_t {
+            pid: std::process::id(),
+            oldPid: 0,
+            bForced: false,
+            bConnectionLost: false,
+        });
+        if !pose.is_null() {
+            pose.write(Default::default());
+        }
     }
+    true
 }
 
 impl vr::IVRSystem023_Interface for System {
@@ -205,12 +646,28 @@ impl vr::IVRSystem023_Interface for System {
             )
             .unwrap();
 
+        // Scaled sizes can't go past what the runtime supports
+        let scale = self.render_scale();
+        let scaled = |recommended: u32, max: u32| {
+            ((recommended as f32 * scale).round() as u32).clamp(1, max)
+        };
+
         if !width.is_null() {
-            unsafe { *width = views[0].recommended_image_rect_width };
+            unsafe {
+                *width = scaled(
+                    views[0].recommended_image_rect_width,
+                    views[0].max_image_rect_width,
+                )
+            };
         }
 
         if !height.is_null() {
-            unsafe { *height = views[0].recommended_image_rect_height };
+            unsafe {
+                *height = scaled(
+                    views[0].recommended_image_rect_height,
+                    views[0].max_image_rect_height,
+                )
+            };
         }
     }
     fn GetProjectionMatrix(&self, eye: vr::EVREye, near_z: f32, far_z: f32) -> vr::HmdMatrix44_t {
@@ -241,19 +698,14 @@ impl vr::IVRSystem023_Interface for System {
         top: *mut f32,
         bottom: *mut f32,
     ) {
-        let ty = self
-            .openxr
-            .session_data
-            .get()
-            .current_origin_as_reference_space();
-        let view = self.get_views(ty).views[eye as usize];
+        let fov = self.projection_fov(eye);
 
         // Top and bottom are flipped, for some reason
         unsafe {
-            *left = view.fov.angle_left.tan();
-            *right = view.fov.angle_right.tan();
-            *bottom = view.fov.angle_up.tan();
-            *top = view.fov.angle_down.tan();
+            *left = fov.angle_left.tan();
+            *right = fov.angle_right.tan();
+            *bottom = fov.angle_up.tan();
+            *top = fov.angle_down.tan();
         }
     }
     fn ComputeDistortion(
@@ -290,34 +742,50 @@ impl vr::IVRSystem023_Interface for System {
             }
         }
     }
-    fn GetTimeSinceLastVsync(&self, _: *mut f32, _: *mut u64) -> bool {
-        crate::warn_unimplemented!("GetTimeSinceLastVsync");
-        false
+    fn GetTimeSinceLastVsync(
+        &self,
+        seconds_since_last_vsync: *mut f32,
+        frame_counter: *mut u64,
+    ) -> bool {
+        let metrics = &self.openxr.frame_metrics;
+        if let Some(seconds) = unsafe { seconds_since_last_vsync.as_mut() } {
+            *seconds = metrics.time_since_last_frame() as f32;
+        }
+        if let Some(counter) = unsafe { frame_counter.as_mut() } {
+            *counter = metrics.frame_index();
+        }
+        true
     }
     fn GetRuntimeVersion(&self) -> *const std::os::raw::c_char {
-        static VERSION: &CStr = c"2.5.1";
-        VERSION.as_ptr()
+        // The string is only replaced in tests, so the pointer stays valid.
+        self.runtime_version.lock().unwrap().as_ptr()
     }
-    fn GetAppContainerFilePaths(&self, _: *mut std::os::raw::c_char, _: u32) -> u32 {
-        todo!()
+    fn GetAppContainerFilePaths(&self, buffer: *mut std::os::raw::c_char, size: u32) -> u32 {
+        // There's no app container to share paths with, so the list is always empty.
+        if !buffer.is_null() && size > 0 {
+            unsafe { buffer.write(0) };
+        }
+        0
     }
     fn AcknowledgeQuit_Exiting(&self) {
-        todo!()
+        self.openxr.request_exit();
     }
-    fn PerformFirmwareUpdate(&self, _: vr::TrackedDeviceIndex_t) -> vr::EVRFirmwareError {
-        todo!()
+    fn PerformFirmwareUpdate(&self, index: vr::TrackedDeviceIndex_t) -> vr::EVRFirmwareError {
+        // OpenXR has no concept of device firmware - nothing to update.
+        warn!("Firmware updates are unsupported (requested for device {index})");
+        vr::EVRFirmwareError::None
     }
     fn ShouldApplicationReduceRenderingWork(&self) -> bool {
-        false
+        self.capabilities().reduce_rendering_work()
     }
     fn ShouldApplicationPause(&self) -> bool {
-        false
+        !self.openxr.user_present()
     }
     fn IsSteamVRDrawingControllers(&self) -> bool {
-        todo!()
+        self.capabilities().drawing_controllers()
     }
     fn IsInputAvailable(&self) -> bool {
-        true
+        self.capabilities().input_available()
     }
     fn GetControllerAxisTypeNameFromEnum(
         &self,
@@ -350,25 +818,9 @@ impl vr::IVRSystem023_Interface for System {
         state_size: u32,
         pose: *mut vr::TrackedDevicePose_t,
     ) -> bool {
-        let input = self.input.force(|_| Input::new(self.openxr.clone()));
-
-        let Some(hand) = input.device_index_to_hand(device_index) else {
-            return false;
-        };
-
-        if self.GetControllerState(device_index, state, state_size) {
-            unsafe {
-                *pose.as_mut().unwrap() = self
-                    .input
-                    .get()
-                    .unwrap()
-                    .get_controller_pose(hand, Some(origin))
-                    .unwrap_or_default();
-            }
-            true
-        } else {
-            false
-        }
+        self.input
+            .force(|_| Input::new(self.openxr.clone()))
+            .get_legacy_controller_state_with_pose(device_index, state, state_size, origin, pose)
     }
     fn GetControllerState(
         &self,
@@ -386,6 +838,11 @@ impl vr::IVRSystem023_Interface for System {
         ty: vr::EHiddenAreaMeshType,
     ) -> vr::HiddenAreaMesh_t {
         if !self.openxr.enabled_extensions.khr_visibility_mask {
+            if ty == vr::EHiddenAreaMeshType::Standard
+                && self.fallback_hidden_area.load(Ordering::Relaxed)
+            {
+                return self.fallback_hidden_area_mesh(eye);
+            }
             return Default::default();
         }
 
@@ -483,6 +940,16 @@ impl vr::IVRSystem023_Interface for System {
         size: u32,
         pose: *mut vr::TrackedDevicePose_t,
     ) -> bool {
+        if self.openxr.take_quit_request() {
+            return write_quit_event(event, size, pose);
+        }
+
+        if let Some(ty) = self.openxr.take_space_change_event() {
+            // Views located before the change are relative to the old space.
+            self.reset_views();
+            return write_space_change_event(ty, event, size, pose);
+        }
+
         let Some(input) = self.input.get() else {
             return false;
         };
@@ -547,13 +1014,26 @@ impl vr::IVRSystem023_Interface for System {
         };
 
         let data = match device_index {
+            // We never have firmware updates to offer, for any device.
+            _ if prop == vr::ETrackedDeviceProperty::Firmware_ManualUpdateURL_String => Some(c""),
             vr::k_unTrackedDeviceIndex_Hmd => match prop {
                 // The Unity OpenVR sample appears to have a hard requirement on these first three properties returning
                 // something to even get the game to recognize the HMD's location. However, the value
                 // itself doesn't appear to be that important.
                 vr::ETrackedDeviceProperty::SerialNumber_String
-                | vr::ETrackedDeviceProperty::ManufacturerName_String
                 | vr::ETrackedDeviceProperty::ControllerType_String => Some(c"<unknown>"),
+                vr::ETrackedDeviceProperty::ManufacturerName_String => Some(
+                    self.hmd_model
+                        .lock()
+                        .unwrap()
+                        .map_or(c"<unknown>", HmdModel::manufacturer_name),
+                ),
+                vr::ETrackedDeviceProperty::ModelNumber_String => {
+                    self.hmd_model.lock().unwrap().map(HmdModel::model_number)
+                }
+                // The runtime handles audio, so we don't know which devices belong to the headset.
+                vr::ETrackedDeviceProperty::Audio_DefaultPlaybackDeviceId_String
+                | vr::ETrackedDeviceProperty::Audio_DefaultRecordingDeviceId_String => Some(c""),
                 _ => None,
             },
             x => input
@@ -582,14 +1062,22 @@ impl vr::IVRSystem023_Interface for System {
     }
     fn GetArrayTrackedDeviceProperty(
         &self,
-        _: vr::TrackedDeviceIndex_t,
-        _: vr::ETrackedDeviceProperty,
+        device_index: vr::TrackedDeviceIndex_t,
+        prop: vr::ETrackedDeviceProperty,
         _: vr::PropertyTypeTag_t,
         _: *mut std::os::raw::c_void,
         _: u32,
-        _: *mut vr::ETrackedPropertyError,
+        err: *mut vr::ETrackedPropertyError,
     ) -> u32 {
-        todo!()
+        debug!(target: log_tags::TRACKED_PROP, "requesting array property: {prop:?} ({device_index})");
+        if let Some(err) = unsafe { err.as_mut() } {
+            *err = if self.IsTrackedDeviceConnected(device_index) {
+                vr::ETrackedPropertyError::UnknownProperty
+            } else {
+                vr::ETrackedPropertyError::InvalidDevice
+            };
+        }
+        0
     }
     fn GetMatrix34TrackedDeviceProperty(
         &self,
@@ -628,22 +1116,29 @@ impl vr::IVRSystem023_Interface for System {
             *err = vr::ETrackedPropertyError::Success;
         }
 
-        self.input
-            .get()
-            .and_then(
-                |input| match input.device_index_to_device_type(device_index) {
+        match device_index {
+            vr::k_unTrackedDeviceIndex_Hmd => match prop {
+                vr::ETrackedDeviceProperty::AudioFirmwareVersion_Uint64
+                | vr::ETrackedDeviceProperty::AudioBridgeFirmwareVersion_Uint64 => Some(0),
+                vr::ETrackedDeviceProperty::CurrentUniverseId_Uint64
+                | vr::ETrackedDeviceProperty::PreviousUniverseId_Uint64 => Some(UNIVERSE_ID),
+                _ => None,
+            },
+            _ => self.input.get().and_then(|input| {
+                match input.device_index_to_device_type(device_index) {
                     Some(TrackedDeviceType::Controller { hand }) => {
                         input.get_controller_uint_tracked_property(hand, prop)
                     }
                     _ => None,
-                },
-            )
-            .unwrap_or_else(|| {
-                if let Some(err) = unsafe { err.as_mut() } {
-                    *err = vr::ETrackedPropertyError::UnknownProperty;
                 }
-                0
-            })
+            }),
+        }
+        .unwrap_or_else(|| {
+            if let Some(err) = unsafe { err.as_mut() } {
+                *err = vr::ETrackedPropertyError::UnknownProperty;
+            }
+            0
+        })
     }
     fn GetInt32TrackedDeviceProperty(
         &self,
@@ -662,20 +1157,23 @@ impl vr::IVRSystem023_Interface for System {
         if let Some(err) = unsafe { err.as_mut() } {
             *err = vr::ETrackedPropertyError::Success;
         }
-        self.input
-            .get()
-            .and_then(|input| {
+        match prop {
+            vr::ETrackedDeviceProperty::DeviceClass_Int32 => {
+                Some(self.GetTrackedDeviceClass(device_index) as i32)
+            }
+            _ => self.input.get().and_then(|input| {
                 input.get_controller_int_tracked_property(
                     input.device_index_to_hand(device_index)?,
                     prop,
                 )
-            })
-            .unwrap_or_else(|| {
-                if let Some(err) = unsafe { err.as_mut() } {
-                    *err = vr::ETrackedPropertyError::UnknownProperty;
-                }
-                0
-            })
+            }),
+        }
+        .unwrap_or_else(|| {
+            if let Some(err) = unsafe { err.as_mut() } {
+                *err = vr::ETrackedPropertyError::UnknownProperty;
+            }
+            0
+        })
     }
     fn GetFloatTrackedDeviceProperty(
         &self,
@@ -684,6 +1182,13 @@ impl vr::IVRSystem023_Interface for System {
         error: *mut vr::ETrackedPropertyError,
     ) -> f32 {
         debug!(target: log_tags::TRACKED_PROP, "requesting float property: {prop:?} ({device_index})");
+        if !self.IsTrackedDeviceConnected(device_index) {
+            if let Some(error) = unsafe { error.as_mut() } {
+                *error = vr::ETrackedPropertyError::InvalidDevice;
+            }
+            return 0.0;
+        }
+
         if device_index != vr::k_unTrackedDeviceIndex_Hmd {
             if let Some(error) = unsafe { error.as_mut() } {
                 *error = vr::ETrackedPropertyError::UnknownProperty;
@@ -691,12 +1196,36 @@ impl vr::IVRSystem023_Interface for System {
             return 0.0;
         }
 
+        if let Some(error) = unsafe { error.as_mut() } {
+            *error = vr::ETrackedPropertyError::Success;
+        }
         match prop {
             vr::ETrackedDeviceProperty::UserIpdMeters_Float => {
                 let views = self.get_views(xr::ReferenceSpaceType::VIEW).views;
                 views[1].pose.position.x - views[0].pose.position.x
             }
-            vr::ETrackedDeviceProperty::DisplayFrequency_Float => 90.0,
+            vr::ETrackedDeviceProperty::DisplayFrequency_Float => self
+                .openxr
+                .frame_metrics
+                .display_period()
+                .map(|period| (1.0 / period) as f32)
+                .unwrap_or(90.0),
+            // OpenXR doesn't expose the runtime's display latency, so assume photons are emitted
+            // about one display period after vsync, as is typical for low persistence displays.
+            vr::ETrackedDeviceProperty::SecondsFromVsyncToPhotons_Float => self
+                .openxr
+                .frame_metrics
+                .display_period()
+                .map(|period| period as f32)
+                .unwrap_or(0.011),
+            // The runtime does its own display correction, so report values that leave the image
+            // untouched - some engines apply a broken correction with zeroes here.
+            vr::ETrackedDeviceProperty::DisplayMCOffset_Float
+            | vr::ETrackedDeviceProperty::DisplayGCBlackClamp_Float
+            | vr::ETrackedDeviceProperty::DisplayGCOffset_Float => 0.0,
+            vr::ETrackedDeviceProperty::DisplayMCScale_Float
+            | vr::ETrackedDeviceProperty::DisplayGCScale_Float
+            | vr::ETrackedDeviceProperty::DisplayGCPrescale_Float => 1.0,
             _ => {
                 if let Some(error) = unsafe { error.as_mut() } {
                     *error = vr::ETrackedPropertyError::UnknownProperty;
@@ -712,10 +1241,51 @@ impl vr::IVRSystem023_Interface for System {
         err: *mut vr::ETrackedPropertyError,
     ) -> bool {
         debug!(target: log_tags::TRACKED_PROP, "requesting bool property: {prop:?} ({device_index})");
+        if !self.IsTrackedDeviceConnected(device_index) {
+            if let Some(err) = unsafe { err.as_mut() } {
+                *err = vr::ETrackedPropertyError::InvalidDevice;
+            }
+            return false;
+        }
+
         if let Some(err) = unsafe { err.as_mut() } {
-            *err = vr::ETrackedPropertyError::UnknownProperty;
+            *err = vr::ETrackedPropertyError::Success;
         }
-        false
+        match device_index {
+            // We never have firmware updates to offer, for any device.
+            _ if matches!(
+                prop,
+                vr::ETrackedDeviceProperty::Firmware_UpdateAvailable_Bool
+                    | vr::ETrackedDeviceProperty::Firmware_ManualUpdate_Bool
+            ) =>
+            {
+                Some(false)
+            }
+            vr::k_unTrackedDeviceIndex_Hmd => match prop {
+                vr::ETrackedDeviceProperty::DeviceCanPowerOff_Bool => Some(false),
+                // OpenXR can't tell us about wireless adapters, so assume a tethered headset.
+                vr::ETrackedDeviceProperty::DeviceIsWireless_Bool => Some(false),
+                vr::ETrackedDeviceProperty::ContainsProximitySensor_Bool => {
+                    Some(self.openxr.enabled_extensions.ext_user_presence)
+                }
+                vr::ETrackedDeviceProperty::Audio_SupportsDualSpeakerAndJackOutput_Bool => {
+                    Some(false)
+                }
+                _ => None,
+            },
+            _ => self.input.get().and_then(|input| {
+                input.get_controller_bool_tracked_property(
+                    input.device_index_to_hand(device_index)?,
+                    prop,
+                )
+            }),
+        }
+        .unwrap_or_else(|| {
+            if let Some(err) = unsafe { err.as_mut() } {
+                *err = vr::ETrackedPropertyError::UnknownProperty;
+            }
+            false
+        })
     }
 
     fn IsTrackedDeviceConnected(&self, device_index: vr::TrackedDeviceIndex_t) -> bool {
@@ -752,7 +1322,7 @@ impl vr::IVRSystem023_Interface for System {
             return vr::ETrackedControllerRole::Invalid;
         };
         input
-            .device_index_to_hand(index)
+            .device_index_to_role(index)
             .map_or(vr::ETrackedControllerRole::Invalid, |hand| hand.into())
     }
 
@@ -766,7 +1336,7 @@ impl vr::IVRSystem023_Interface for System {
 
         Hand::try_from(role).map_or(vr::k_unTrackedDeviceIndexInvalid, |hand| {
             input
-                .get_controller_device_index(hand)
+                .get_controller_device_index_for_role(hand)
                 .unwrap_or(vr::k_unTrackedDeviceIndexInvalid)
         })
     }
@@ -782,20 +1352,22 @@ impl vr::IVRSystem023_Interface for System {
         &self,
         device_index: vr::TrackedDeviceIndex_t,
     ) -> vr::EDeviceActivityLevel {
-        match device_index {
-            vr::k_unTrackedDeviceIndex_Hmd => vr::EDeviceActivityLevel::UserInteraction,
-            x if self
-                .input
-                .get()
-                .is_some_and(|input| input.device_index_to_hand(x).is_some()) =>
-            {
-                if self.IsTrackedDeviceConnected(x) {
-                    vr::EDeviceActivityLevel::UserInteraction
-                } else {
-                    vr::EDeviceActivityLevel::Unknown
-                }
+        let level = self
+            .input
+            .get()
+            .and_then(|input| input.get_device_activity_level(device_index));
+
+        match level {
+            // The proximity sensor tells us more than the HMD's pose does.
+            _ if device_index == vr::k_unTrackedDeviceIndex_Hmd && !self.openxr.user_present() => {
+                vr::EDeviceActivityLevel::Standby
             }
-            _ => vr::EDeviceActivityLevel::Unknown,
+            Some(level) => level,
+            // Without input, we haven't been tracking the HMD's pose.
+            None if device_index == vr::k_unTrackedDeviceIndex_Hmd => {
+                vr::EDeviceActivityLevel::UserInteraction
+            }
+            None => vr::EDeviceActivityLevel::Unknown,
         }
     }
     fn GetSortedTrackedDeviceIndicesOfClass(
@@ -816,15 +1388,23 @@ impl vr::IVRSystem023_Interface for System {
     fn GetDeviceToAbsoluteTrackingPose(
         &self,
         origin: vr::ETrackingUniverseOrigin,
-        _seconds_to_photon_from_now: f32,
+        seconds_to_photon_from_now: f32,
         pose_array: *mut vr::TrackedDevicePose_t,
         pose_count: u32,
     ) {
+        // The offset is relative to the frame's predicted display time rather than the actual
+        // current time, so apps aligning with predicted_display_time get consistent poses.
+        let predicted_time = (seconds_to_photon_from_now != 0.0).then(|| {
+            let seconds = self.predicted_display_time() + seconds_to_photon_from_now as f64;
+            xr::Time::from_nanos((seconds * 1e9).round() as i64)
+        });
+
         self.input
             .force(|_| Input::new(self.openxr.clone()))
             .get_poses(
                 unsafe { std::slice::from_raw_parts_mut(pose_array, pose_count as usize) },
                 Some(origin),
+                predicted_time,
             );
     }
     fn SetDisplayVisibility(&self, _: bool) -> bool {
@@ -832,8 +1412,7 @@ impl vr::IVRSystem023_Interface for System {
         false
     }
     fn IsDisplayOnDesktop(&self) -> bool {
-        // Direct mode
-        false
+        self.capabilities().display_on_desktop()
     }
     fn GetOutputDevice(
         &self,
@@ -856,11 +1435,18 @@ impl vr::IVRSystem023_Interface for System {
                 .expect("Failed to get vulkan physical device") as _;
         }
     }
-    fn GetDXGIOutputInfo(&self, _: *mut i32) {
-        todo!()
+    fn GetDXGIOutputInfo(&self, adapter_index: *mut i32) {
+        // We never enable XR_KHR_D3D11_enable - D3D games are translated to Vulkan (DXVK/vkd3d),
+        // so there's no LUID to match against. The translated adapter for the GPU the runtime uses
+        // is the first one, so just report that.
+        crate::warn_once!("D3D11 is not supported, reporting default DXGI adapter");
+        if let Some(index) = unsafe { adapter_index.as_mut() } {
+            *index = 0;
+        }
     }
     fn GetD3D9AdapterIndex(&self) -> i32 {
-        todo!()
+        // No D3D9 support.
+        -1
     }
 }
 
@@ -898,12 +1484,21 @@ impl vr::IVRSystem017On019 for System {
 }
 
 impl vr::IVRSystem016On017 for System {
-    fn GetOutputDevice(&self, _device: *mut u64, _texture_type: vr::ETextureType) {
-        // TODO: figure out what to pass for the instance...
-        todo!()
-    }
-}
-
+    fn GetOutputDevice(&self, device: *mut u64, texture_type: vr::ETextureType) {
+        let Some(instance) = self.openxr.vulkan_instance() else {
+            error!("No Vulkan instance available for GetOutputDevice");
+            return;
+        };
+
+        <Self as vr::IVRSystem022_Interface>::GetOutputDevice(
+            self,
+            device,
+            texture_type,
+            instance as _,
+        );
+    }
+}
+
 impl vr::IVRSystem014On015 for System {
     fn GetProjectionMatrix(
         &self,
@@ -1026,6 +1621,852 @@ mod tests {
     use std::ffi::CStr;
     use vr::IVRSystem022_Interface;
 
+    #[test]
+    fn vsync_to_photons_is_sane() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let system = System::new(xr, &Injector::default());
+
+        let mut err = vr::ETrackedPropertyError::Success;
+        let seconds = system.GetFloatTrackedDeviceProperty(
+            vr::k_unTrackedDeviceIndex_Hmd,
+            vr::ETrackedDeviceProperty::SecondsFromVsyncToPhotons_Float,
+            &mut err,
+        );
+        assert_eq!(err, vr::ETrackedPropertyError::Success);
+        assert!(seconds.is_finite() && seconds > 0.0, "{seconds}");
+    }
+
+    #[test]
+    fn display_correction_properties_are_neutral() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let system = System::new(xr, &Injector::default());
+
+        for (prop, expected) in [
+            (vr::ETrackedDeviceProperty::DisplayMCOffset_Float, 0.0),
+            (vr::ETrackedDeviceProperty::DisplayMCScale_Float, 1.0),
+            (vr::ETrackedDeviceProperty::DisplayGCBlackClamp_Float, 0.0),
+            (vr::ETrackedDeviceProperty::DisplayGCOffset_Float, 0.0),
+            (vr::ETrackedDeviceProperty::DisplayGCScale_Float, 1.0),
+            (vr::ETrackedDeviceProperty::DisplayGCPrescale_Float, 1.0),
+        ] {
+            let mut err = vr::ETrackedPropertyError::UnknownProperty;
+            let value = system.GetFloatTrackedDeviceProperty(
+                vr::k_unTrackedDeviceIndex_Hmd,
+                prop,
+                &mut err,
+            );
+            assert_eq!(err, vr::ETrackedPropertyError::Success, "{prop:?}");
+            assert_eq!(value, expected, "{prop:?}");
+        }
+    }
+
+    #[test]
+    fn strict_mode_panics_on_unimplemented() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let system = System::new(xr, &Injector::default());
+        let get_name = || {
+            let name =
+                system.GetControllerAxisTypeNameFromEnum(vr::EVRControllerAxisType::TrackPad);
+            unsafe { CStr::from_ptr(name) }
+        };
+
+        assert_eq!(get_name(), c"Unknown");
+
+        crate::set_strict_mode(true);
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(get_name));
+        crate::set_strict_mode(false);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn acknowledge_quit_exiting_ends_session() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let system = System::new(xr.clone(), &Injector::default());
+
+        system.AcknowledgeQuit_Exiting();
+        system.AcknowledgeQuit_Exiting();
+        xr.poll_events();
+        assert_eq!(xr.session_data.get().state, xr::SessionState::STOPPING);
+
+        // No running session anymore, this shouldn't do anything.
+        system.AcknowledgeQuit_Exiting();
+    }
+
+    #[test]
+    fn session_stopping_sends_quit_event() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let system = System::new(xr.clone(), &Injector::default());
+
+        let mut event = vr::VREvent_t::default();
+        let size = std::mem::size_of_val(&event) as u32;
+        assert!(!system.PollNextEvent(&mut event, size));
+
+        fakexr::stop_session(xr.session_data.get().session.as_raw());
+        xr.poll_events();
+
+        assert!(system.PollNextEvent(&mut event, size));
+        assert_eq!(event.eventType, vr::EVREventType::Quit as u32);
+        assert_eq!(unsafe { event.data.process.pid }, std::process::id());
+        // Only sent once
+        assert!(!system.PollNextEvent(&mut event, size));
+
+        // The app acknowledging the quit shouldn't try to exit an already stopping session.
+        system.AcknowledgeQuit_Exiting();
+    }
+
+    #[test]
+    fn reference_space_change_sends_event() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let system = System::new(xr.clone(), &Injector::default());
+        let session = xr.session_data.get().session.as_raw();
+        let seated_space = || {
+            xr.session_data
+                .get()
+                .get_space_for_origin(vr::ETrackingUniverseOrigin::Seated)
+                .as_raw()
+        };
+
+        let mut event = vr::VREvent_t::default();
+        let size = std::mem::size_of_val(&event) as u32;
+        xr.reset_tracking_space(vr::ETrackingUniverseOrigin::Seated);
+        let recentered = seated_space();
+        system.get_views(xr::ReferenceSpaceType::LOCAL);
+        let located = system.frame_stats().count;
+
+        fakexr::change_reference_space(session, xr::ReferenceSpaceType::LOCAL);
+        xr.poll_events();
+        // Our recenter offset is relative to the old space, so it should be dropped
+        assert_ne!(seated_space(), recentered);
+
+        assert!(system.PollNextEvent(&mut event, size));
+        assert_eq!(
+            event.eventType,
+            vr::EVREventType::SeatedZeroPoseReset as u32
+        );
+        assert!(system.frame_stats().count > located);
+        assert!(!system.PollNextEvent(&mut event, size));
+
+        fakexr::change_reference_space(session, xr::ReferenceSpaceType::STAGE);
+        xr.poll_events();
+        assert!(system.PollNextEvent(&mut event, size));
+        assert_eq!(
+            event.eventType,
+            vr::EVREventType::ChaperoneUniverseHasChanged as u32
+        );
+        assert!(!system.PollNextEvent(&mut event, size));
+    }
+
+    #[test]
+    fn system_capabilities_defaults() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let system = System::new(xr, &Injector::default());
+
+        assert!(!system.IsDisplayOnDesktop());
+        assert!(!system.IsSteamVRDrawingControllers());
+        assert!(!system.ShouldApplicationReduceRenderingWork());
+    }
+
+    #[test]
+    fn reduce_rendering_work_while_unfocused() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let system = System::new(xr.clone(), &Injector::default());
+        let session = xr.session_data.get().session.as_raw();
+
+        fakexr::set_session_state(session, xr::SessionState::VISIBLE);
+        xr.poll_events();
+        assert!(system.ShouldApplicationReduceRenderingWork());
+
+        fakexr::set_session_state(session, xr::SessionState::FOCUSED);
+        xr.poll_events();
+        assert!(!system.ShouldApplicationReduceRenderingWork());
+    }
+
+    #[test]
+    fn input_unavailable_while_unfocused() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let system = System::new(xr.clone(), &Injector::default());
+        let session = xr.session_data.get().session.as_raw();
+
+        fakexr::set_session_state(session, xr::SessionState::FOCUSED);
+        xr.poll_events();
+        assert!(system.IsInputAvailable());
+
+        fakexr::set_session_state(session, xr::SessionState::VISIBLE);
+        xr.poll_events();
+        assert!(!system.IsInputAvailable());
+        // Input going away doesn't pause the app - it keeps rendering under the dashboard.
+        assert!(!system.ShouldApplicationPause());
+
+        fakexr::set_session_state(session, xr::SessionState::FOCUSED);
+        xr.poll_events();
+        assert!(system.IsInputAvailable());
+    }
+
+    #[test]
+    fn predicted_display_time_matches_frame() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let system = System::new(xr.clone(), &Injector::default());
+
+        xr.display_time.set(xr::Time::from_nanos(2_500_000_000));
+        assert_eq!(system.predicted_display_time(), 2.5);
+    }
+
+    #[test]
+    fn force_seated_uses_local_space() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let system = System::new(xr.clone(), &Injector::default());
+        xr.set_tracking_space(vr::ETrackingUniverseOrigin::Standing);
+        assert_eq!(
+            xr.session_data.get().current_origin_as_reference_space(),
+            xr::ReferenceSpaceType::STAGE
+        );
+
+        xr.set_force_seated(true);
+        let session = xr.session_data.get();
+        assert_eq!(
+            session.current_origin_as_reference_space(),
+            xr::ReferenceSpaceType::LOCAL
+        );
+        assert_eq!(
+            session
+                .get_space_for_origin(vr::ETrackingUniverseOrigin::Standing)
+                .as_raw(),
+            session
+                .get_space_for_origin(vr::ETrackingUniverseOrigin::Seated)
+                .as_raw()
+        );
+        let ty = session.current_origin_as_reference_space();
+        drop(session);
+
+        system.get_views(ty);
+        let views = system.views.lock().unwrap();
+        assert!(views.local.is_some());
+        assert!(views.stage.is_none());
+    }
+
+    #[test]
+    fn projection_fov_is_origin_independent() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let system = System::new(xr.clone(), &Injector::default());
+
+        let projection = |eye| {
+            let [mut left, mut right, mut top, mut bottom] = [0.0; 4];
+            system.GetProjectionRaw(eye, &mut left, &mut right, &mut top, &mut bottom);
+            [left, right, top, bottom]
+        };
+        let eye_to_head = |eye| system.GetEyeToHeadTransform(eye).m;
+
+        let mut results = Vec::new();
+        for origin in [
+            vr::ETrackingUniverseOrigin::Seated,
+            vr::ETrackingUniverseOrigin::Standing,
+        ] {
+            xr.set_tracking_space(origin);
+            system.reset_views();
+            let ty = xr.session_data.get().current_origin_as_reference_space();
+            let origin_views = system.get_views(ty).views;
+            let view_space_views = system.get_views(xr::ReferenceSpaceType::VIEW).views;
+
+            for (origin_view, view) in origin_views.iter().zip(&view_space_views) {
+                assert_eq!(origin_view.fov, view.fov);
+            }
+            results.push((
+                projection(vr::EVREye::Left),
+                projection(vr::EVREye::Right),
+                eye_to_head(vr::EVREye::Left),
+                eye_to_head(vr::EVREye::Right),
+            ));
+        }
+
+        assert_eq!(results[0], results[1]);
+    }
+
+    #[test]
+    fn ipd_offset() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let system = System::new(xr, &Injector::default());
+
+        let eye_distance = || {
+            let left = system.GetEyeToHeadTransform(vr::EVREye::Left).m;
+            let right = system.GetEyeToHeadTransform(vr::EVREye::Right).m;
+            Vec3::new(right[0][3], right[1][3], right[2][3])
+                .distance(Vec3::new(left[0][3], left[1][3], left[2][3]))
+        };
+
+        let original = eye_distance();
+        system.set_ipd_offset(2.0);
+        let adjusted = eye_distance();
+        assert!(
+            (adjusted - original - 0.002).abs() < 1e-6,
+            "expected {original} + 0.002, got {adjusted}"
+        );
+
+        // Clamped to 10mm
+        system.set_ipd_offset(50.0);
+        assert!((eye_distance() - original - 0.01).abs() < 1e-6);
+    }
+
+    #[test]
+    fn fallback_hidden_area_mesh() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let system = System::new(xr.clone(), &Injector::default());
+        assert!(!xr.enabled_extensions.khr_visibility_mask);
+
+        let fov = xr::Fovf {
+            angle_left: -0.9,
+            angle_right: 0.8,
+            angle_up: 0.85,
+            angle_down: -0.95,
+        };
+        fakexr::set_view_fovs(xr.session_data.get().session.as_raw(), [fov; 2]);
+
+        let mesh = system.GetHiddenAreaMesh(vr::EVREye::Left, vr::EHiddenAreaMeshType::Standard);
+        assert!(mesh.pVertexData.is_null());
+        assert_eq!(mesh.unTriangleCount, 0);
+
+        system.set_fallback_hidden_area(true);
+        let mesh = system.GetHiddenAreaMesh(vr::EVREye::Left, vr::EHiddenAreaMeshType::Standard);
+        assert_eq!(mesh.unTriangleCount, 4);
+        let vertices = unsafe {
+            std::slice::from_raw_parts(mesh.pVertexData, mesh.unTriangleCount as usize * 3)
+        };
+        for vertex in vertices {
+            for coord in vertex.v {
+                assert!((0.0..=1.0).contains(&coord), "{coord} is outside the image");
+            }
+        }
+
+        // Cached after the first call
+        let again = system.GetHiddenAreaMesh(vr::EVREye::Left, vr::EHiddenAreaMeshType::Standard);
+        assert_eq!(again.pVertexData, mesh.pVertexData);
+    }
+
+    #[test]
+    fn firmware_update_properties() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let input = Arc::new(Input::new(xr.clone()));
+        let system = System::new(xr, &Injector::default());
+        system.input.set(Arc::downgrade(&input));
+
+        for prop in [
+            vr::ETrackedDeviceProperty::Firmware_UpdateAvailable_Bool,
+            vr::ETrackedDeviceProperty::Firmware_ManualUpdate_Bool,
+        ] {
+            let mut err = vr::ETrackedPropertyError::UnknownProperty;
+            assert!(!system.GetBoolTrackedDeviceProperty(
+                vr::k_unTrackedDeviceIndex_Hmd,
+                prop,
+                &mut err
+            ));
+            assert_eq!(err, vr::ETrackedPropertyError::Success, "{prop:?}");
+        }
+
+        let mut buf = [1; 8];
+        let mut err = vr::ETrackedPropertyError::UnknownProperty;
+        let len = system.GetStringTrackedDeviceProperty(
+            vr::k_unTrackedDeviceIndex_Hmd,
+            vr::ETrackedDeviceProperty::Firmware_ManualUpdateURL_String,
+            buf.as_mut_ptr(),
+            buf.len() as u32,
+            &mut err,
+        );
+        assert_eq!(err, vr::ETrackedPropertyError::Success);
+        assert_eq!(len, 1);
+        assert_eq!(buf[0], 0);
+    }
+
+    #[test]
+    fn audio_properties() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let input = Arc::new(Input::new(xr.clone()));
+        let system = System::new(xr, &Injector::default());
+        system.input.set(Arc::downgrade(&input));
+        let hmd = vr::k_unTrackedDeviceIndex_Hmd;
+
+        for prop in [
+            vr::ETrackedDeviceProperty::Audio_DefaultPlaybackDeviceId_String,
+            vr::ETrackedDeviceProperty::Audio_DefaultRecordingDeviceId_String,
+        ] {
+            let mut buf = [1; 8];
+            let mut err = vr::ETrackedPropertyError::UnknownProperty;
+            let len = system.GetStringTrackedDeviceProperty(
+                hmd,
+                prop,
+                buf.as_mut_ptr(),
+                buf.len() as u32,
+                &mut err,
+            );
+            assert_eq!(err, vr::ETrackedPropertyError::Success, "{prop:?}");
+            assert_eq!(len, 1);
+            assert_eq!(buf[0], 0);
+        }
+
+        for prop in [
+            vr::ETrackedDeviceProperty::AudioFirmwareVersion_Uint64,
+            vr::ETrackedDeviceProperty::AudioBridgeFirmwareVersion_Uint64,
+        ] {
+            let mut err = vr::ETrackedPropertyError::UnknownProperty;
+            assert_eq!(
+                system.GetUint64TrackedDeviceProperty(hmd, prop, &mut err),
+                0
+            );
+            assert_eq!(err, vr::ETrackedPropertyError::Success, "{prop:?}");
+        }
+
+        let mut err = vr::ETrackedPropertyError::UnknownProperty;
+        assert!(!system.GetBoolTrackedDeviceProperty(
+            hmd,
+            vr::ETrackedDeviceProperty::Audio_SupportsDualSpeakerAndJackOutput_Bool,
+            &mut err
+        ));
+        assert_eq!(err, vr::ETrackedPropertyError::Success);
+    }
+
+    #[test]
+    fn firmware_update_is_noop() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let system = System::new(xr, &Injector::default());
+
+        assert_eq!(
+            system.PerformFirmwareUpdate(vr::k_unTrackedDeviceIndex_Hmd),
+            vr::EVRFirmwareError::None
+        );
+    }
+
+    #[test]
+    fn app_container_file_paths_empty() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let system = System::new(xr, &Injector::default());
+
+        assert_eq!(system.GetAppContainerFilePaths(std::ptr::null_mut(), 0), 0);
+
+        let mut buf = [b'a' as std::os::raw::c_char; 8];
+        assert_eq!(
+            system.GetAppContainerFilePaths(buf.as_mut_ptr(), buf.len() as u32),
+            0
+        );
+        assert_eq!(buf[0], 0);
+    }
+
+    #[test]
+    fn device_class_property_matches_device_class() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let input = Arc::new(Input::new(xr.clone()));
+        let system = System::new(xr, &Injector::default());
+        system.input.set(Arc::downgrade(&input));
+        input.force_connect_controllers();
+
+        for index in 0..3 {
+            let class = system.GetTrackedDeviceClass(index);
+            assert_ne!(class, vr::ETrackedDeviceClass::Invalid, "{index}");
+
+            let mut err = vr::ETrackedPropertyError::UnknownProperty;
+            let property = system.GetInt32TrackedDeviceProperty(
+                index,
+                vr::ETrackedDeviceProperty::DeviceClass_Int32,
+                &mut err,
+            );
+            assert_eq!(err, vr::ETrackedPropertyError::Success, "{index}");
+            assert_eq!(property, class as i32, "{index}");
+        }
+
+        let mut err = vr::ETrackedPropertyError::UnknownProperty;
+        assert!(!system.GetBoolTrackedDeviceProperty(
+            vr::k_unTrackedDeviceIndex_Hmd,
+            vr::ETrackedDeviceProperty::DeviceIsWireless_Bool,
+            &mut err
+        ));
+        assert_eq!(err, vr::ETrackedPropertyError::Success);
+    }
+
+    #[test]
+    fn device_class_for_unused_indices() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let input = Arc::new(Input::new(xr.clone()));
+        let system = System::new(xr, &Injector::default());
+        system.input.set(Arc::downgrade(&input));
+
+        assert_eq!(
+            system.GetTrackedDeviceClass(vr::k_unTrackedDeviceIndex_Hmd),
+            vr::ETrackedDeviceClass::HMD
+        );
+        for index in [
+            3,
+            vr::k_unMaxTrackedDeviceCount - 1,
+            vr::k_unMaxTrackedDeviceCount,
+        ] {
+            assert_eq!(
+                system.GetTrackedDeviceClass(index),
+                vr::ETrackedDeviceClass::Invalid,
+                "index {index} should not have a device"
+            );
+        }
+    }
+
+    #[test]
+    fn hmd_model_override() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let input = Arc::new(Input::new(xr.clone()));
+        let system = System::new(xr, &Injector::default());
+        system.input.set(Arc::downgrade(&input));
+
+        let get_prop = |prop| {
+            let mut buf = [0; 64];
+            let mut err = vr::ETrackedPropertyError::Success;
+            system.GetStringTrackedDeviceProperty(
+                vr::k_unTrackedDeviceIndex_Hmd,
+                prop,
+                buf.as_mut_ptr(),
+                buf.len() as u32,
+                &mut err,
+            );
+            (err == vr::ETrackedPropertyError::Success)
+                .then(|| unsafe { CStr::from_ptr(buf.as_ptr()) }.to_owned())
+        };
+
+        assert_eq!(
+            get_prop(vr::ETrackedDeviceProperty::ManufacturerName_String).as_deref(),
+            Some(c"<unknown>")
+        );
+        assert_eq!(
+            get_prop(vr::ETrackedDeviceProperty::ModelNumber_String),
+            None
+        );
+
+        system.set_hmd_model(Some(HmdModel::Index));
+        assert_eq!(
+            get_prop(vr::ETrackedDeviceProperty::ManufacturerName_String).as_deref(),
+            Some(c"Valve")
+        );
+        assert_eq!(
+            get_prop(vr::ETrackedDeviceProperty::ModelNumber_String).as_deref(),
+            Some(c"Index")
+        );
+    }
+
+    #[test]
+    fn runtime_version_override() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let system = System::new(xr, &Injector::default());
+
+        let version = unsafe { CStr::from_ptr(system.GetRuntimeVersion()) };
+        assert_eq!(version, DEFAULT_RUNTIME_VERSION);
+
+        system.set_runtime_version(c"1.27.5");
+        let version = unsafe { CStr::from_ptr(system.GetRuntimeVersion()) };
+        assert_eq!(version, c"1.27.5");
+        // The same string is returned every call.
+        assert_eq!(system.GetRuntimeVersion(), system.GetRuntimeVersion());
+    }
+
+    #[test]
+    fn symmetric_fov_pads_both_eyes() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let system = System::new(xr.clone(), &Injector::default());
+        let fov = |angle_left, angle_right, angle_up, angle_down| xr::Fovf {
+            angle_left,
+            angle_right,
+            angle_up,
+            angle_down,
+        };
+        fakexr::set_view_fovs(
+            xr.session_data.get().session.as_raw(),
+            [fov(-0.9, 0.7, 0.8, -0.6), fov(-0.7, 0.85, 0.75, -0.65)],
+        );
+        let projection = |eye| {
+            let [mut left, mut right, mut top, mut bottom] = [0.0; 4];
+            system.GetProjectionRaw(eye, &mut left, &mut right, &mut top, &mut bottom);
+            [left, right, top, bottom]
+        };
+
+        let tan = f32::tan;
+        let raw_left = projection(vr::EVREye::Left);
+        assert_eq!(raw_left, [tan(-0.9), tan(0.7), tan(-0.6), tan(0.8)]);
+        assert_ne!(raw_left, projection(vr::EVREye::Right));
+
+        system.set_symmetric_fov(true);
+        let symmetric = projection(vr::EVREye::Left);
+        assert_eq!(symmetric, [tan(-0.9), tan(0.9), tan(-0.8), tan(0.8)]);
+        assert_eq!(symmetric, projection(vr::EVREye::Right));
+
+        // The projection matrix shouldn't be skewed
+        let matrix = system.GetProjectionMatrix(vr::EVREye::Left, 0.1, 100.0).m;
+        assert!(matrix[0][2].abs() < f32::EPSILON);
+        assert!(matrix[1][2].abs() < f32::EPSILON);
+    }
+
+    #[test]
+    fn canted_wide_fov_projection_matrix() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let system = System::new(xr.clone(), &Injector::default());
+
+        // Roughly what a wide FOV Pimax reports: each eye sees much further outwards than inwards.
+        let left_fov = xr::Fovf {
+            angle_left: -1.4,
+            angle_right: 0.75,
+            angle_up: 0.9,
+            angle_down: -0.95,
+        };
+        let right_fov = xr::Fovf {
+            angle_left: -0.75,
+            angle_right: 1.4,
+            ..left_fov
+        };
+        fakexr::set_view_fovs(
+            xr.session_data.get().session.as_raw(),
+            [left_fov, right_fov],
+        );
+
+        let (near, far) = (0.1, 100.0);
+        for (eye, fov) in [(vr::EVREye::Left, left_fov), (vr::EVREye::Right, right_fov)] {
+            let m = system.GetProjectionMatrix(eye, near, far).m;
+            let project = |tan_x: f32, tan_y: f32, distance: f32| {
+                let p = [tan_x * distance, tan_y * distance, -distance, 1.0];
+                let clip: [f32; 4] =
+                    std::array::from_fn(|row| (0..4).map(|col| m[row][col] * p[col]).sum());
+                assert!(
+                    clip[3] > 0.0,
+                    "{eye:?}: point in front of the eye was clipped"
+                );
+                [clip[0] / clip[3], clip[1] / clip[3], clip[2] / clip[3]]
+            };
+            let assert_near = |actual: [f32; 3], expected: [f32; 3]| {
+                assert!(
+                    actual
+                        .iter()
+                        .zip(expected)
+                        .all(|(a, e)| (a - e).abs() < 0.0001),
+                    "{eye:?}: expected {expected:?}, got {actual:?}"
+                );
+            };
+
+            // The edges of the FOV should land on the edges of the image, at any depth.
+            let (left, right) = (fov.angle_left.tan(), fov.angle_right.tan());
+            let (up, down) = (fov.angle_up.tan(), fov.angle_down.tan());
+            let center_x = (left + right) / 2.0;
+            let center_y = (up + down) / 2.0;
+            for distance in [near, 1.0, far] {
+                let depth = project(center_x, center_y, distance)[2];
+                assert_near(project(left, center_y, distance), [-1.0, 0.0, depth]);
+                assert_near(project(right, center_y, distance), [1.0, 0.0, depth]);
+                assert_near(project(center_x, up, distance), [0.0, 1.0, depth]);
+                assert_near(project(center_x, down, distance), [0.0, -1.0, depth]);
+            }
+            assert!(project(center_x, center_y, near)[2].abs() < 0.0001);
+            assert!((project(center_x, center_y, far)[2] - 1.0).abs() < 0.0001);
+        }
+    }
+
+    #[test]
+    fn render_scale_multiplies_recommended_size() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let system = System::new(xr, &Injector::default());
+        let size = || {
+            let [mut width, mut height] = [0; 2];
+            system.GetRecommendedRenderTargetSize(&mut width, &mut height);
+            (width, height)
+        };
+
+        assert_eq!(size(), fakexr::RECOMMENDED_VIEW_SIZE);
+
+        system.set_render_scale(1.5);
+        let (width, height) = fakexr::RECOMMENDED_VIEW_SIZE;
+        assert_eq!(size(), (width * 3 / 2, height * 3 / 2));
+
+        // Clamped to the view configuration max
+        system.set_render_scale(10.0);
+        assert_eq!(size(), fakexr::MAX_VIEW_SIZE);
+    }
+
+    #[test]
+    fn frame_stats_track_locate_views() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let system = System::new(xr, &Injector::default());
+        assert_eq!(system.frame_stats().count, 0);
+
+        system.get_views(xr::ReferenceSpaceType::VIEW);
+        // Cached
+        system.get_views(xr::ReferenceSpaceType::VIEW);
+        assert_eq!(system.frame_stats().count, 1);
+
+        system.get_views(xr::ReferenceSpaceType::STAGE);
+        system.reset_views();
+        let stats = system.frame_stats();
+        // reset_views locates the views for the view space and the current origin again
+        assert_eq!(stats.count, 4);
+        assert!(stats.min <= stats.avg() && stats.avg() <= stats.max);
+        assert!(stats.last <= stats.max);
+    }
+
+    #[test]
+    fn property_getters_check_device() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let system = System::new(xr, &Injector::default());
+
+        // Controllers aren't connected without input.
+        for index in [
+            1,
+            vr::k_unMaxTrackedDeviceCount - 1,
+            vr::k_unTrackedDeviceIndexInvalid,
+        ] {
+            let mut err = vr::ETrackedPropertyError::Success;
+            system.GetUint64TrackedDeviceProperty(
+                index,
+                vr::ETrackedDeviceProperty::SupportedButtons_Uint64,
+                &mut err,
+            );
+            assert_eq!(
+                err,
+                vr::ETrackedPropertyError::InvalidDevice,
+                "uint64 ({index})"
+            );
+
+            let mut err = vr::ETrackedPropertyError::Success;
+            system.GetFloatTrackedDeviceProperty(
+                index,
+                vr::ETrackedDeviceProperty::DeviceBatteryPercentage_Float,
+                &mut err,
+            );
+            assert_eq!(
+                err,
+                vr::ETrackedPropertyError::InvalidDevice,
+                "float ({index})"
+            );
+
+            let mut err = vr::ETrackedPropertyError::Success;
+            system.GetArrayTrackedDeviceProperty(
+                index,
+                vr::ETrackedDeviceProperty::CameraToHeadTransforms_Matrix34_Array,
+                0,
+                std::ptr::null_mut(),
+                0,
+                &mut err,
+            );
+            assert_eq!(
+                err,
+                vr::ETrackedPropertyError::InvalidDevice,
+                "array ({index})"
+            );
+        }
+
+        let mut err = vr::ETrackedPropertyError::InvalidDevice;
+        let version = system.GetUint64TrackedDeviceProperty(
+            vr::k_unTrackedDeviceIndex_Hmd,
+            vr::ETrackedDeviceProperty::AudioFirmwareVersion_Uint64,
+            &mut err,
+        );
+        assert_eq!(err, vr::ETrackedPropertyError::Success);
+        assert_eq!(version, 0);
+
+        let mut err = vr::ETrackedPropertyError::Success;
+        system.GetUint64TrackedDeviceProperty(
+            vr::k_unTrackedDeviceIndex_Hmd,
+            vr::ETrackedDeviceProperty::SupportedButtons_Uint64,
+            &mut err,
+        );
+        assert_eq!(err, vr::ETrackedPropertyError::UnknownProperty);
+    }
+
+    #[test]
+    fn universe_id_is_stable() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let system = System::new(xr.clone(), &Injector::default());
+        let universe_id = || {
+            let mut err = vr::ETrackedPropertyError::UnknownProperty;
+            let id = system.GetUint64TrackedDeviceProperty(
+                vr::k_unTrackedDeviceIndex_Hmd,
+                vr::ETrackedDeviceProperty::CurrentUniverseId_Uint64,
+                &mut err,
+            );
+            assert_eq!(err, vr::ETrackedPropertyError::Success);
+            id
+        };
+
+        let id = universe_id();
+        assert_ne!(id, 0);
+        assert_eq!(universe_id(), id);
+
+        // Changing the play area doesn't change the universe.
+        let session = xr.session_data.get().session.as_raw();
+        fakexr::change_reference_space(session, xr::ReferenceSpaceType::STAGE);
+        xr.poll_events();
+        let mut event = vr::VREvent_t::default();
+        assert!(system.PollNextEvent(&mut event, std::mem::size_of_val(&event) as u32));
+        assert_eq!(
+            event.eventType,
+            vr::EVREventType::ChaperoneUniverseHasChanged as u32
+        );
+        let data = unsafe { event.data.chaperone };
+        assert_eq!(data.m_nPreviousUniverse, id);
+        assert_eq!(data.m_nCurrentUniverse, id);
+        assert_eq!(universe_id(), id);
+    }
+
+    #[test]
+    fn proximity_sensor_requires_user_presence() {
+        for available in [true, false] {
+            let mut xr = OpenXrData::new(&Injector::default()).unwrap();
+            xr.enabled_extensions.ext_user_presence = available;
+            let system = System::new(Arc::new(xr), &Injector::default());
+
+            let mut err = vr::ETrackedPropertyError::UnknownProperty;
+            let contains_sensor = system.GetBoolTrackedDeviceProperty(
+                vr::k_unTrackedDeviceIndex_Hmd,
+                vr::ETrackedDeviceProperty::ContainsProximitySensor_Bool,
+                &mut err,
+            );
+            assert_eq!(err, vr::ETrackedPropertyError::Success);
+            assert_eq!(contains_sensor, available);
+        }
+    }
+
+    #[test]
+    fn user_presence_pauses_app() {
+        let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());
+        let input = Arc::new(Input::new(xr.clone()));
+        xr.input.set(Arc::downgrade(&input));
+        let system = System::new(xr.clone(), &Injector::default());
+        system.input.set(Arc::downgrade(&input));
+
+        let mut err = vr::ETrackedPropertyError::Success;
+        assert!(system.GetBoolTrackedDeviceProperty(
+            vr::k_unTrackedDeviceIndex_Hmd,
+            vr::ETrackedDeviceProperty::ContainsProximitySensor_Bool,
+            &mut err
+        ));
+        assert_eq!(err, vr::ETrackedPropertyError::Success);
+        assert!(!system.ShouldApplicationPause());
+
+        let mut event = vr::VREvent_t::default();
+        let size = std::mem::size_of_val(&event) as u32;
+        let session = xr.session_data.get().session.as_raw();
+        // Flush device activation events
+        while system.PollNextEvent(&mut event, size) {}
+
+        fakexr::set_user_presence(session, false);
+        xr.poll_events();
+        assert!(system.ShouldApplicationPause());
+        assert_eq!(
+            system.GetTrackedDeviceActivityLevel(vr::k_unTrackedDeviceIndex_Hmd),
+            vr::EDeviceActivityLevel::Standby
+        );
+        assert!(system.PollNextEvent(&mut event, size));
+        assert_eq!(
+            event.eventType,
+            vr::EVREventType::TrackedDeviceUserInteractionEnded as u32
+        );
+        assert_eq!(event.trackedDeviceIndex, vr::k_unTrackedDeviceIndex_Hmd);
+
+        fakexr::set_user_presence(session, true);
+        xr.poll_events();
+        assert!(!system.ShouldApplicationPause());
+        assert!(system.PollNextEvent(&mut event, size));
+        assert_eq!(
+            event.eventType,
+            vr::EVREventType::TrackedDeviceUserInteractionStarted as u32
+        );
+    }
+
     #[test]
     fn unity_required_properties() {
         let xr = Arc::new(OpenXrData::new(&Injector::default()).unwrap());